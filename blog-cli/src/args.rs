@@ -1,3 +1,4 @@
+use std::path::PathBuf;
 use uuid::Uuid;
 
 #[derive(clap::Parser, Debug)]
@@ -12,20 +13,67 @@ pub(super) struct Args {
     /// Use gRPC instead of HTTP
     #[clap(short, long)]
     pub grpc: bool,
+
+    /// PEM bundle of additional CA certificates to trust for `https://` addresses
+    #[clap(long, env = "BLOG_CA_CERT")]
+    pub ca_cert: Option<PathBuf>,
+
+    /// Personal access token to use instead of the token saved by `login`
+    #[clap(long, env = "BLOG_TOKEN", hide_env_values = true)]
+    pub token: Option<String>,
 }
 
 #[derive(clap::Subcommand, Debug, Clone)]
 pub(super) enum Command {
     /// Login to the server
-    Login { username: String, password: String },
+    Login {
+        username: String,
+        password: String,
+        /// TOTP or recovery code; prompted for when two-factor authentication is enabled
+        #[clap(short, long)]
+        code: Option<String>,
+        /// Scope requested for the session token, e.g. `posts:read`; all scopes if omitted
+        #[clap(long = "scope")]
+        scopes: Vec<String>,
+    },
+    /// Start two-factor authentication enrollment
+    #[clap(name = "enable-2fa")]
+    EnableTwoFactor,
+    /// Confirm two-factor authentication enrollment with a TOTP code
+    #[clap(name = "confirm-2fa")]
+    ConfirmTwoFactor { code: String },
     /// Register a new user
     RegisterUser {
         username: String,
         password: String,
         email: String,
     },
+    /// Create a personal access token
+    CreateToken {
+        name: String,
+        /// Scope granted to the token, e.g. `posts:write`; can be repeated
+        #[clap(long = "scope", required = true)]
+        scopes: Vec<String>,
+        /// Number of days the token stays valid; never expires if omitted
+        #[clap(long)]
+        expires_in_days: Option<u32>,
+    },
+    /// List personal access tokens
+    ListTokens,
+    /// Revoke a personal access token
+    RevokeToken { id: Uuid },
     /// Create a new post
     CreatePost { title: String, content: String },
+    /// Create several posts at once; pass `--title` and `--content` once per post
+    CreatePosts {
+        #[clap(long = "title", required = true)]
+        titles: Vec<String>,
+        #[clap(long = "content", required = true)]
+        contents: Vec<String>,
+        /// Create either all posts or none of them
+        #[clap(long)]
+        transactional: bool,
+    },
     /// Update a post
     UpdatePost {
         id: Uuid,
@@ -36,6 +84,13 @@ pub(super) enum Command {
     DeletePost { id: Uuid },
     /// Get a post
     GetPost { id: Uuid },
+    /// Get several posts by id
+    GetPosts {
+        #[clap(required = true)]
+        ids: Vec<Uuid>,
+    },
     /// Get a list of posts
     GetPostsList { limit: usize, offset: usize },
+    /// Print post changes as they happen; requires gRPC
+    Watch,
 }
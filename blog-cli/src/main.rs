@@ -1,6 +1,8 @@
-use blog_client::{BlogClient, CreatePostCommand};
+use blog_client::{BlogClient, CreatePostCommand, LoginResult};
 use clap::Parser;
+use std::io::Write;
 use std::path::PathBuf;
+use tokio_stream::StreamExt;
 
 const TOKEN_FILE: &str = ".blog_token";
 const HOME_ENV: &str = "HOME";
@@ -11,7 +13,7 @@ mod args;
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     let args = args::Args::parse();
-    let address = if args.address.starts_with("http://") {
+    let address = if args.address.starts_with("http://") || args.address.starts_with("https://") {
         args.address.to_string()
     } else {
         format!("http://{}", args.address)
@@ -21,16 +23,63 @@ async fn main() -> anyhow::Result<()> {
     } else {
         blog_client::Protocol::Http(address)
     };
-    let mut client = blog_client::Client::new(protocol).await?;
+    let mut client = match &args.ca_cert {
+        Some(ca_cert) => {
+            blog_client::Client::with_ca_certificate(protocol, &std::fs::read(ca_cert)?).await?
+        }
+        None => blog_client::Client::new(protocol).await?,
+    };
+    let token = args.token;
 
     match args.command {
-        args::Command::Login { username, password } => {
-            let token = client
-                .login(blog_client::LoginCommand::new(username, password))
-                .await?;
+        args::Command::Login {
+            username,
+            password,
+            code,
+            scopes,
+        } => {
+            let token = match client
+                .login(blog_client::LoginCommand::new(username, password).with_scopes(scopes))
+                .await?
+            {
+                LoginResult::Token(token) => token,
+                LoginResult::TwoFactorRequired(challenge_token) => {
+                    let code = match code {
+                        Some(code) => code,
+                        None => read_code()?,
+                    };
+                    client
+                        .verify_two_factor(blog_client::VerifyTwoFactorCommand::new(
+                            challenge_token,
+                            code,
+                        ))
+                        .await?
+                }
+            };
             save_token(&token)?;
             println!("login successful");
         }
+        args::Command::EnableTwoFactor => {
+            let token = read_token(token)?;
+            let enrollment = client
+                .enroll_two_factor(blog_client::AuthorizedCommand::new(
+                    blog_client::EnrollTwoFactorCommand::new(),
+                    token.as_str(),
+                ))
+                .await?;
+            println!("{}", enrollment);
+            println!("add the URI to your authenticator app and run `blog-cli confirm-2fa <code>`");
+        }
+        args::Command::ConfirmTwoFactor { code } => {
+            let token = read_token(token)?;
+            client
+                .confirm_two_factor(blog_client::AuthorizedCommand::new(
+                    blog_client::ConfirmTwoFactorCommand::new(code),
+                    token.as_str(),
+                ))
+                .await?;
+            println!("two-factor authentication enabled");
+        }
         args::Command::RegisterUser {
             username,
             password,
@@ -43,8 +92,45 @@ async fn main() -> anyhow::Result<()> {
                 .await?;
             println!("user registered successfully");
         }
+        args::Command::CreateToken {
+            name,
+            scopes,
+            expires_in_days,
+        } => {
+            let token = read_token(token)?;
+            let api_token = client
+                .create_api_token(blog_client::AuthorizedCommand::new(
+                    blog_client::CreateApiTokenCommand::new(name, scopes, expires_in_days),
+                    token.as_str(),
+                ))
+                .await?;
+            println!("token created successfully, store it now as it will not be shown again");
+            println!("{}", api_token);
+        }
+        args::Command::ListTokens => {
+            let token = read_token(token)?;
+            let api_tokens = client
+                .list_api_tokens(blog_client::AuthorizedCommand::new(
+                    blog_client::ListApiTokensCommand::new(),
+                    token.as_str(),
+                ))
+                .await?;
+            for api_token in api_tokens {
+                println!("{}", api_token);
+            }
+        }
+        args::Command::RevokeToken { id } => {
+            let token = read_token(token)?;
+            client
+                .revoke_api_token(blog_client::AuthorizedCommand::new(
+                    blog_client::RevokeApiTokenCommand::new(id),
+                    token.as_str(),
+                ))
+                .await?;
+            println!("token revoked successfully");
+        }
         args::Command::CreatePost { title, content } => {
-            let token = read_token()?;
+            let token = read_token(token)?;
             let post = client
                 .create_post(blog_client::AuthorizedCommand::new(
                     CreatePostCommand::new(title, content),
@@ -54,8 +140,30 @@ async fn main() -> anyhow::Result<()> {
             println!("post created successfully");
             println!("{}", post);
         }
+        args::Command::CreatePosts {
+            titles,
+            contents,
+            transactional,
+        } => {
+            if titles.len() != contents.len() {
+                anyhow::bail!("every post needs both --title and --content");
+            }
+            let token = read_token(token)?;
+            let posts = titles
+                .into_iter()
+                .zip(contents)
+                .map(|(title, content)| CreatePostCommand::new(title, content))
+                .collect();
+            let results = client
+                .bulk_create_posts(blog_client::AuthorizedCommand::new(
+                    blog_client::BulkCreatePostsCommand::new(posts, transactional),
+                    token.as_str(),
+                ))
+                .await?;
+            print_batch_results(results);
+        }
         args::Command::UpdatePost { id, title, content } => {
-            let token = read_token()?;
+            let token = read_token(token)?;
             let post = client
                 .update_post(blog_client::AuthorizedCommand::new(
                     blog_client::UpdatePostCommand::new(id, title, content),
@@ -66,7 +174,7 @@ async fn main() -> anyhow::Result<()> {
             println!("{}", post);
         }
         args::Command::DeletePost { id } => {
-            let token = read_token()?;
+            let token = read_token(token)?;
             client
                 .delete_post(blog_client::AuthorizedCommand::new(
                     blog_client::DeletePostCommand::new(id),
@@ -81,17 +189,49 @@ async fn main() -> anyhow::Result<()> {
                 .await?;
             println!("{}", post);
         }
+        args::Command::GetPosts { ids } => {
+            let results = client
+                .batch_get_posts(blog_client::BatchGetPostsCommand::new(ids))
+                .await?;
+            print_batch_results(results);
+        }
         args::Command::GetPostsList { limit, offset } => {
             let posts = client
                 .get_post_list(blog_client::GetPostsListCommand::new(limit, offset))
                 .await?;
             println!("{}", posts);
         }
+        args::Command::Watch => {
+            let mut events = client
+                .watch_posts(blog_client::WatchPostsCommand::new())
+                .await?;
+            println!("watching posts, press Ctrl+C to stop");
+            while let Some(event) = events.next().await {
+                println!("{}", event?);
+            }
+        }
     }
 
     Ok(())
 }
 
+fn print_batch_results<E: std::fmt::Display>(results: Vec<Result<blog_client::Post, E>>) {
+    for result in results {
+        match result {
+            Ok(post) => println!("{}", post),
+            Err(err) => println!("error: {}\n", err),
+        }
+    }
+}
+
+fn read_code() -> anyhow::Result<String> {
+    print!("two-factor code: ");
+    std::io::stdout().flush()?;
+    let mut code = String::new();
+    std::io::stdin().read_line(&mut code)?;
+    Ok(code.trim().to_string())
+}
+
 fn save_token(token: &str) -> anyhow::Result<()> {
     let home = std::env::var(HOME_ENV)
         .or_else(|_| std::env::var(USERPROFILE_ENV))
@@ -101,14 +241,17 @@ fn save_token(token: &str) -> anyhow::Result<()> {
     Ok(())
 }
 
-fn read_token() -> anyhow::Result<String> {
+fn read_token(token: Option<String>) -> anyhow::Result<String> {
+    if let Some(token) = token {
+        return Ok(token);
+    }
     let home = std::env::var(HOME_ENV)
         .or_else(|_| std::env::var(USERPROFILE_ENV))
         .map_err(|_| anyhow::anyhow!("Could not find home directory"))?;
     let path = PathBuf::from(home).join(TOKEN_FILE);
     if !path.exists() {
         return Err(anyhow::anyhow!(
-            "You must login first. Run `blog-cli login` to do so."
+            "You must login first. Run `blog-cli login` or pass a token with `--token`/`BLOG_TOKEN`."
         ));
     }
     let token = std::fs::read_to_string(&path)?;
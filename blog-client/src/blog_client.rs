@@ -1,15 +1,19 @@
 mod client;
 
+pub use crate::errors::{FieldViolation, ValidationError};
 pub use client::{Client, Errors, Protocol};
 use std::fmt::{Display, Formatter};
 
 use crate::errors::{
-    CreatePostError, DeletePostError, GetPostError, GetPostsListError, LoginError,
-    RegisterUserError, UpdatePostError,
+    ApiTokenError, BatchGetPostsError, BatchItemError, BulkCreatePostsError, CreatePostError,
+    DeletePostError, GetPostError, GetPostsListError, LoginError, RegisterUserError,
+    TwoFactorError, UpdatePostError, WatchPostsError,
 };
 use async_trait::async_trait;
 use secrecy::{ExposeSecret, SecretString};
 use serde::Deserialize;
+use std::pin::Pin;
+use tokio_stream::Stream;
 use uuid::Uuid;
 
 /// Trait representing a client for the blog system.
@@ -19,13 +23,51 @@ use uuid::Uuid;
 pub trait BlogClient {
     /// Registers a new user.
     async fn register_user(&mut self, cmd: RegisterUserCommand) -> Result<(), RegisterUserError>;
-    /// Authenticates a user and returns a JWT token.
-    async fn login(&mut self, cmd: LoginCommand) -> Result<String, LoginError>;
+    /// Authenticates a user and returns a JWT token or a two-factor challenge.
+    async fn login(&mut self, cmd: LoginCommand) -> Result<LoginResult, LoginError>;
+    /// Completes a two-factor login and returns a JWT token.
+    async fn verify_two_factor(
+        &mut self,
+        cmd: VerifyTwoFactorCommand,
+    ) -> Result<String, LoginError>;
+    /// Starts two-factor enrollment. Requires authorization.
+    async fn enroll_two_factor(
+        &mut self,
+        cmd: AuthorizedCommand<'_, EnrollTwoFactorCommand>,
+    ) -> Result<TwoFactorEnrollment, TwoFactorError>;
+    /// Confirms two-factor enrollment with a TOTP code. Requires authorization.
+    async fn confirm_two_factor(
+        &mut self,
+        cmd: AuthorizedCommand<'_, ConfirmTwoFactorCommand>,
+    ) -> Result<(), TwoFactorError>;
+    /// Creates a personal access token. Requires authorization.
+    async fn create_api_token(
+        &mut self,
+        cmd: AuthorizedCommand<'_, CreateApiTokenCommand>,
+    ) -> Result<CreatedApiToken, ApiTokenError>;
+    /// Lists personal access tokens of the current user. Requires authorization.
+    async fn list_api_tokens(
+        &mut self,
+        cmd: AuthorizedCommand<'_, ListApiTokensCommand>,
+    ) -> Result<Vec<ApiToken>, ApiTokenError>;
+    /// Revokes a personal access token. Requires authorization.
+    async fn revoke_api_token(
+        &mut self,
+        cmd: AuthorizedCommand<'_, RevokeApiTokenCommand>,
+    ) -> Result<(), ApiTokenError>;
     /// Creates a new post. Requires authorization.
     async fn create_post(
         &mut self,
         cmd: AuthorizedCommand<'_, CreatePostCommand>,
     ) -> Result<Post, CreatePostError>;
+    /// Creates many posts at once. Requires authorization.
+    ///
+    /// Returns one result per post in request order. A transactional batch creates either
+    /// all posts or none.
+    async fn bulk_create_posts(
+        &mut self,
+        cmd: AuthorizedCommand<'_, BulkCreatePostsCommand>,
+    ) -> Result<Vec<Result<Post, BatchItemError>>, BulkCreatePostsError>;
     /// Updates an existing post. Requires authorization.
     async fn update_post(
         &mut self,
@@ -38,13 +80,26 @@ pub trait BlogClient {
     ) -> Result<(), DeletePostError>;
     /// Retrieves a single post by its ID.
     async fn get_post(&mut self, cmd: GetPostCommand) -> Result<Post, GetPostError>;
+    /// Retrieves many posts by their IDs. Returns one result per ID in request order.
+    async fn batch_get_posts(
+        &mut self,
+        cmd: BatchGetPostsCommand,
+    ) -> Result<Vec<Result<Post, BatchItemError>>, BatchGetPostsError>;
     /// Retrieves a paginated list of posts.
     async fn get_post_list(
         &mut self,
         cmd: GetPostsListCommand,
     ) -> Result<Pagination<Post>, GetPostsListError>;
+    /// Subscribes to post changes. The stream yields events until the connection is closed.
+    async fn watch_posts(
+        &mut self,
+        cmd: WatchPostsCommand,
+    ) -> Result<PostEventStream, WatchPostsError>;
 }
 
+/// Live stream of post changes returned by [`BlogClient::watch_posts`].
+pub type PostEventStream = Pin<Box<dyn Stream<Item = Result<PostEvent, WatchPostsError>> + Send>>;
+
 /// Command for retrieving a list of posts with pagination.
 pub struct GetPostsListCommand {
     limit: usize,
@@ -84,6 +139,23 @@ impl GetPostCommand {
     }
 }
 
+/// Command for retrieving many posts by their IDs.
+pub struct BatchGetPostsCommand {
+    ids: Vec<Uuid>,
+}
+
+impl BatchGetPostsCommand {
+    /// Creates a new `BatchGetPostsCommand`.
+    pub fn new(ids: Vec<Uuid>) -> Self {
+        Self { ids }
+    }
+
+    /// Returns the post IDs.
+    pub fn get_ids(&self) -> &[Uuid] {
+        &self.ids
+    }
+}
+
 /// Command for deleting a post by its ID.
 pub struct DeletePostCommand {
     id: Uuid,
@@ -136,16 +208,23 @@ impl RegisterUserCommand {
 pub struct LoginCommand {
     username: String,
     password: SecretString,
+    scopes: Vec<String>,
 }
 
 impl LoginCommand {
-    /// Creates a new `LoginCommand`.
+    /// Creates a new `LoginCommand` requesting all scopes.
     pub fn new(username: String, password: String) -> Self {
         Self {
             username,
             password: SecretString::from(password),
+            scopes: Vec::new(),
         }
     }
+    /// Restricts the issued token to the given scopes.
+    pub fn with_scopes(mut self, scopes: Vec<String>) -> Self {
+        self.scopes = scopes;
+        self
+    }
     /// Returns the username.
     pub fn get_username(&self) -> &str {
         &self.username
@@ -154,6 +233,253 @@ impl LoginCommand {
     pub fn get_password(&self) -> &str {
         self.password.expose_secret()
     }
+    /// Returns the requested scopes; empty means all scopes.
+    pub fn get_scopes(&self) -> &[String] {
+        &self.scopes
+    }
+}
+
+/// Result of the first login step.
+#[derive(Debug)]
+pub enum LoginResult {
+    /// The user is authenticated; contains a JWT token.
+    Token(String),
+    /// The user has two-factor authentication enabled; contains a challenge token
+    /// to be passed to `verify_two_factor` together with a TOTP or recovery code.
+    TwoFactorRequired(String),
+}
+
+/// Command for completing a two-factor login.
+pub struct VerifyTwoFactorCommand {
+    challenge_token: String,
+    code: String,
+}
+
+impl VerifyTwoFactorCommand {
+    /// Creates a new `VerifyTwoFactorCommand`.
+    pub fn new(challenge_token: String, code: String) -> Self {
+        Self {
+            challenge_token,
+            code,
+        }
+    }
+
+    /// Returns the challenge token.
+    pub fn get_challenge_token(&self) -> &str {
+        &self.challenge_token
+    }
+    /// Returns the TOTP or recovery code.
+    pub fn get_code(&self) -> &str {
+        &self.code
+    }
+}
+
+/// Command for starting two-factor enrollment.
+#[derive(Default)]
+pub struct EnrollTwoFactorCommand;
+
+impl EnrollTwoFactorCommand {
+    /// Creates a new `EnrollTwoFactorCommand`.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+/// Command for confirming two-factor enrollment.
+pub struct ConfirmTwoFactorCommand {
+    code: String,
+}
+
+impl ConfirmTwoFactorCommand {
+    /// Creates a new `ConfirmTwoFactorCommand`.
+    pub fn new(code: String) -> Self {
+        Self { code }
+    }
+
+    /// Returns the TOTP code.
+    pub fn get_code(&self) -> &str {
+        &self.code
+    }
+}
+
+/// Two-factor enrollment data to be shown to the user.
+#[derive(Deserialize, Debug)]
+pub struct TwoFactorEnrollment {
+    otpauth_uri: String,
+    recovery_codes: Vec<String>,
+}
+
+impl TwoFactorEnrollment {
+    pub(crate) fn new(otpauth_uri: String, recovery_codes: Vec<String>) -> Self {
+        Self {
+            otpauth_uri,
+            recovery_codes,
+        }
+    }
+
+    /// Returns the `otpauth://` URI for authenticator apps.
+    pub fn get_otpauth_uri(&self) -> &str {
+        &self.otpauth_uri
+    }
+    /// Returns the one-time recovery codes.
+    pub fn get_recovery_codes(&self) -> &[String] {
+        &self.recovery_codes
+    }
+}
+
+impl Display for TwoFactorEnrollment {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "OTP auth URI: {}", self.otpauth_uri)?;
+        writeln!(f, "Recovery codes:")?;
+        for code in &self.recovery_codes {
+            writeln!(f, "  {}", code)?;
+        }
+        Ok(())
+    }
+}
+
+/// Command for creating a personal access token.
+pub struct CreateApiTokenCommand {
+    name: String,
+    scopes: Vec<String>,
+    expires_in_days: Option<u32>,
+}
+
+impl CreateApiTokenCommand {
+    /// Creates a new `CreateApiTokenCommand`.
+    ///
+    /// A token without `expires_in_days` never expires.
+    pub fn new(name: String, scopes: Vec<String>, expires_in_days: Option<u32>) -> Self {
+        Self {
+            name,
+            scopes,
+            expires_in_days,
+        }
+    }
+
+    /// Returns the token name.
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+    /// Returns the requested scopes.
+    pub fn get_scopes(&self) -> &[String] {
+        &self.scopes
+    }
+    /// Returns the token lifetime in days.
+    pub fn get_expires_in_days(&self) -> Option<u32> {
+        self.expires_in_days
+    }
+}
+
+/// Command for listing personal access tokens.
+#[derive(Default)]
+pub struct ListApiTokensCommand;
+
+impl ListApiTokensCommand {
+    /// Creates a new `ListApiTokensCommand`.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+/// Command for revoking a personal access token by its ID.
+pub struct RevokeApiTokenCommand {
+    id: Uuid,
+}
+
+impl RevokeApiTokenCommand {
+    /// Creates a new `RevokeApiTokenCommand`.
+    pub fn new(id: Uuid) -> Self {
+        Self { id }
+    }
+
+    /// Returns the token ID.
+    pub fn get_id(&self) -> &Uuid {
+        &self.id
+    }
+}
+
+/// Metadata of a personal access token. The token value itself is never returned after creation.
+#[derive(Deserialize, Debug)]
+pub struct ApiToken {
+    id: Uuid,
+    name: String,
+    scopes: Vec<String>,
+    created_at: chrono::DateTime<chrono::Utc>,
+    expires_at: Option<chrono::DateTime<chrono::Utc>>,
+    last_used_at: Option<chrono::DateTime<chrono::Utc>>,
+    revoked_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl ApiToken {
+    pub(crate) fn new(
+        id: Uuid,
+        name: String,
+        scopes: Vec<String>,
+        created_at: chrono::DateTime<chrono::Utc>,
+        expires_at: Option<chrono::DateTime<chrono::Utc>>,
+        last_used_at: Option<chrono::DateTime<chrono::Utc>>,
+        revoked_at: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> Self {
+        Self {
+            id,
+            name,
+            scopes,
+            created_at,
+            expires_at,
+            last_used_at,
+            revoked_at,
+        }
+    }
+}
+
+impl Display for ApiToken {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        fn format_date(value: &Option<chrono::DateTime<chrono::Utc>>) -> String {
+            value
+                .map(|value| value.to_string())
+                .unwrap_or_else(|| "-".to_string())
+        }
+
+        writeln!(f, "ID: {}", self.id)?;
+        writeln!(f, "Name: {}", self.name)?;
+        writeln!(f, "Scopes: {}", self.scopes.join(", "))?;
+        writeln!(f, "Created at: {}", self.created_at)?;
+        writeln!(f, "Expires at: {}", format_date(&self.expires_at))?;
+        writeln!(f, "Last used at: {}", format_date(&self.last_used_at))?;
+        writeln!(f, "Revoked at: {}", format_date(&self.revoked_at))?;
+        Ok(())
+    }
+}
+
+/// A newly created personal access token together with its secret value.
+#[derive(Deserialize, Debug)]
+pub struct CreatedApiToken {
+    token: String,
+    #[serde(flatten)]
+    api_token: ApiToken,
+}
+
+impl CreatedApiToken {
+    pub(crate) fn new(token: String, api_token: ApiToken) -> Self {
+        Self { token, api_token }
+    }
+
+    /// Returns the token value. It is shown only once and cannot be retrieved later.
+    pub fn get_token(&self) -> &str {
+        &self.token
+    }
+    /// Returns the token metadata.
+    pub fn get_api_token(&self) -> &ApiToken {
+        &self.api_token
+    }
+}
+
+impl Display for CreatedApiToken {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Token: {}", self.token)?;
+        write!(f, "{}", self.api_token)
+    }
 }
 
 /// Command for creating a new post.
@@ -178,6 +504,33 @@ impl CreatePostCommand {
     }
 }
 
+/// Command for creating many posts at once.
+pub struct BulkCreatePostsCommand {
+    posts: Vec<CreatePostCommand>,
+    transactional: bool,
+}
+
+impl BulkCreatePostsCommand {
+    /// Creates a new `BulkCreatePostsCommand`.
+    ///
+    /// A transactional batch creates either all posts or none.
+    pub fn new(posts: Vec<CreatePostCommand>, transactional: bool) -> Self {
+        Self {
+            posts,
+            transactional,
+        }
+    }
+
+    /// Returns the posts to create.
+    pub fn get_posts(&self) -> &[CreatePostCommand] {
+        &self.posts
+    }
+    /// Returns whether the batch is created in a single transaction.
+    pub fn is_transactional(&self) -> bool {
+        self.transactional
+    }
+}
+
 /// Command for updating an existing post.
 pub struct UpdatePostCommand {
     id: Uuid,
@@ -248,6 +601,40 @@ impl Display for Post {
     }
 }
 
+/// Command for subscribing to post changes.
+#[derive(Default)]
+pub struct WatchPostsCommand;
+
+impl WatchPostsCommand {
+    /// Creates a new `WatchPostsCommand`.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+/// A change to a post published by the server.
+#[derive(Debug)]
+pub enum PostEvent {
+    /// A post was created.
+    Created(Post),
+    /// A post was updated; contains its new state.
+    Updated(Post),
+    /// A post was deleted; contains its last state.
+    Deleted(Post),
+}
+
+impl Display for PostEvent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let (kind, post) = match self {
+            PostEvent::Created(post) => ("created", post),
+            PostEvent::Updated(post) => ("updated", post),
+            PostEvent::Deleted(post) => ("deleted", post),
+        };
+        writeln!(f, "Event: {}", kind)?;
+        write!(f, "{}", post)
+    }
+}
+
 /// Wrapper for commands that require an authorization token.
 pub struct AuthorizedCommand<'a, T> {
     command: T,
@@ -308,6 +695,7 @@ mod tests {
     use super::*;
 
     #[test]
+    #[allow(clippy::unused_unit)]
     fn deserialize_post() -> () {
         let response = r#"{
   "id": "204ff4d7-ea2e-4e7c-becb-4eb46747d81d",
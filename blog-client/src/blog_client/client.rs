@@ -1,12 +1,17 @@
 use crate::errors::{
-    CreatePostError, DeletePostError, GetPostError, GetPostsListError, LoginError,
-    RegisterUserError, UpdatePostError,
+    ApiTokenError, BatchGetPostsError, BatchItemError, BulkCreatePostsError, CreatePostError,
+    DeletePostError, GetPostError, GetPostsListError, LoginError, RegisterUserError,
+    TwoFactorError, UpdatePostError, WatchPostsError,
 };
 use crate::grpc_client::GrpcClient;
 use crate::http_client::HttpClient;
 use crate::{
-    AuthorizedCommand, BlogClient, CreatePostCommand, DeletePostCommand, GetPostCommand,
-    GetPostsListCommand, LoginCommand, Pagination, Post, RegisterUserCommand, UpdatePostCommand,
+    ApiToken, AuthorizedCommand, BatchGetPostsCommand, BlogClient, BulkCreatePostsCommand,
+    ConfirmTwoFactorCommand, CreateApiTokenCommand, CreatePostCommand, CreatedApiToken,
+    DeletePostCommand, EnrollTwoFactorCommand, GetPostCommand, GetPostsListCommand,
+    ListApiTokensCommand, LoginCommand, LoginResult, Pagination, Post, PostEventStream,
+    RegisterUserCommand, RevokeApiTokenCommand, TwoFactorEnrollment, UpdatePostCommand,
+    VerifyTwoFactorCommand, WatchPostsCommand,
 };
 use async_trait::async_trait;
 
@@ -32,13 +37,28 @@ enum ClientMode {
 
 impl Client {
     /// Creates a new `Client` with the specified protocol.
+    ///
+    /// `https://` addresses are verified against the system's root certificates.
     pub async fn new(protocol: Protocol) -> Result<Self, Errors> {
+        Self::connect(protocol, None).await
+    }
+
+    /// Creates a new `Client` that additionally trusts the CA certificates in a PEM bundle,
+    /// e.g. to reach a server with a self-signed certificate over `https://`.
+    pub async fn with_ca_certificate(
+        protocol: Protocol,
+        ca_certificate: &[u8],
+    ) -> Result<Self, Errors> {
+        Self::connect(protocol, Some(ca_certificate)).await
+    }
+
+    async fn connect(protocol: Protocol, ca_certificate: Option<&[u8]>) -> Result<Self, Errors> {
         match protocol {
             Protocol::Http(address) => Ok(Self {
-                client_mode: ClientMode::Http(HttpClient::new(address)),
+                client_mode: ClientMode::Http(HttpClient::new(address, ca_certificate)?),
             }),
             Protocol::Grpc(address) => Ok(Self {
-                client_mode: ClientMode::Grpc(GrpcClient::new(address).await?),
+                client_mode: ClientMode::Grpc(GrpcClient::new(address, ca_certificate).await?),
             }),
         }
     }
@@ -53,13 +73,73 @@ impl BlogClient for Client {
         }
     }
 
-    async fn login(&mut self, cmd: LoginCommand) -> Result<String, LoginError> {
+    async fn login(&mut self, cmd: LoginCommand) -> Result<LoginResult, LoginError> {
         match self.client_mode {
             ClientMode::Http(ref client) => client.login(&cmd).await,
             ClientMode::Grpc(ref mut client) => client.login(&cmd).await,
         }
     }
 
+    async fn verify_two_factor(
+        &mut self,
+        cmd: VerifyTwoFactorCommand,
+    ) -> Result<String, LoginError> {
+        match self.client_mode {
+            ClientMode::Http(ref client) => client.verify_two_factor(&cmd).await,
+            ClientMode::Grpc(ref mut client) => client.verify_two_factor(&cmd).await,
+        }
+    }
+
+    async fn enroll_two_factor(
+        &mut self,
+        cmd: AuthorizedCommand<'_, EnrollTwoFactorCommand>,
+    ) -> Result<TwoFactorEnrollment, TwoFactorError> {
+        match self.client_mode {
+            ClientMode::Http(ref client) => client.enroll_two_factor(&cmd).await,
+            ClientMode::Grpc(ref mut client) => client.enroll_two_factor(&cmd).await,
+        }
+    }
+
+    async fn confirm_two_factor(
+        &mut self,
+        cmd: AuthorizedCommand<'_, ConfirmTwoFactorCommand>,
+    ) -> Result<(), TwoFactorError> {
+        match self.client_mode {
+            ClientMode::Http(ref client) => client.confirm_two_factor(&cmd).await,
+            ClientMode::Grpc(ref mut client) => client.confirm_two_factor(&cmd).await,
+        }
+    }
+
+    async fn create_api_token(
+        &mut self,
+        cmd: AuthorizedCommand<'_, CreateApiTokenCommand>,
+    ) -> Result<CreatedApiToken, ApiTokenError> {
+        match self.client_mode {
+            ClientMode::Http(ref client) => client.create_api_token(&cmd).await,
+            ClientMode::Grpc(ref mut client) => client.create_api_token(&cmd).await,
+        }
+    }
+
+    async fn list_api_tokens(
+        &mut self,
+        cmd: AuthorizedCommand<'_, ListApiTokensCommand>,
+    ) -> Result<Vec<ApiToken>, ApiTokenError> {
+        match self.client_mode {
+            ClientMode::Http(ref client) => client.list_api_tokens(&cmd).await,
+            ClientMode::Grpc(ref mut client) => client.list_api_tokens(&cmd).await,
+        }
+    }
+
+    async fn revoke_api_token(
+        &mut self,
+        cmd: AuthorizedCommand<'_, RevokeApiTokenCommand>,
+    ) -> Result<(), ApiTokenError> {
+        match self.client_mode {
+            ClientMode::Http(ref client) => client.revoke_api_token(&cmd).await,
+            ClientMode::Grpc(ref mut client) => client.revoke_api_token(&cmd).await,
+        }
+    }
+
     async fn create_post(
         &mut self,
         cmd: AuthorizedCommand<'_, CreatePostCommand>,
@@ -70,6 +150,16 @@ impl BlogClient for Client {
         }
     }
 
+    async fn bulk_create_posts(
+        &mut self,
+        cmd: AuthorizedCommand<'_, BulkCreatePostsCommand>,
+    ) -> Result<Vec<Result<Post, BatchItemError>>, BulkCreatePostsError> {
+        match self.client_mode {
+            ClientMode::Http(ref client) => client.bulk_create_posts(&cmd).await,
+            ClientMode::Grpc(ref mut client) => client.bulk_create_posts(&cmd).await,
+        }
+    }
+
     async fn update_post(
         &mut self,
         cmd: AuthorizedCommand<'_, UpdatePostCommand>,
@@ -97,6 +187,16 @@ impl BlogClient for Client {
         }
     }
 
+    async fn batch_get_posts(
+        &mut self,
+        cmd: BatchGetPostsCommand,
+    ) -> Result<Vec<Result<Post, BatchItemError>>, BatchGetPostsError> {
+        match self.client_mode {
+            ClientMode::Http(ref client) => client.batch_get_posts(&cmd).await,
+            ClientMode::Grpc(ref mut client) => client.batch_get_posts(&cmd).await,
+        }
+    }
+
     async fn get_post_list(
         &mut self,
         cmd: GetPostsListCommand,
@@ -106,6 +206,16 @@ impl BlogClient for Client {
             ClientMode::Grpc(ref mut client) => client.get_post_list(&cmd).await,
         }
     }
+
+    async fn watch_posts(
+        &mut self,
+        cmd: WatchPostsCommand,
+    ) -> Result<PostEventStream, WatchPostsError> {
+        match self.client_mode {
+            ClientMode::Http(ref client) => client.watch_posts(&cmd).await,
+            ClientMode::Grpc(ref mut client) => client.watch_posts(&cmd).await,
+        }
+    }
 }
 
 /// Errors that can occur during client initialization.
@@ -114,4 +224,7 @@ pub enum Errors {
     /// Connection error for gRPC.
     #[error("connection error: {0}")]
     ConnectionError(#[from] tonic::transport::Error),
+    /// The HTTP client could not be built, e.g. because the CA certificate is invalid.
+    #[error("HTTP client error: {0}")]
+    HttpClientError(#[from] reqwest::Error),
 }
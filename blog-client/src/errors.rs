@@ -1,3 +1,70 @@
+use std::fmt::{Display, Formatter};
+
+/// A field rejected by server-side validation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldViolation {
+    field: String,
+    code: String,
+    description: String,
+}
+
+impl FieldViolation {
+    pub(crate) fn new(field: String, code: String, description: String) -> Self {
+        Self {
+            field,
+            code,
+            description,
+        }
+    }
+
+    /// Name of the rejected field, e.g. `title`.
+    pub fn field(&self) -> &str {
+        &self.field
+    }
+
+    /// Machine-readable reason, e.g. `TITLE_TOO_LONG`.
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+
+    /// Human-readable description of the violation.
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+}
+
+/// Validation failure reported by the server, with every rejected field.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationError {
+    message: String,
+    violations: Vec<FieldViolation>,
+}
+
+impl ValidationError {
+    pub(crate) fn new(message: String, violations: Vec<FieldViolation>) -> Self {
+        Self {
+            message,
+            violations,
+        }
+    }
+
+    /// Human-readable error message.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Rejected fields; empty when the server did not report any.
+    pub fn violations(&self) -> &[FieldViolation] {
+        &self.violations
+    }
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
 /// Errors that can occur during user registration.
 #[derive(Debug, thiserror::Error)]
 pub enum RegisterUserError {
@@ -9,7 +76,7 @@ pub enum RegisterUserError {
     UsernameOrEmailExist,
     /// Error when the user data is invalid.
     #[error("invalid user: {0}")]
-    InvalidUser(String),
+    InvalidUser(ValidationError),
 }
 
 /// Errors that can occur during login.
@@ -21,6 +88,53 @@ pub enum LoginError {
     /// Error when the username or password is incorrect.
     #[error("invalid user name or password")]
     InvalidUserNameOrPassword,
+    /// Error when the two-factor code or challenge token is invalid or expired.
+    #[error("invalid two-factor code")]
+    InvalidTwoFactorCode,
+    /// Error when one of the requested scopes is unknown; the violations name the `scopes`
+    /// field.
+    #[error("{0}")]
+    InvalidScope(ValidationError),
+}
+
+/// Errors that can occur during two-factor enrollment.
+#[derive(Debug, thiserror::Error)]
+pub enum TwoFactorError {
+    /// Unexpected request or network error.
+    #[error("request error: {0}")]
+    Unexpected(String),
+    /// Error when the user is not authorized.
+    #[error("unauthorized")]
+    Unauthorized,
+    /// Error when the token lacks the scope required for the operation.
+    #[error("{0}")]
+    Forbidden(String),
+    /// Error when two-factor authentication is already enabled or not enrolled yet.
+    #[error("{0}")]
+    Conflict(String),
+    /// Error when the TOTP code is invalid.
+    #[error("invalid two-factor code")]
+    InvalidCode,
+}
+
+/// Errors that can occur while managing personal access tokens.
+#[derive(Debug, thiserror::Error)]
+pub enum ApiTokenError {
+    /// Unexpected request or network error.
+    #[error("request error: {0}")]
+    Unexpected(String),
+    /// Error when the user is not authorized.
+    #[error("unauthorized")]
+    Unauthorized,
+    /// Error when the token name, scopes or expiration are invalid.
+    #[error("invalid token: {0}")]
+    InvalidToken(String),
+    /// Error when the token is not found.
+    #[error("token not found")]
+    NotFound,
+    /// Error when the token lacks the scope required for the operation.
+    #[error("{0}")]
+    Forbidden(String),
 }
 
 /// Errors that can occur during post creation.
@@ -31,10 +145,58 @@ pub enum CreatePostError {
     Unexpected(String),
     /// Error when the post data is invalid.
     #[error("invalid post: {0}")]
-    InvalidPost(String),
+    InvalidPost(ValidationError),
     /// Error when the user is not authorized.
     #[error("unauthorized")]
     Unauthorized,
+    /// Error when the token lacks the scope required for the operation.
+    #[error("{0}")]
+    Forbidden(String),
+}
+
+/// Errors that can occur during bulk post creation.
+#[derive(Debug, thiserror::Error)]
+pub enum BulkCreatePostsError {
+    /// Unexpected request or network error.
+    #[error("request error: {0}")]
+    Unexpected(String),
+    /// Error when the batch is empty or too large.
+    #[error("invalid batch: {0}")]
+    InvalidBatch(String),
+    /// Error when the user is not authorized.
+    #[error("unauthorized")]
+    Unauthorized,
+    /// Error when the token lacks the scope required for the operation.
+    #[error("{0}")]
+    Forbidden(String),
+}
+
+/// Errors that can occur during batch post retrieval.
+#[derive(Debug, thiserror::Error)]
+pub enum BatchGetPostsError {
+    /// Unexpected request or network error.
+    #[error("request error: {0}")]
+    Unexpected(String),
+    /// Error when the batch is empty or too large.
+    #[error("invalid batch: {0}")]
+    InvalidBatch(String),
+}
+
+/// Error of a single item of a batch operation.
+#[derive(Debug, thiserror::Error)]
+pub enum BatchItemError {
+    /// Unexpected error while processing the item.
+    #[error("request error: {0}")]
+    Unexpected(String),
+    /// Error when the post data is invalid.
+    #[error("invalid post: {0}")]
+    InvalidPost(ValidationError),
+    /// Error when the post is not found.
+    #[error("post not found")]
+    NotFound,
+    /// Error when the post was not created because another post of a transactional batch failed.
+    #[error("{0}")]
+    Aborted(String),
 }
 
 /// Errors that can occur during post update.
@@ -45,7 +207,7 @@ pub enum UpdatePostError {
     Unexpected(String),
     /// Error when the post data is invalid.
     #[error("invalid post: {0}")]
-    InvalidPost(String),
+    InvalidPost(ValidationError),
     /// Error when the user is not authorized.
     #[error("unauthorized")]
     Unauthorized,
@@ -92,3 +254,17 @@ pub enum GetPostsListError {
     #[error("request error: {0}")]
     Unexpected(String),
 }
+
+/// Errors that can occur while watching post changes.
+#[derive(Debug, thiserror::Error)]
+pub enum WatchPostsError {
+    /// Unexpected request or network error.
+    #[error("request error: {0}")]
+    Unexpected(String),
+    /// Error when the protocol does not support watching posts.
+    #[error("watching posts is not supported over {0}")]
+    Unsupported(&'static str),
+    /// Error when the client fell behind and the server skipped some events.
+    #[error("{0}")]
+    Lagged(String),
+}
@@ -1,24 +1,49 @@
 use crate::errors::{
-    CreatePostError, DeletePostError, GetPostError, GetPostsListError, LoginError,
-    RegisterUserError, UpdatePostError,
+    ApiTokenError, BatchGetPostsError, BulkCreatePostsError, CreatePostError, DeletePostError,
+    GetPostError, GetPostsListError, LoginError, RegisterUserError, TwoFactorError,
+    UpdatePostError, WatchPostsError,
 };
+use crate::grpc_client::batch_post_result::Result as BatchResult;
 use crate::grpc_client::blog_service_client::BlogServiceClient;
+use crate::grpc_client::post_event::Kind;
+use crate::trace_context::TraceContextInterceptor;
 use std::str::FromStr;
+use tokio_stream::StreamExt;
 use tonic::metadata::MetadataValue;
 use tonic::metadata::errors::InvalidMetadataValue;
+use tonic::service::interceptor::InterceptedService;
+use tonic::transport::{Certificate, Channel, ClientTlsConfig, Endpoint};
 use tonic::{Code, Request, Status};
+use tonic_types::StatusExt;
 use uuid::Uuid;
 
+/// Field of the violation the server reports for an unknown login scope.
+const SCOPES_FIELD: &str = "scopes";
+
 tonic::include_proto!("blog");
 
 pub(crate) struct GrpcClient {
-    client: BlogServiceClient<tonic::transport::Channel>,
+    client: BlogServiceClient<InterceptedService<Channel, TraceContextInterceptor>>,
 }
 
 impl GrpcClient {
-    pub(crate) async fn new(address: String) -> Result<Self, tonic::transport::Error> {
+    pub(crate) async fn new(
+        address: String,
+        ca_certificate: Option<&[u8]>,
+    ) -> Result<Self, tonic::transport::Error> {
+        let mut endpoint = Endpoint::new(address)?;
+        if endpoint.uri().scheme_str() == Some("https") {
+            let mut tls_config = ClientTlsConfig::new().with_native_roots();
+            if let Some(ca_certificate) = ca_certificate {
+                tls_config = tls_config.ca_certificate(Certificate::from_pem(ca_certificate));
+            }
+            endpoint = endpoint.tls_config(tls_config)?;
+        }
         Ok(Self {
-            client: BlogServiceClient::connect(address).await?,
+            client: BlogServiceClient::with_interceptor(
+                endpoint.connect().await?,
+                TraceContextInterceptor,
+            ),
         })
     }
 
@@ -31,9 +56,110 @@ impl GrpcClient {
         Ok(())
     }
 
-    pub(crate) async fn login(&mut self, cmd: &crate::LoginCommand) -> Result<String, LoginError> {
+    pub(crate) async fn login(
+        &mut self,
+        cmd: &crate::LoginCommand,
+    ) -> Result<crate::LoginResult, LoginError> {
         let request: Request<VerifyUserCommand> = cmd.into();
-        Ok(self.client.login(request).await?.into_inner().token)
+        let result = self.client.login(request).await?.into_inner();
+        if result.challenge_token.is_empty() {
+            Ok(crate::LoginResult::Token(result.token))
+        } else {
+            Ok(crate::LoginResult::TwoFactorRequired(
+                result.challenge_token,
+            ))
+        }
+    }
+
+    pub(crate) async fn verify_two_factor(
+        &mut self,
+        cmd: &crate::VerifyTwoFactorCommand,
+    ) -> Result<String, LoginError> {
+        let request: Request<VerifyTwoFactorCommand> = cmd.into();
+        match self.client.verify_two_factor(request).await {
+            Ok(response) => Ok(response.into_inner().token),
+            Err(status) if status.code() == Code::Unauthenticated => {
+                Err(LoginError::InvalidTwoFactorCode)
+            }
+            Err(status) => Err(status.into()),
+        }
+    }
+
+    pub(crate) async fn enroll_two_factor(
+        &mut self,
+        cmd: &crate::AuthorizedCommand<'_, crate::EnrollTwoFactorCommand>,
+    ) -> Result<crate::TwoFactorEnrollment, TwoFactorError> {
+        let mut request = Request::new(());
+        add_token(&mut request, cmd.get_token())
+            .map_err(|e| TwoFactorError::Unexpected(e.to_string()))?;
+        let result = self.client.enroll_two_factor(request).await?.into_inner();
+        Ok(crate::TwoFactorEnrollment::new(
+            result.otpauth_uri,
+            result.recovery_codes,
+        ))
+    }
+
+    pub(crate) async fn confirm_two_factor(
+        &mut self,
+        cmd: &crate::AuthorizedCommand<'_, crate::ConfirmTwoFactorCommand>,
+    ) -> Result<(), TwoFactorError> {
+        let mut request = Request::new(ConfirmTwoFactorCommand {
+            code: cmd.get_command().get_code().to_string(),
+        });
+        add_token(&mut request, cmd.get_token())
+            .map_err(|e| TwoFactorError::Unexpected(e.to_string()))?;
+        let _ = self.client.confirm_two_factor(request).await?;
+        Ok(())
+    }
+
+    pub(crate) async fn create_api_token(
+        &mut self,
+        cmd: &crate::AuthorizedCommand<'_, crate::CreateApiTokenCommand>,
+    ) -> Result<crate::CreatedApiToken, ApiTokenError> {
+        let mut request = Request::new(CreateApiTokenCommand {
+            name: cmd.get_command().get_name().to_string(),
+            scopes: cmd.get_command().get_scopes().to_vec(),
+            expires_in_days: cmd.get_command().get_expires_in_days().unwrap_or_default(),
+        });
+        add_token(&mut request, cmd.get_token())
+            .map_err(|e| ApiTokenError::Unexpected(e.to_string()))?;
+        let result = self.client.create_api_token(request).await?.into_inner();
+        let api_token = result
+            .api_token
+            .ok_or_else(|| ApiTokenError::Unexpected("Missing api_token".to_string()))?
+            .try_into()
+            .map_err(ApiTokenError::Unexpected)?;
+        Ok(crate::CreatedApiToken::new(result.token, api_token))
+    }
+
+    pub(crate) async fn list_api_tokens(
+        &mut self,
+        cmd: &crate::AuthorizedCommand<'_, crate::ListApiTokensCommand>,
+    ) -> Result<Vec<crate::ApiToken>, ApiTokenError> {
+        let mut request = Request::new(());
+        add_token(&mut request, cmd.get_token())
+            .map_err(|e| ApiTokenError::Unexpected(e.to_string()))?;
+        self.client
+            .list_api_tokens(request)
+            .await?
+            .into_inner()
+            .api_tokens
+            .into_iter()
+            .map(|token| token.try_into().map_err(ApiTokenError::Unexpected))
+            .collect()
+    }
+
+    pub(crate) async fn revoke_api_token(
+        &mut self,
+        cmd: &crate::AuthorizedCommand<'_, crate::RevokeApiTokenCommand>,
+    ) -> Result<(), ApiTokenError> {
+        let mut request = Request::new(RevokeApiTokenCommand {
+            id: cmd.get_command().get_id().to_string(),
+        });
+        add_token(&mut request, cmd.get_token())
+            .map_err(|e| ApiTokenError::Unexpected(e.to_string()))?;
+        let _ = self.client.revoke_api_token(request).await?;
+        Ok(())
     }
 
     pub(crate) async fn create_post(
@@ -51,6 +177,28 @@ impl GrpcClient {
             .map_err(CreatePostError::Unexpected)
     }
 
+    pub(crate) async fn bulk_create_posts(
+        &mut self,
+        cmd: &crate::AuthorizedCommand<'_, crate::BulkCreatePostsCommand>,
+    ) -> Result<Vec<Result<crate::Post, crate::errors::BatchItemError>>, BulkCreatePostsError> {
+        let transactional = cmd.get_command().is_transactional();
+        let items = cmd
+            .get_command()
+            .get_posts()
+            .iter()
+            .map(|post| BulkCreatePostsItem {
+                title: post.get_title().to_string(),
+                content: post.get_content().to_string(),
+                transactional,
+            })
+            .collect::<Vec<_>>();
+        let mut request = Request::new(tokio_stream::iter(items));
+        add_token(&mut request, cmd.get_token())
+            .map_err(|e| BulkCreatePostsError::Unexpected(e.to_string()))?;
+        let result = self.client.bulk_create_posts(request).await?.into_inner();
+        batch_results(result).map_err(BulkCreatePostsError::Unexpected)
+    }
+
     pub(crate) async fn update_post(
         &mut self,
         cmd: &crate::AuthorizedCommand<'_, crate::UpdatePostCommand>,
@@ -90,6 +238,17 @@ impl GrpcClient {
             .map_err(GetPostError::Unexpected)
     }
 
+    pub(crate) async fn batch_get_posts(
+        &mut self,
+        cmd: &crate::BatchGetPostsCommand,
+    ) -> Result<Vec<Result<crate::Post, crate::errors::BatchItemError>>, BatchGetPostsError> {
+        let request = Request::new(BatchGetPostsCommand {
+            ids: cmd.get_ids().iter().map(|id| id.to_string()).collect(),
+        });
+        let result = self.client.batch_get_posts(request).await?.into_inner();
+        batch_results(result).map_err(BatchGetPostsError::Unexpected)
+    }
+
     pub(crate) async fn get_post_list(
         &mut self,
         cmd: &crate::GetPostsListCommand,
@@ -102,6 +261,42 @@ impl GrpcClient {
             .try_into()
             .map_err(GetPostsListError::Unexpected)
     }
+
+    pub(crate) async fn watch_posts(
+        &mut self,
+        _cmd: &crate::WatchPostsCommand,
+    ) -> Result<crate::PostEventStream, WatchPostsError> {
+        let stream = self
+            .client
+            .watch_posts(Request::new(()))
+            .await?
+            .into_inner()
+            .map(|event| event?.try_into().map_err(WatchPostsError::Unexpected));
+        Ok(Box::pin(stream))
+    }
+}
+
+impl TryFrom<PostEvent> for crate::PostEvent {
+    type Error = String;
+    fn try_from(value: PostEvent) -> Result<Self, Self::Error> {
+        let kind = value.kind();
+        let post = crate::Post::try_from(value.post.ok_or("Missing post")?)?;
+        match kind {
+            Kind::Created => Ok(crate::PostEvent::Created(post)),
+            Kind::Updated => Ok(crate::PostEvent::Updated(post)),
+            Kind::Deleted => Ok(crate::PostEvent::Deleted(post)),
+            Kind::Unspecified => Err("Unknown post event kind".to_string()),
+        }
+    }
+}
+
+impl From<Status> for WatchPostsError {
+    fn from(status: Status) -> Self {
+        match status.code() {
+            Code::DataLoss => WatchPostsError::Lagged(status.message().to_string()),
+            _ => WatchPostsError::Unexpected(status.message().to_string()),
+        }
+    }
 }
 
 impl TryFrom<GetPostListResult> for crate::Pagination<crate::Post> {
@@ -181,7 +376,7 @@ impl TryFrom<&crate::AuthorizedCommand<'_, crate::DeletePostCommand>>
 impl From<Status> for UpdatePostError {
     fn from(status: Status) -> Self {
         match status.code() {
-            Code::InvalidArgument => UpdatePostError::InvalidPost(status.message().to_string()),
+            Code::InvalidArgument => UpdatePostError::InvalidPost(validation_error(&status)),
             Code::Unauthenticated => UpdatePostError::Unauthorized,
             Code::NotFound => UpdatePostError::NotFound,
             Code::PermissionDenied => UpdatePostError::Forbidden,
@@ -229,11 +424,90 @@ impl TryFrom<Post> for crate::Post {
     }
 }
 
+fn batch_results(
+    result: BatchPostsResult,
+) -> Result<Vec<Result<crate::Post, crate::errors::BatchItemError>>, String> {
+    result
+        .results
+        .into_iter()
+        .map(|item| match item.result.ok_or("Missing batch result")? {
+            BatchResult::Post(post) => Ok(Ok(crate::Post::try_from(post)?)),
+            BatchResult::Error(error) => Ok(Err(error.into())),
+        })
+        .collect()
+}
+
+impl From<BatchItemError> for crate::errors::BatchItemError {
+    fn from(error: BatchItemError) -> Self {
+        match Code::from_i32(error.code) {
+            Code::InvalidArgument => {
+                crate::errors::BatchItemError::InvalidPost(crate::ValidationError::new(
+                    error.message,
+                    error.violations.into_iter().map(Into::into).collect(),
+                ))
+            }
+            Code::NotFound => crate::errors::BatchItemError::NotFound,
+            Code::Aborted => crate::errors::BatchItemError::Aborted(error.message),
+            _ => crate::errors::BatchItemError::Unexpected(error.message),
+        }
+    }
+}
+
+impl From<FieldViolation> for crate::FieldViolation {
+    fn from(violation: FieldViolation) -> Self {
+        crate::FieldViolation::new(violation.field, violation.reason, violation.description)
+    }
+}
+
+/// Reads the `google.rpc.BadRequest` details of an `INVALID_ARGUMENT` status, if any.
+fn validation_error(status: &Status) -> crate::ValidationError {
+    let violations = status
+        .get_details_bad_request()
+        .map(|bad_request| {
+            bad_request
+                .field_violations
+                .into_iter()
+                .map(|violation| {
+                    crate::FieldViolation::new(
+                        violation.field,
+                        violation.reason,
+                        violation.description,
+                    )
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    crate::ValidationError::new(status.message().to_string(), violations)
+}
+
+impl From<Status> for BulkCreatePostsError {
+    fn from(status: Status) -> Self {
+        match status.code() {
+            Code::InvalidArgument => {
+                BulkCreatePostsError::InvalidBatch(status.message().to_string())
+            }
+            Code::Unauthenticated => BulkCreatePostsError::Unauthorized,
+            Code::PermissionDenied => BulkCreatePostsError::Forbidden(status.message().to_string()),
+            _ => BulkCreatePostsError::Unexpected(status.message().to_string()),
+        }
+    }
+}
+
+impl From<Status> for BatchGetPostsError {
+    fn from(status: Status) -> Self {
+        match status.code() {
+            Code::InvalidArgument => BatchGetPostsError::InvalidBatch(status.message().to_string()),
+            _ => BatchGetPostsError::Unexpected(status.message().to_string()),
+        }
+    }
+}
+
 impl From<Status> for CreatePostError {
     fn from(status: Status) -> Self {
         match status.code() {
-            Code::InvalidArgument => CreatePostError::InvalidPost(status.message().to_string()),
+            Code::InvalidArgument => CreatePostError::InvalidPost(validation_error(&status)),
             Code::Unauthenticated => CreatePostError::Unauthorized,
+            Code::PermissionDenied => CreatePostError::Forbidden(status.message().to_string()),
             _ => CreatePostError::Unexpected(status.message().to_string()),
         }
     }
@@ -259,17 +533,80 @@ impl TryFrom<&crate::AuthorizedCommand<'_, crate::CreatePostCommand>>
 impl From<Status> for LoginError {
     fn from(status: Status) -> Self {
         match status.code() {
-            Code::NotFound | Code::InvalidArgument => LoginError::InvalidUserNameOrPassword,
+            Code::InvalidArgument => {
+                let error = validation_error(&status);
+                if error
+                    .violations()
+                    .iter()
+                    .any(|violation| violation.field() == SCOPES_FIELD)
+                {
+                    LoginError::InvalidScope(error)
+                } else {
+                    LoginError::InvalidUserNameOrPassword
+                }
+            }
+            Code::NotFound => LoginError::InvalidUserNameOrPassword,
             _ => LoginError::Unexpected(status.message().to_string()),
         }
     }
 }
 
+impl TryFrom<ApiToken> for crate::ApiToken {
+    type Error = String;
+    fn try_from(value: ApiToken) -> Result<Self, Self::Error> {
+        let id = Uuid::try_parse(value.id.as_str()).map_err(|_| "Invalid UUID")?;
+        let created_at = value.created_at.ok_or("Missing created_at")?;
+        Ok(crate::ApiToken::new(
+            id,
+            value.name,
+            value.scopes,
+            timestamp_to_date_time(created_at),
+            value.expires_at.map(timestamp_to_date_time),
+            value.last_used_at.map(timestamp_to_date_time),
+            value.revoked_at.map(timestamp_to_date_time),
+        ))
+    }
+}
+
+impl From<Status> for ApiTokenError {
+    fn from(status: Status) -> Self {
+        match status.code() {
+            Code::Unauthenticated => ApiTokenError::Unauthorized,
+            Code::PermissionDenied => ApiTokenError::Forbidden(status.message().to_string()),
+            Code::InvalidArgument => ApiTokenError::InvalidToken(status.message().to_string()),
+            Code::NotFound => ApiTokenError::NotFound,
+            _ => ApiTokenError::Unexpected(status.message().to_string()),
+        }
+    }
+}
+
+impl From<Status> for TwoFactorError {
+    fn from(status: Status) -> Self {
+        match status.code() {
+            Code::Unauthenticated => TwoFactorError::Unauthorized,
+            Code::PermissionDenied => TwoFactorError::Forbidden(status.message().to_string()),
+            Code::FailedPrecondition => TwoFactorError::Conflict(status.message().to_string()),
+            Code::InvalidArgument => TwoFactorError::InvalidCode,
+            _ => TwoFactorError::Unexpected(status.message().to_string()),
+        }
+    }
+}
+
+impl From<&crate::VerifyTwoFactorCommand> for Request<VerifyTwoFactorCommand> {
+    fn from(cmd: &crate::VerifyTwoFactorCommand) -> Self {
+        Request::new(VerifyTwoFactorCommand {
+            challenge_token: cmd.get_challenge_token().to_string(),
+            code: cmd.get_code().to_string(),
+        })
+    }
+}
+
 impl From<&crate::LoginCommand> for Request<VerifyUserCommand> {
     fn from(cmd: &crate::LoginCommand) -> Self {
         Request::new(VerifyUserCommand {
             username: cmd.get_username().to_string(),
             password: cmd.get_password().to_string(),
+            scopes: cmd.get_scopes().to_vec(),
         })
     }
 }
@@ -278,7 +615,7 @@ impl From<Status> for RegisterUserError {
     fn from(status: Status) -> Self {
         match status.code() {
             Code::AlreadyExists => RegisterUserError::UsernameOrEmailExist,
-            Code::InvalidArgument => RegisterUserError::InvalidUser(status.message().to_string()),
+            Code::InvalidArgument => RegisterUserError::InvalidUser(validation_error(&status)),
             _ => RegisterUserError::Unexpected(status.message().to_string()),
         }
     }
@@ -294,9 +631,55 @@ impl From<&crate::RegisterUserCommand> for Request<RegisterUserCommand> {
     }
 }
 
+fn timestamp_to_date_time(value: prost_types::Timestamp) -> chrono::DateTime<chrono::Utc> {
+    chrono::DateTime::from_timestamp(value.seconds, value.nanos as u32).unwrap_or_default()
+}
+
 fn add_token<T>(request: &mut Request<T>, token: &str) -> Result<(), InvalidMetadataValue> {
     request
         .metadata_mut()
         .insert("authorization", MetadataValue::from_str(token)?);
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tonic_types::{ErrorDetails, FieldViolation};
+
+    #[test]
+    fn login_error_reads_the_scopes_violation() {
+        let status = Status::with_error_details(
+            Code::InvalidArgument,
+            "unknown scope: posts:admin",
+            ErrorDetails::with_bad_request(vec![FieldViolation::new(
+                "scopes",
+                "unknown scope: posts:admin",
+            )]),
+        );
+
+        match LoginError::from(status) {
+            LoginError::InvalidScope(error) => {
+                assert_eq!(error.violations()[0].field(), "scopes");
+            }
+            other => panic!("unexpected error: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn login_error_treats_other_invalid_arguments_as_rejected_credentials() {
+        let status = Status::with_error_details(
+            Code::InvalidArgument,
+            "password is too short",
+            ErrorDetails::with_bad_request(vec![FieldViolation::new(
+                "password",
+                "password is too short",
+            )]),
+        );
+
+        assert!(matches!(
+            LoginError::from(status),
+            LoginError::InvalidUserNameOrPassword
+        ));
+    }
+}
@@ -1,12 +1,20 @@
 use crate::errors::{
-    CreatePostError, DeletePostError, GetPostError, GetPostsListError, LoginError,
-    RegisterUserError, UpdatePostError,
+    ApiTokenError, BatchGetPostsError, BatchItemError, BulkCreatePostsError, CreatePostError,
+    DeletePostError, GetPostError, GetPostsListError, LoginError, RegisterUserError,
+    TwoFactorError, UpdatePostError, WatchPostsError,
 };
+use crate::trace_context::WithTraceContext;
 use crate::{
-    AuthorizedCommand, CreatePostCommand, DeletePostCommand, GetPostCommand, GetPostsListCommand,
-    LoginCommand, Pagination, Post, RegisterUserCommand, UpdatePostCommand,
+    ApiToken, AuthorizedCommand, BatchGetPostsCommand, BulkCreatePostsCommand,
+    ConfirmTwoFactorCommand, CreateApiTokenCommand, CreatePostCommand, CreatedApiToken,
+    DeletePostCommand, EnrollTwoFactorCommand, FieldViolation, GetPostCommand, GetPostsListCommand,
+    ListApiTokensCommand, LoginCommand, LoginResult, Pagination, Post, PostEventStream,
+    RegisterUserCommand, RevokeApiTokenCommand, TwoFactorEnrollment, UpdatePostCommand,
+    ValidationError, VerifyTwoFactorCommand, WatchPostsCommand,
 };
+use reqwest::{Method, RequestBuilder};
 use serde::Deserialize;
+use std::fmt::Display;
 
 pub(crate) struct HttpClient {
     client: reqwest::Client,
@@ -14,11 +22,23 @@ pub(crate) struct HttpClient {
 }
 
 impl HttpClient {
-    pub(crate) fn new(address: String) -> Self {
-        Self {
-            client: reqwest::Client::new(),
-            address,
+    pub(crate) fn new(address: String, ca_certificate: Option<&[u8]>) -> reqwest::Result<Self> {
+        let mut builder = reqwest::Client::builder();
+        if let Some(ca_certificate) = ca_certificate {
+            builder =
+                builder.tls_certs_merge(reqwest::Certificate::from_pem_bundle(ca_certificate)?);
         }
+        Ok(Self {
+            client: builder.build()?,
+            address,
+        })
+    }
+
+    /// Starts a request to `path` on the server, carrying the trace context of the current span.
+    fn request(&self, method: Method, path: impl Display) -> RequestBuilder {
+        self.client
+            .request(method, format!("{}{}", self.address, path))
+            .with_trace_context()
     }
 
     pub(crate) async fn register_user(
@@ -32,8 +52,7 @@ impl HttpClient {
         });
 
         let response = self
-            .client
-            .post(format!("{}/api/auth/register", self.address))
+            .request(Method::POST, "/api/auth/register")
             .json(&request)
             .send()
             .await?;
@@ -42,7 +61,7 @@ impl HttpClient {
             reqwest::StatusCode::CREATED => Ok(()),
             reqwest::StatusCode::CONFLICT => Err(RegisterUserError::UsernameOrEmailExist),
             reqwest::StatusCode::UNPROCESSABLE_ENTITY => Err(RegisterUserError::InvalidUser(
-                response.json::<ErrorResponse>().await?.error,
+                response.json::<ErrorResponse>().await?.into(),
             )),
             _ => Err(RegisterUserError::Unexpected(
                 response.json::<ErrorResponse>().await?.error,
@@ -50,6 +69,86 @@ impl HttpClient {
         }
     }
 
+    pub(crate) async fn create_api_token(
+        &self,
+        cmd: &AuthorizedCommand<'_, CreateApiTokenCommand>,
+    ) -> Result<CreatedApiToken, ApiTokenError> {
+        let request = serde_json::json!({
+            "name": cmd.get_command().get_name(),
+            "scopes": cmd.get_command().get_scopes(),
+            "expires_in_days": cmd.get_command().get_expires_in_days(),
+        });
+
+        let response = self
+            .request(Method::POST, "/api/tokens")
+            .header("Authorization", format!("Bearer {}", cmd.get_token()))
+            .json(&request)
+            .send()
+            .await?;
+
+        match response.status() {
+            reqwest::StatusCode::CREATED => Ok(response.json::<CreatedApiToken>().await?),
+            reqwest::StatusCode::UNAUTHORIZED => Err(ApiTokenError::Unauthorized),
+            reqwest::StatusCode::FORBIDDEN => Err(ApiTokenError::Forbidden(
+                response.json::<ErrorResponse>().await?.error,
+            )),
+            reqwest::StatusCode::UNPROCESSABLE_ENTITY => Err(ApiTokenError::InvalidToken(
+                response.json::<ErrorResponse>().await?.error,
+            )),
+            _ => Err(ApiTokenError::Unexpected(
+                response.json::<ErrorResponse>().await?.error,
+            )),
+        }
+    }
+
+    pub(crate) async fn list_api_tokens(
+        &self,
+        cmd: &AuthorizedCommand<'_, ListApiTokensCommand>,
+    ) -> Result<Vec<ApiToken>, ApiTokenError> {
+        let response = self
+            .request(Method::GET, "/api/tokens")
+            .header("Authorization", format!("Bearer {}", cmd.get_token()))
+            .send()
+            .await?;
+
+        match response.status() {
+            reqwest::StatusCode::OK => Ok(response.json::<Vec<ApiToken>>().await?),
+            reqwest::StatusCode::UNAUTHORIZED => Err(ApiTokenError::Unauthorized),
+            reqwest::StatusCode::FORBIDDEN => Err(ApiTokenError::Forbidden(
+                response.json::<ErrorResponse>().await?.error,
+            )),
+            _ => Err(ApiTokenError::Unexpected(
+                response.json::<ErrorResponse>().await?.error,
+            )),
+        }
+    }
+
+    pub(crate) async fn revoke_api_token(
+        &self,
+        cmd: &AuthorizedCommand<'_, RevokeApiTokenCommand>,
+    ) -> Result<(), ApiTokenError> {
+        let response = self
+            .request(
+                Method::DELETE,
+                format!("/api/tokens/{}", cmd.get_command().get_id()),
+            )
+            .header("Authorization", format!("Bearer {}", cmd.get_token()))
+            .send()
+            .await?;
+
+        match response.status() {
+            reqwest::StatusCode::NO_CONTENT => Ok(()),
+            reqwest::StatusCode::UNAUTHORIZED => Err(ApiTokenError::Unauthorized),
+            reqwest::StatusCode::FORBIDDEN => Err(ApiTokenError::Forbidden(
+                response.json::<ErrorResponse>().await?.error,
+            )),
+            reqwest::StatusCode::NOT_FOUND => Err(ApiTokenError::NotFound),
+            _ => Err(ApiTokenError::Unexpected(
+                response.json::<ErrorResponse>().await?.error,
+            )),
+        }
+    }
+
     pub(crate) async fn create_post(
         &self,
         cmd: &AuthorizedCommand<'_, CreatePostCommand>,
@@ -60,8 +159,7 @@ impl HttpClient {
         });
 
         let response = self
-            .client
-            .post(format!("{}/api/posts", self.address))
+            .request(Method::POST, "/api/posts")
             .header("Authorization", format!("Bearer {}", cmd.get_token()))
             .json(&request)
             .send()
@@ -70,37 +168,178 @@ impl HttpClient {
         match response.status() {
             reqwest::StatusCode::CREATED => Ok(response.json::<Post>().await?),
             reqwest::StatusCode::UNAUTHORIZED => Err(CreatePostError::Unauthorized),
-            reqwest::StatusCode::UNPROCESSABLE_ENTITY => Err(CreatePostError::InvalidPost(
+            reqwest::StatusCode::FORBIDDEN => Err(CreatePostError::Forbidden(
                 response.json::<ErrorResponse>().await?.error,
             )),
+            reqwest::StatusCode::UNPROCESSABLE_ENTITY => Err(CreatePostError::InvalidPost(
+                response.json::<ErrorResponse>().await?.into(),
+            )),
             _ => Err(CreatePostError::Unexpected(
                 response.json::<ErrorResponse>().await?.error,
             )),
         }
     }
 
-    pub(crate) async fn login(&self, cmd: &LoginCommand) -> Result<String, LoginError> {
+    pub(crate) async fn bulk_create_posts(
+        &self,
+        cmd: &AuthorizedCommand<'_, BulkCreatePostsCommand>,
+    ) -> Result<Vec<Result<Post, BatchItemError>>, BulkCreatePostsError> {
+        let posts = cmd
+            .get_command()
+            .get_posts()
+            .iter()
+            .map(|post| {
+                serde_json::json!({
+                    "title": post.get_title(),
+                    "content": post.get_content(),
+                })
+            })
+            .collect::<Vec<_>>();
+        let request = serde_json::json!({
+            "posts": posts,
+            "transactional": cmd.get_command().is_transactional(),
+        });
+
+        let response = self
+            .request(Method::POST, "/api/posts/batch")
+            .header("Authorization", format!("Bearer {}", cmd.get_token()))
+            .json(&request)
+            .send()
+            .await?;
+
+        match response.status() {
+            reqwest::StatusCode::OK => Ok(response.json::<BatchResponse>().await?.into_results()),
+            reqwest::StatusCode::UNAUTHORIZED => Err(BulkCreatePostsError::Unauthorized),
+            reqwest::StatusCode::FORBIDDEN => Err(BulkCreatePostsError::Forbidden(
+                response.json::<ErrorResponse>().await?.error,
+            )),
+            reqwest::StatusCode::UNPROCESSABLE_ENTITY => Err(BulkCreatePostsError::InvalidBatch(
+                response.json::<ErrorResponse>().await?.error,
+            )),
+            _ => Err(BulkCreatePostsError::Unexpected(
+                response.json::<ErrorResponse>().await?.error,
+            )),
+        }
+    }
+
+    pub(crate) async fn login(&self, cmd: &LoginCommand) -> Result<LoginResult, LoginError> {
         let request = serde_json::json!({
             "username": cmd.get_username(),
             "password": cmd.get_password(),
+            "scopes": cmd.get_scopes(),
         });
 
         let response = self
-            .client
-            .post(format!("{}/api/auth/login", self.address))
+            .request(Method::POST, "/api/auth/login")
             .json(&request)
             .send()
             .await?;
 
         match response.status() {
-            reqwest::StatusCode::OK => Ok(response.json::<LoginResponse>().await?.token),
+            reqwest::StatusCode::OK => {
+                let response = response.json::<LoginResponse>().await?;
+                match (response.token, response.challenge_token) {
+                    (_, Some(challenge_token)) => {
+                        Ok(LoginResult::TwoFactorRequired(challenge_token))
+                    }
+                    (Some(token), None) => Ok(LoginResult::Token(token)),
+                    (None, None) => Err(LoginError::Unexpected("missing token".to_string())),
+                }
+            }
             reqwest::StatusCode::UNAUTHORIZED => Err(LoginError::InvalidUserNameOrPassword),
+            reqwest::StatusCode::UNPROCESSABLE_ENTITY => Err(LoginError::InvalidScope(
+                response.json::<ErrorResponse>().await?.into(),
+            )),
             _ => Err(LoginError::Unexpected(
                 response.json::<ErrorResponse>().await?.error,
             )),
         }
     }
 
+    pub(crate) async fn verify_two_factor(
+        &self,
+        cmd: &VerifyTwoFactorCommand,
+    ) -> Result<String, LoginError> {
+        let request = serde_json::json!({
+            "challenge_token": cmd.get_challenge_token(),
+            "code": cmd.get_code(),
+        });
+
+        let response = self
+            .request(Method::POST, "/api/auth/2fa/verify")
+            .json(&request)
+            .send()
+            .await?;
+
+        match response.status() {
+            reqwest::StatusCode::OK => response
+                .json::<LoginResponse>()
+                .await?
+                .token
+                .ok_or_else(|| LoginError::Unexpected("missing token".to_string())),
+            reqwest::StatusCode::UNAUTHORIZED => Err(LoginError::InvalidTwoFactorCode),
+            _ => Err(LoginError::Unexpected(
+                response.json::<ErrorResponse>().await?.error,
+            )),
+        }
+    }
+
+    pub(crate) async fn enroll_two_factor(
+        &self,
+        cmd: &AuthorizedCommand<'_, EnrollTwoFactorCommand>,
+    ) -> Result<TwoFactorEnrollment, TwoFactorError> {
+        let response = self
+            .request(Method::POST, "/api/auth/2fa/enroll")
+            .header("Authorization", format!("Bearer {}", cmd.get_token()))
+            .send()
+            .await?;
+
+        match response.status() {
+            reqwest::StatusCode::OK => Ok(response.json::<TwoFactorEnrollment>().await?),
+            reqwest::StatusCode::UNAUTHORIZED => Err(TwoFactorError::Unauthorized),
+            reqwest::StatusCode::FORBIDDEN => Err(TwoFactorError::Forbidden(
+                response.json::<ErrorResponse>().await?.error,
+            )),
+            reqwest::StatusCode::CONFLICT => Err(TwoFactorError::Conflict(
+                response.json::<ErrorResponse>().await?.error,
+            )),
+            _ => Err(TwoFactorError::Unexpected(
+                response.json::<ErrorResponse>().await?.error,
+            )),
+        }
+    }
+
+    pub(crate) async fn confirm_two_factor(
+        &self,
+        cmd: &AuthorizedCommand<'_, ConfirmTwoFactorCommand>,
+    ) -> Result<(), TwoFactorError> {
+        let request = serde_json::json!({
+            "code": cmd.get_command().get_code(),
+        });
+
+        let response = self
+            .request(Method::POST, "/api/auth/2fa/confirm")
+            .header("Authorization", format!("Bearer {}", cmd.get_token()))
+            .json(&request)
+            .send()
+            .await?;
+
+        match response.status() {
+            reqwest::StatusCode::NO_CONTENT => Ok(()),
+            reqwest::StatusCode::UNAUTHORIZED => Err(TwoFactorError::Unauthorized),
+            reqwest::StatusCode::FORBIDDEN => Err(TwoFactorError::Forbidden(
+                response.json::<ErrorResponse>().await?.error,
+            )),
+            reqwest::StatusCode::CONFLICT => Err(TwoFactorError::Conflict(
+                response.json::<ErrorResponse>().await?.error,
+            )),
+            reqwest::StatusCode::UNPROCESSABLE_ENTITY => Err(TwoFactorError::InvalidCode),
+            _ => Err(TwoFactorError::Unexpected(
+                response.json::<ErrorResponse>().await?.error,
+            )),
+        }
+    }
+
     pub(crate) async fn update_post(
         &self,
         cmd: &AuthorizedCommand<'_, UpdatePostCommand>,
@@ -111,12 +350,10 @@ impl HttpClient {
         });
 
         let response = self
-            .client
-            .put(format!(
-                "{}/api/posts/{}",
-                self.address,
-                cmd.get_command().get_id()
-            ))
+            .request(
+                Method::PUT,
+                format!("/api/posts/{}", cmd.get_command().get_id()),
+            )
             .header("Authorization", format!("Bearer {}", cmd.get_token()))
             .json(&request)
             .send()
@@ -128,7 +365,7 @@ impl HttpClient {
             reqwest::StatusCode::NOT_FOUND => Err(UpdatePostError::NotFound),
             reqwest::StatusCode::FORBIDDEN => Err(UpdatePostError::Forbidden),
             reqwest::StatusCode::UNPROCESSABLE_ENTITY => Err(UpdatePostError::InvalidPost(
-                response.json::<ErrorResponse>().await?.error,
+                response.json::<ErrorResponse>().await?.into(),
             )),
             _ => Err(UpdatePostError::Unexpected(
                 response.json::<ErrorResponse>().await?.error,
@@ -141,12 +378,10 @@ impl HttpClient {
         cmd: &AuthorizedCommand<'_, DeletePostCommand>,
     ) -> Result<(), DeletePostError> {
         let response = self
-            .client
-            .delete(format!(
-                "{}/api/posts/{}",
-                self.address,
-                cmd.get_command().get_id()
-            ))
+            .request(
+                Method::DELETE,
+                format!("/api/posts/{}", cmd.get_command().get_id()),
+            )
             .header("Authorization", format!("Bearer {}", cmd.get_token()))
             .send()
             .await?;
@@ -164,8 +399,7 @@ impl HttpClient {
 
     pub(crate) async fn get_post(&self, cmd: &GetPostCommand) -> Result<Post, GetPostError> {
         let response = self
-            .client
-            .get(format!("{}/api/posts/{}", self.address, cmd.get_id()))
+            .request(Method::GET, format!("/api/posts/{}", cmd.get_id()))
             .send()
             .await?;
 
@@ -178,18 +412,45 @@ impl HttpClient {
         }
     }
 
+    pub(crate) async fn batch_get_posts(
+        &self,
+        cmd: &BatchGetPostsCommand,
+    ) -> Result<Vec<Result<Post, BatchItemError>>, BatchGetPostsError> {
+        let ids = cmd
+            .get_ids()
+            .iter()
+            .map(|id| id.to_string())
+            .collect::<Vec<_>>()
+            .join(",");
+        let response = self
+            .request(Method::GET, format!("/api/posts/batch?ids={}", ids))
+            .send()
+            .await?;
+
+        match response.status() {
+            reqwest::StatusCode::OK => Ok(response.json::<BatchResponse>().await?.into_results()),
+            reqwest::StatusCode::UNPROCESSABLE_ENTITY => Err(BatchGetPostsError::InvalidBatch(
+                response.json::<ErrorResponse>().await?.error,
+            )),
+            _ => Err(BatchGetPostsError::Unexpected(
+                response.json::<ErrorResponse>().await?.error,
+            )),
+        }
+    }
+
     pub(crate) async fn get_post_list(
         &self,
         cmd: &GetPostsListCommand,
     ) -> Result<Pagination<Post>, GetPostsListError> {
         let response = self
-            .client
-            .get(format!(
-                "{}/api/posts?limit={}&offset={}",
-                self.address,
-                cmd.get_limit(),
-                cmd.get_offset()
-            ))
+            .request(
+                Method::GET,
+                format!(
+                    "/api/posts?limit={}&offset={}",
+                    cmd.get_limit(),
+                    cmd.get_offset()
+                ),
+            )
             .send()
             .await?;
 
@@ -208,6 +469,13 @@ impl HttpClient {
             )),
         }
     }
+
+    pub(crate) async fn watch_posts(
+        &self,
+        _cmd: &WatchPostsCommand,
+    ) -> Result<PostEventStream, WatchPostsError> {
+        Err(WatchPostsError::Unsupported("HTTP"))
+    }
 }
 
 impl From<reqwest::Error> for GetPostsListError {
@@ -234,6 +502,18 @@ impl From<reqwest::Error> for UpdatePostError {
     }
 }
 
+impl From<reqwest::Error> for BulkCreatePostsError {
+    fn from(err: reqwest::Error) -> Self {
+        BulkCreatePostsError::Unexpected(err.to_string())
+    }
+}
+
+impl From<reqwest::Error> for BatchGetPostsError {
+    fn from(err: reqwest::Error) -> Self {
+        BatchGetPostsError::Unexpected(err.to_string())
+    }
+}
+
 impl From<reqwest::Error> for CreatePostError {
     fn from(err: reqwest::Error) -> Self {
         CreatePostError::Unexpected(err.to_string())
@@ -246,6 +526,18 @@ impl From<reqwest::Error> for RegisterUserError {
     }
 }
 
+impl From<reqwest::Error> for ApiTokenError {
+    fn from(err: reqwest::Error) -> Self {
+        ApiTokenError::Unexpected(err.to_string())
+    }
+}
+
+impl From<reqwest::Error> for TwoFactorError {
+    fn from(err: reqwest::Error) -> Self {
+        TwoFactorError::Unexpected(err.to_string())
+    }
+}
+
 impl From<reqwest::Error> for LoginError {
     fn from(err: reqwest::Error) -> Self {
         LoginError::Unexpected(err.to_string())
@@ -254,12 +546,76 @@ impl From<reqwest::Error> for LoginError {
 
 #[derive(Deserialize)]
 struct LoginResponse {
-    token: String,
+    token: Option<String>,
+    challenge_token: Option<String>,
 }
 
 #[derive(Deserialize)]
 struct ErrorResponse {
     error: String,
+    #[serde(default)]
+    violations: Vec<ViolationResponse>,
+}
+
+impl From<ErrorResponse> for ValidationError {
+    fn from(response: ErrorResponse) -> Self {
+        ValidationError::new(response.error, into_violations(response.violations))
+    }
+}
+
+#[derive(Deserialize)]
+struct ViolationResponse {
+    field: String,
+    code: String,
+    detail: String,
+}
+
+fn into_violations(violations: Vec<ViolationResponse>) -> Vec<FieldViolation> {
+    violations
+        .into_iter()
+        .map(|violation| FieldViolation::new(violation.field, violation.code, violation.detail))
+        .collect()
+}
+
+#[derive(Deserialize)]
+struct BatchResponse {
+    results: Vec<BatchItemResponse>,
+}
+
+impl BatchResponse {
+    fn into_results(self) -> Vec<Result<Post, BatchItemError>> {
+        self.results
+            .into_iter()
+            .map(BatchItemResponse::into_result)
+            .collect()
+    }
+}
+
+/// Outcome of one batch item, carrying the status the item would get on its own.
+#[derive(Deserialize)]
+struct BatchItemResponse {
+    status: u16,
+    post: Option<Post>,
+    error: Option<String>,
+    #[serde(default)]
+    violations: Vec<ViolationResponse>,
+}
+
+impl BatchItemResponse {
+    fn into_result(self) -> Result<Post, BatchItemError> {
+        if let Some(post) = self.post {
+            return Ok(post);
+        }
+        let error = self.error.unwrap_or_default();
+        Err(match reqwest::StatusCode::from_u16(self.status) {
+            Ok(reqwest::StatusCode::NOT_FOUND) => BatchItemError::NotFound,
+            Ok(reqwest::StatusCode::UNPROCESSABLE_ENTITY) => BatchItemError::InvalidPost(
+                ValidationError::new(error, into_violations(self.violations)),
+            ),
+            Ok(reqwest::StatusCode::CONFLICT) => BatchItemError::Aborted(error),
+            _ => BatchItemError::Unexpected(error),
+        })
+    }
 }
 
 #[derive(Deserialize)]
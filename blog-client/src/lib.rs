@@ -2,5 +2,6 @@ mod blog_client;
 mod errors;
 mod grpc_client;
 mod http_client;
+mod trace_context;
 
 pub use blog_client::*;
@@ -0,0 +1,73 @@
+use opentelemetry::global;
+use std::collections::HashMap;
+use tonic::metadata::{MetadataKey, MetadataValue};
+use tonic::service::Interceptor;
+use tonic::{Request, Status};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+/// Trace context of the current span, serialized by the global text map propagator.
+///
+/// Empty unless the application exports spans through `tracing-opentelemetry` and installed
+/// a propagator, e.g. `TraceContextPropagator` for W3C `traceparent` headers.
+pub(crate) fn trace_context_headers() -> HashMap<String, String> {
+    let context = tracing::Span::current().context();
+    let mut headers = HashMap::new();
+    global::get_text_map_propagator(|propagator| propagator.inject_context(&context, &mut headers));
+    headers
+}
+
+pub(crate) trait WithTraceContext {
+    /// Adds the trace context of the current span to the outgoing request.
+    fn with_trace_context(self) -> Self;
+}
+
+impl WithTraceContext for reqwest::RequestBuilder {
+    fn with_trace_context(self) -> Self {
+        trace_context_headers()
+            .into_iter()
+            .fold(self, |request, (name, value)| request.header(name, value))
+    }
+}
+
+/// Adds the trace context of the current span to every outgoing gRPC call.
+#[derive(Clone)]
+pub(crate) struct TraceContextInterceptor;
+
+impl Interceptor for TraceContextInterceptor {
+    fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, Status> {
+        for (name, value) in trace_context_headers() {
+            if let (Ok(name), Ok(value)) = (
+                MetadataKey::from_bytes(name.as_bytes()),
+                MetadataValue::try_from(value),
+            ) {
+                request.metadata_mut().insert(name, value);
+            }
+        }
+        Ok(request)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opentelemetry::trace::TracerProvider;
+    use opentelemetry_sdk::propagation::TraceContextPropagator;
+    use opentelemetry_sdk::trace::SdkTracerProvider;
+    use tracing_subscriber::Registry;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[test]
+    fn trace_context_headers_carry_the_current_span() {
+        global::set_text_map_propagator(TraceContextPropagator::new());
+        let tracer_provider = SdkTracerProvider::builder().build();
+        let subscriber = Registry::default()
+            .with(tracing_opentelemetry::layer().with_tracer(tracer_provider.tracer("test")));
+
+        let headers = tracing::subscriber::with_default(subscriber, || {
+            let _span = tracing::info_span!("outgoing call").entered();
+            trace_context_headers()
+        });
+
+        assert!(headers["traceparent"].starts_with("00-"));
+    }
+}
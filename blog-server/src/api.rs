@@ -1,2 +1,7 @@
+mod authentication;
+mod forwarded;
 pub(crate) mod grpc;
 pub(crate) mod http;
+
+pub(crate) use authentication::*;
+pub(crate) use forwarded::*;
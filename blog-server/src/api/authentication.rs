@@ -0,0 +1,148 @@
+use crate::application::api_tokens::{
+    AuthenticateApiTokenError, authenticate_api_token_handler, is_api_token,
+};
+use crate::application::contracts::{ApiTokenRepository, UnitOfWork};
+use crate::domain::value_objects::{Identification, Scope};
+use crate::infrastructure::{JwtService, RateLimitSubject};
+use std::net::{IpAddr, Ipv4Addr};
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// The authenticated caller of a request together with the scopes granted to its token.
+#[derive(Debug, Clone)]
+pub(crate) struct Principal {
+    user_id: Uuid,
+    scopes: Vec<Scope>,
+}
+
+impl Principal {
+    pub(crate) fn user_id(&self) -> Identification {
+        Identification::from(self.user_id)
+    }
+
+    pub(crate) fn scopes(&self) -> &[Scope] {
+        &self.scopes
+    }
+
+    pub(crate) fn has_scope(&self, scope: Scope) -> bool {
+        self.scopes.contains(&scope)
+    }
+}
+
+/// Resolves a bearer token, either a JWT or a personal access token, into a principal.
+pub(crate) async fn authenticate(
+    token: &str,
+    jwt_service: &JwtService,
+    token_repo: &Arc<dyn ApiTokenRepository>,
+    unit_of_work: &Arc<dyn UnitOfWork>,
+) -> Result<Principal, AuthenticationError> {
+    if is_api_token(token) {
+        let api_token = authenticate_api_token_handler(token, token_repo, unit_of_work)
+            .await
+            .map_err(|err| match err {
+                AuthenticateApiTokenError::InvalidToken => AuthenticationError::InvalidToken,
+                AuthenticateApiTokenError::Unavailable => AuthenticationError::Unavailable,
+                AuthenticateApiTokenError::Unexpected(error) => {
+                    AuthenticationError::Unexpected(error)
+                }
+            })?;
+        return Ok(Principal {
+            user_id: *api_token.user_id().as_ref(),
+            scopes: api_token.scopes().to_vec(),
+        });
+    }
+
+    let claims = jwt_service
+        .decode_jwt(token)
+        .map_err(|_| AuthenticationError::InvalidToken)?;
+    Ok(Principal {
+        user_id: claims.sub(),
+        scopes: claims.scopes(),
+    })
+}
+
+/// Picks the rate limit budget of a request without touching storage, so that it can run
+/// before authentication: a JWT with a valid signature is charged to its user, and every other
+/// request, including those with a personal access token or an invalid token, to the client
+/// address. Requests without a known client address share one budget.
+pub(crate) fn rate_limit_subject(
+    token: Option<&str>,
+    client_ip: Option<IpAddr>,
+    jwt_service: &JwtService,
+) -> RateLimitSubject {
+    let user_id = token
+        .filter(|token| !is_api_token(token))
+        .and_then(|token| jwt_service.decode_jwt(token).ok())
+        .map(|claims| claims.sub());
+    match user_id {
+        Some(user_id) => RateLimitSubject::User(user_id),
+        None => RateLimitSubject::Anonymous(client_ip.unwrap_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED))),
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum AuthenticationError {
+    #[error("invalid token")]
+    InvalidToken,
+    #[error("storage is temporarily unavailable")]
+    Unavailable,
+    #[error("unexpected error: {0}")]
+    Unexpected(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::entities::User;
+    use crate::domain::value_objects::{Email, PasswordHash, UserName};
+    use secrecy::SecretString;
+
+    fn jwt_service() -> JwtService {
+        JwtService::new(SecretString::from("0123456789abcdef0123456789abcdef"))
+    }
+
+    fn user() -> User {
+        User::new(
+            UserName::try_from("jane_doe".to_string()).unwrap(),
+            Email::try_from("jane@example.com".to_string()).unwrap(),
+            PasswordHash::from(SecretString::from("hash")),
+        )
+    }
+
+    #[test]
+    fn rate_limit_subject_charges_valid_jwts_to_their_user() {
+        let jwt_service = jwt_service();
+        let user = user();
+        let token = jwt_service.generate_jwt(&user, &[]).unwrap();
+
+        assert_eq!(
+            rate_limit_subject(Some(&token), None, &jwt_service),
+            RateLimitSubject::User(*user.id().as_ref())
+        );
+    }
+
+    #[test]
+    fn rate_limit_subject_charges_other_tokens_to_the_client_address() {
+        let jwt_service = jwt_service();
+        let forged = JwtService::new(SecretString::from("fedcba9876543210fedcba9876543210"))
+            .generate_jwt(&user(), &[])
+            .unwrap();
+        let client_ip = "203.0.113.7".parse().unwrap();
+
+        for token in [
+            None,
+            Some("blog_pat_junk"),
+            Some("not a jwt"),
+            Some(&forged),
+        ] {
+            assert_eq!(
+                rate_limit_subject(token, Some(client_ip), &jwt_service),
+                RateLimitSubject::Anonymous(client_ip)
+            );
+        }
+        assert_eq!(
+            rate_limit_subject(None, None, &jwt_service),
+            RateLimitSubject::Anonymous(IpAddr::V4(Ipv4Addr::UNSPECIFIED))
+        );
+    }
+}
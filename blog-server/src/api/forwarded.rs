@@ -0,0 +1,139 @@
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+
+/// Header in which the single-port listener sends the [`ForwardSecret`] to the HTTP server.
+pub(crate) const FORWARD_SECRET_HEADER: &str = "x-forward-secret";
+const FORWARD_SECRET_BYTES: usize = 32;
+
+/// Random secret, generated per process, that the single-port listener sends with every request
+/// it forwards to the internal HTTP listener. Any local process can connect to that listener, so
+/// the client address it forwards is only trusted on requests that carry the secret.
+#[derive(Clone)]
+pub(crate) struct ForwardSecret(Arc<str>);
+
+impl ForwardSecret {
+    pub(crate) fn generate() -> Self {
+        let mut bytes = [0u8; FORWARD_SECRET_BYTES];
+        OsRng.fill_bytes(&mut bytes);
+        let secret = bytes
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect::<String>();
+        Self(secret.into())
+    }
+
+    pub(crate) fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Compares in constant time, so that the secret cannot be guessed byte by byte.
+    pub(crate) fn matches(&self, value: &[u8]) -> bool {
+        let secret = self.0.as_bytes();
+        secret.len() == value.len()
+            && secret
+                .iter()
+                .zip(value)
+                .fold(0, |difference, (a, b)| difference | (a ^ b))
+                == 0
+    }
+}
+
+/// Client address that a reverse proxy put in the `Forwarded` or `X-Forwarded-For` header,
+/// given the last line of each header.
+///
+/// Only the right-most entry is used: it is the one the proxy in front of the server added,
+/// while the entries before it came from the client and cannot be trusted.
+pub(crate) fn forwarded_client_ip(
+    forwarded: Option<&str>,
+    x_forwarded_for: Option<&str>,
+) -> Option<IpAddr> {
+    match forwarded {
+        Some(forwarded) => forwarded.rsplit(',').next().and_then(forwarded_for),
+        None => x_forwarded_for
+            .and_then(|value| value.rsplit(',').next())
+            .and_then(|ip| ip.trim().parse().ok()),
+    }
+}
+
+/// Parses the `for` parameter of one `Forwarded` element, e.g. `for="[2001:db8::1]:4711"`.
+fn forwarded_for(element: &str) -> Option<IpAddr> {
+    let node = element.split(';').find_map(|pair| {
+        let (key, value) = pair.split_once('=')?;
+        key.trim()
+            .eq_ignore_ascii_case("for")
+            .then(|| value.trim().trim_matches('"'))
+    })?;
+    node.parse()
+        .ok()
+        .or_else(|| node.parse::<SocketAddr>().ok().map(|addr| addr.ip()))
+        .or_else(|| {
+            node.strip_prefix('[')
+                .and_then(|node| node.strip_suffix(']'))
+                .and_then(|ip| ip.parse().ok())
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(ip: &str) -> Option<IpAddr> {
+        Some(ip.parse().unwrap())
+    }
+
+    #[test]
+    fn forward_secret_only_matches_itself() {
+        let secret = ForwardSecret::generate();
+
+        assert!(secret.matches(secret.as_str().as_bytes()));
+        assert!(!secret.matches(ForwardSecret::generate().as_str().as_bytes()));
+        assert!(!secret.matches(&secret.as_str().as_bytes()[1..]));
+        assert!(!secret.matches(b""));
+    }
+
+    #[test]
+    fn forwarded_client_ip_takes_the_entry_added_by_the_proxy() {
+        assert_eq!(
+            forwarded_client_ip(None, Some("10.0.0.1, 203.0.113.7")),
+            ip("203.0.113.7")
+        );
+        assert_eq!(
+            forwarded_client_ip(Some("for=10.0.0.1, for=203.0.113.7;proto=https"), None),
+            ip("203.0.113.7")
+        );
+    }
+
+    #[test]
+    fn forwarded_client_ip_prefers_forwarded_header() {
+        assert_eq!(
+            forwarded_client_ip(Some("for=203.0.113.7"), Some("198.51.100.1")),
+            ip("203.0.113.7")
+        );
+    }
+
+    #[test]
+    fn forwarded_client_ip_accepts_quoted_nodes_with_ports() {
+        assert_eq!(
+            forwarded_client_ip(Some(r#"For="[2001:db8::1]:4711""#), None),
+            ip("2001:db8::1")
+        );
+        assert_eq!(
+            forwarded_client_ip(Some(r#"for="[2001:db8::1]""#), None),
+            ip("2001:db8::1")
+        );
+        assert_eq!(
+            forwarded_client_ip(Some(r#"for="203.0.113.7:4711""#), None),
+            ip("203.0.113.7")
+        );
+    }
+
+    #[test]
+    fn forwarded_client_ip_ignores_unknown_or_missing_nodes() {
+        assert_eq!(forwarded_client_ip(Some("for=unknown"), None), None);
+        assert_eq!(forwarded_client_ip(Some("for=_hidden"), None), None);
+        assert_eq!(forwarded_client_ip(Some("proto=https"), None), None);
+        assert_eq!(forwarded_client_ip(None, Some("not an ip")), None);
+        assert_eq!(forwarded_client_ip(None, None), None);
+    }
+}
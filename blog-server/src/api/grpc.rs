@@ -1,6 +1,7 @@
 mod commands;
 mod errors;
 pub(crate) mod grpc_handlers;
+pub(crate) mod middleware;
 mod results;
 
 pub(crate) mod proto {
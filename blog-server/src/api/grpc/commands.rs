@@ -1,5 +1,6 @@
 use crate::api::grpc::blog::{
-    CreatePostCommand, RegisterUserCommand, UpdatePostCommand, VerifyUserCommand,
+    BulkCreatePostsItem, ConfirmTwoFactorCommand, CreateApiTokenCommand, CreatePostCommand,
+    RegisterUserCommand, UpdatePostCommand, VerifyTwoFactorCommand, VerifyUserCommand,
 };
 use secrecy::SecretString;
 use tonic::Request;
@@ -18,7 +19,46 @@ impl From<Request<RegisterUserCommand>> for crate::application::auth::RegisterUs
 impl From<Request<VerifyUserCommand>> for crate::application::auth::VerifyUserCommand {
     fn from(value: Request<VerifyUserCommand>) -> Self {
         let command = value.into_inner();
-        Self::new(command.username, SecretString::from(command.password))
+        Self::new(
+            command.username,
+            SecretString::from(command.password),
+            Some(command.scopes),
+        )
+    }
+}
+
+impl From<Request<VerifyTwoFactorCommand>> for crate::application::auth::VerifyTwoFactorCommand {
+    fn from(value: Request<VerifyTwoFactorCommand>) -> Self {
+        let command = value.into_inner();
+        Self::new(
+            SecretString::from(command.challenge_token),
+            SecretString::from(command.code),
+        )
+    }
+}
+
+impl From<Request<ConfirmTwoFactorCommand>> for crate::application::auth::ConfirmTwoFactorCommand {
+    fn from(value: Request<ConfirmTwoFactorCommand>) -> Self {
+        Self::new(SecretString::from(value.into_inner().code))
+    }
+}
+
+impl From<Request<CreateApiTokenCommand>>
+    for crate::application::api_tokens::CreateApiTokenCommand
+{
+    fn from(value: Request<CreateApiTokenCommand>) -> Self {
+        let command = value.into_inner();
+        let expires_in_days = match command.expires_in_days {
+            0 => None,
+            days => Some(days),
+        };
+        Self::new(command.name, command.scopes, expires_in_days)
+    }
+}
+
+impl From<BulkCreatePostsItem> for crate::application::blog::CreatePostCommand {
+    fn from(value: BulkCreatePostsItem) -> Self {
+        Self::new(value.title, value.content)
     }
 }
 
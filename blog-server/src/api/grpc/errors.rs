@@ -1,21 +1,73 @@
-use crate::application::auth::{RegisterUserError, VerifyUserError};
+use crate::application::api_tokens::{
+    CreateApiTokenError, ListApiTokensError, RevokeApiTokenError,
+};
+use crate::application::auth::{
+    ConfirmTwoFactorError, EnrollTwoFactorError, RegisterUserError, VerifyTwoFactorError,
+    VerifyUserError,
+};
 use crate::application::blog::{
-    CreatePostError, DeletePostError, GetPostError, GetPostListError, UpdatePostError,
+    BatchGetPostsError, BulkCreatePostItemError, BulkCreatePostsError, CreatePostError,
+    DeletePostError, GetPostError, GetPostListError, UpdatePostError,
 };
-use tonic::Status;
+use crate::domain::value_objects::{FieldViolation, describe_violations};
+use prost::Message;
+use prost_types::Any;
+use std::collections::HashMap;
+use tonic::{Code, Status};
+use tonic_types::{BadRequest, ErrorInfo, pb};
+
+/// Domain of the `google.rpc.ErrorInfo` attached to validation errors.
+const ERROR_DOMAIN: &str = "blog.BlogService";
+/// Reason of the `google.rpc.ErrorInfo` attached to validation errors.
+const VALIDATION_FAILED: &str = "VALIDATION_FAILED";
 
 impl From<CreatePostError> for Status {
     fn from(value: CreatePostError) -> Self {
         match value {
-            CreatePostError::InvalidTitle(err) => Status::invalid_argument(err.to_string()),
+            CreatePostError::InvalidTitle(err) => invalid_fields(&[FieldViolation::from(&err)]),
+            CreatePostError::Unavailable => create_unavailable_error(),
             CreatePostError::Unexpected(_) => create_internal_error(),
         }
     }
 }
 
+impl From<BulkCreatePostsError> for Status {
+    fn from(value: BulkCreatePostsError) -> Self {
+        match value {
+            BulkCreatePostsError::InvalidBatchSize => Status::invalid_argument(value.to_string()),
+            BulkCreatePostsError::Unavailable => create_unavailable_error(),
+            BulkCreatePostsError::Unexpected(_) => create_internal_error(),
+        }
+    }
+}
+
+impl From<BulkCreatePostItemError> for Status {
+    fn from(value: BulkCreatePostItemError) -> Self {
+        match value {
+            BulkCreatePostItemError::InvalidTitle(err) => {
+                invalid_fields(&[FieldViolation::from(&err)])
+            }
+            BulkCreatePostItemError::Aborted => Status::aborted(value.to_string()),
+            BulkCreatePostItemError::Unavailable => create_unavailable_error(),
+            BulkCreatePostItemError::Unexpected(_) => create_internal_error(),
+        }
+    }
+}
+
+impl From<BatchGetPostsError> for Status {
+    fn from(value: BatchGetPostsError) -> Self {
+        match value {
+            BatchGetPostsError::InvalidBatchSize => Status::invalid_argument(value.to_string()),
+            BatchGetPostsError::Unavailable => create_unavailable_error(),
+            BatchGetPostsError::Unexpected(_) => create_internal_error(),
+        }
+    }
+}
+
 impl From<GetPostListError> for Status {
     fn from(value: GetPostListError) -> Self {
         match value {
+            GetPostListError::Unavailable => create_unavailable_error(),
             GetPostListError::Unexpected(_) => create_internal_error(),
         }
     }
@@ -25,7 +77,8 @@ impl From<RegisterUserError> for Status {
     fn from(value: RegisterUserError) -> Self {
         match value {
             RegisterUserError::UsernameOrEmailExist => Status::already_exists(value.to_string()),
-            RegisterUserError::InvalidUser(err) => Status::invalid_argument(err),
+            RegisterUserError::InvalidUser(violations) => invalid_fields(&violations),
+            RegisterUserError::Unavailable => create_unavailable_error(),
             RegisterUserError::Unexpected(_) => create_internal_error(),
         }
     }
@@ -36,17 +89,94 @@ impl From<VerifyUserError> for Status {
         match value {
             VerifyUserError::UserNotFound => Status::not_found(value.to_string()),
             VerifyUserError::InvalidUserNameOrPassword(err) => Status::invalid_argument(err),
+            VerifyUserError::InvalidCredentials(violation) => {
+                invalid_fields(std::slice::from_ref(&violation))
+            }
+            VerifyUserError::InvalidScope(err) => invalid_fields(&[FieldViolation::from(&err)]),
+            VerifyUserError::Unavailable => create_unavailable_error(),
             VerifyUserError::Unexpected(_) => create_internal_error(),
         }
     }
 }
 
+impl From<EnrollTwoFactorError> for Status {
+    fn from(value: EnrollTwoFactorError) -> Self {
+        match value {
+            EnrollTwoFactorError::UserNotFound => Status::not_found(value.to_string()),
+            EnrollTwoFactorError::AlreadyEnabled => Status::failed_precondition(value.to_string()),
+            EnrollTwoFactorError::Unavailable => create_unavailable_error(),
+            EnrollTwoFactorError::Unexpected(_) => create_internal_error(),
+        }
+    }
+}
+
+impl From<ConfirmTwoFactorError> for Status {
+    fn from(value: ConfirmTwoFactorError) -> Self {
+        match value {
+            ConfirmTwoFactorError::UserNotFound => Status::not_found(value.to_string()),
+            ConfirmTwoFactorError::NotEnrolled | ConfirmTwoFactorError::AlreadyEnabled => {
+                Status::failed_precondition(value.to_string())
+            }
+            ConfirmTwoFactorError::InvalidCode => Status::invalid_argument(value.to_string()),
+            ConfirmTwoFactorError::Unavailable => create_unavailable_error(),
+            ConfirmTwoFactorError::Unexpected(_) => create_internal_error(),
+        }
+    }
+}
+
+impl From<VerifyTwoFactorError> for Status {
+    fn from(value: VerifyTwoFactorError) -> Self {
+        match value {
+            VerifyTwoFactorError::UserNotFound
+            | VerifyTwoFactorError::NotEnabled
+            | VerifyTwoFactorError::InvalidCode => Status::unauthenticated(value.to_string()),
+            VerifyTwoFactorError::Unavailable => create_unavailable_error(),
+            VerifyTwoFactorError::Unexpected(_) => create_internal_error(),
+        }
+    }
+}
+
+impl From<CreateApiTokenError> for Status {
+    fn from(value: CreateApiTokenError) -> Self {
+        match value {
+            CreateApiTokenError::InvalidName(err) => invalid_fields(&[FieldViolation::from(&err)]),
+            CreateApiTokenError::InvalidScope(err) => invalid_fields(&[FieldViolation::from(&err)]),
+            CreateApiTokenError::InvalidExpiration(_) => {
+                Status::invalid_argument(value.to_string())
+            }
+            CreateApiTokenError::ScopeNotGranted(_) => Status::permission_denied(value.to_string()),
+            CreateApiTokenError::Unavailable => create_unavailable_error(),
+            CreateApiTokenError::Unexpected(_) => create_internal_error(),
+        }
+    }
+}
+
+impl From<ListApiTokensError> for Status {
+    fn from(value: ListApiTokensError) -> Self {
+        match value {
+            ListApiTokensError::Unavailable => create_unavailable_error(),
+            ListApiTokensError::Unexpected(_) => create_internal_error(),
+        }
+    }
+}
+
+impl From<RevokeApiTokenError> for Status {
+    fn from(value: RevokeApiTokenError) -> Self {
+        match value {
+            RevokeApiTokenError::NotFound => Status::not_found(value.to_string()),
+            RevokeApiTokenError::Unavailable => create_unavailable_error(),
+            RevokeApiTokenError::Unexpected(_) => create_internal_error(),
+        }
+    }
+}
+
 impl From<UpdatePostError> for Status {
     fn from(value: UpdatePostError) -> Self {
         match value {
             UpdatePostError::NotFound => Status::not_found(value.to_string()),
             UpdatePostError::NotAllowed => Status::permission_denied(value.to_string()),
-            UpdatePostError::InvalidTitle(err) => Status::invalid_argument(err.to_string()),
+            UpdatePostError::InvalidTitle(err) => invalid_fields(&[FieldViolation::from(&err)]),
+            UpdatePostError::Unavailable => create_unavailable_error(),
             UpdatePostError::Unexpected(_) => create_internal_error(),
         }
     }
@@ -57,6 +187,7 @@ impl From<DeletePostError> for Status {
         match value {
             DeletePostError::NotFound => Status::not_found(value.to_string()),
             DeletePostError::NotAllowed => Status::permission_denied(value.to_string()),
+            DeletePostError::Unavailable => create_unavailable_error(),
             DeletePostError::Unexpected(_) => create_internal_error(),
         }
     }
@@ -66,11 +197,60 @@ impl From<GetPostError> for Status {
     fn from(value: GetPostError) -> Self {
         match value {
             GetPostError::NotFound => Status::not_found(value.to_string()),
+            GetPostError::Unavailable => create_unavailable_error(),
             GetPostError::Unexpected(_) => create_internal_error(),
         }
     }
 }
 
+/// Builds an `INVALID_ARGUMENT` status carrying `google.rpc.ErrorInfo` and `google.rpc.BadRequest`
+/// details, so clients do not have to parse the message.
+///
+/// The details are encoded by hand because `tonic_types::ErrorDetails` drops the violation
+/// `reason` when converting to protobuf.
+fn invalid_fields(violations: &[FieldViolation]) -> Status {
+    let error_info = pb::ErrorInfo {
+        reason: VALIDATION_FAILED.to_string(),
+        domain: ERROR_DOMAIN.to_string(),
+        metadata: HashMap::new(),
+    };
+    let bad_request = pb::BadRequest {
+        field_violations: violations
+            .iter()
+            .map(|violation| pb::bad_request::FieldViolation {
+                field: violation.field().to_string(),
+                description: violation.description().to_string(),
+                reason: violation.code().to_string(),
+                localized_message: None,
+            })
+            .collect(),
+    };
+    let message = describe_violations(violations);
+    let status = pb::Status {
+        code: Code::InvalidArgument as i32,
+        message: message.clone(),
+        details: vec![
+            Any {
+                type_url: ErrorInfo::TYPE_URL.to_string(),
+                value: error_info.encode_to_vec(),
+            },
+            Any {
+                type_url: BadRequest::TYPE_URL.to_string(),
+                value: bad_request.encode_to_vec(),
+            },
+        ],
+    };
+    Status::with_details(
+        Code::InvalidArgument,
+        message,
+        status.encode_to_vec().into(),
+    )
+}
+
 fn create_internal_error() -> Status {
     Status::internal("internal error")
 }
+
+fn create_unavailable_error() -> Status {
+    Status::unavailable("service temporarily unavailable")
+}
@@ -1 +1,2 @@
 pub(crate) mod blog_service;
+pub(crate) mod health_service;
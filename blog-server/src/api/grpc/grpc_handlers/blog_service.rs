@@ -1,48 +1,84 @@
 use crate::api::grpc::blog::blog_service_server::BlogService;
 use crate::api::grpc::blog::{
-    CreatePostCommand, DeletePostCommand, GetPostCommand, GetPostListCommand, GetPostListResult,
-    Post, RegisterUserCommand, UpdatePostCommand, User, VerifyUserCommand, VerifyUserResult,
+    BatchGetPostsCommand, BatchPostsResult, BulkCreatePostsItem, ConfirmTwoFactorCommand,
+    CreateApiTokenCommand, CreateApiTokenResult, CreatePostCommand, DeletePostCommand,
+    EnrollTwoFactorResult, GetPostCommand, GetPostListCommand, GetPostListResult,
+    ListApiTokensResult, Post, PostEvent, RegisterUserCommand, RevokeApiTokenCommand,
+    UpdatePostCommand, User, VerifyTwoFactorCommand, VerifyUserCommand, VerifyUserResult,
+};
+use crate::api::grpc::middleware::auth::RequestPrincipal;
+use crate::application::api_tokens::{
+    create_api_token_handler, list_api_tokens_handler, revoke_api_token_handler,
+};
+use crate::application::auth::{
+    confirm_two_factor_handler, enroll_two_factor_handler, register_user_handler,
+    verify_two_factor_handler, verify_user_handler,
 };
-use crate::application::auth::{register_user_handler, verify_user_handler};
 use crate::application::blog::{
-    create_post_handler, delete_post_handler, get_post_handler, get_post_list_handler,
-    update_post_handler,
+    BulkCreatePostsCommand, BulkCreatePostsError, MAX_BATCH_SIZE, batch_get_posts_handler,
+    bulk_create_posts_handler, create_post_handler, delete_post_handler, get_post_handler,
+    get_post_list_handler, update_post_handler,
+};
+use crate::application::contracts::{
+    ApiTokenRepository, PostEventBus, PostRepository, UnitOfWork, UserRepository,
 };
-use crate::application::contracts::{PostRepository, UserRepository};
-use crate::domain::value_objects::Identification;
-use crate::infrastructure::JwtService;
+use crate::domain::value_objects::{Identification, Scope};
+use crate::infrastructure::{ChallengeAttempts, JwtService, Metrics};
+use secrecy::ExposeSecret;
+use std::pin::Pin;
 use std::sync::Arc;
-use tonic::{Request, Response, Status};
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::wrappers::errors::BroadcastStreamRecvError;
+use tokio_stream::{Stream, StreamExt};
+use tonic::{Request, Response, Status, Streaming};
 use uuid::Uuid;
 
 pub(crate) struct GrpcBlogService {
     user_repo: Arc<dyn UserRepository>,
     post_repo: Arc<dyn PostRepository>,
+    api_token_repo: Arc<dyn ApiTokenRepository>,
+    unit_of_work: Arc<dyn UnitOfWork>,
+    post_events: Arc<dyn PostEventBus>,
     jwt_service: Arc<JwtService>,
+    challenge_attempts: Arc<ChallengeAttempts>,
+    metrics: Arc<Metrics>,
 }
 
 impl GrpcBlogService {
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn new(
         user_repo: Arc<dyn UserRepository>,
         post_repo: Arc<dyn PostRepository>,
+        api_token_repo: Arc<dyn ApiTokenRepository>,
+        unit_of_work: Arc<dyn UnitOfWork>,
+        post_events: Arc<dyn PostEventBus>,
         jwt_service: Arc<JwtService>,
+        challenge_attempts: Arc<ChallengeAttempts>,
+        metrics: Arc<Metrics>,
     ) -> Self {
         Self {
             user_repo,
             post_repo,
+            api_token_repo,
+            unit_of_work,
+            post_events,
             jwt_service,
+            challenge_attempts,
+            metrics,
         }
     }
 }
 
 #[tonic::async_trait]
 impl BlogService for GrpcBlogService {
+    type WatchPostsStream = Pin<Box<dyn Stream<Item = Result<PostEvent, Status>> + Send>>;
+
     #[tracing::instrument(name = "GRPC register a new user", skip(self))]
     async fn register_user(
         &self,
         request: Request<RegisterUserCommand>,
     ) -> Result<Response<User>, Status> {
-        let user = register_user_handler(request.into(), &self.user_repo).await?;
+        let user = register_user_handler(request.into(), &self.unit_of_work).await?;
         Ok(Response::new(user.into()))
     }
 
@@ -51,37 +87,152 @@ impl BlogService for GrpcBlogService {
         &self,
         request: Request<VerifyUserCommand>,
     ) -> Result<Response<VerifyUserResult>, Status> {
-        let user = verify_user_handler(request.into(), &self.user_repo).await?;
+        let (user, scopes) = verify_user_handler(request.into(), &self.user_repo)
+            .await
+            .inspect_err(|err| {
+                if err.is_rejected_login() {
+                    self.metrics.login_failed();
+                }
+            })?;
+        if user.is_totp_enabled() {
+            let challenge_token = self
+                .jwt_service
+                .generate_challenge_token(&user, &scopes)
+                .map_err(|_| Status::internal("internal error"))?;
+            return Ok(Response::new(VerifyUserResult {
+                challenge_token,
+                ..Default::default()
+            }));
+        }
+
         let token = self
             .jwt_service
-            .generate_jwt(&user)
+            .generate_jwt(&user, &scopes)
             .map_err(|_| Status::internal("internal error"))?;
         Ok(Response::new(VerifyUserResult {
             token,
             user: Some(user.into()),
+            challenge_token: String::new(),
         }))
     }
 
+    #[tracing::instrument(name = "GRPC verify two-factor code", skip_all)]
+    async fn verify_two_factor(
+        &self,
+        request: Request<VerifyTwoFactorCommand>,
+    ) -> Result<Response<VerifyUserResult>, Status> {
+        let claims = self
+            .jwt_service
+            .decode_challenge_token(request.get_ref().challenge_token.as_str())
+            .map_err(|_| Status::unauthenticated("invalid challenge token"))?;
+        if !self.challenge_attempts.try_attempt(claims.id()) {
+            return Err(Status::unauthenticated("too many attempts, log in again"));
+        }
+        let user = verify_two_factor_handler(
+            Identification::from(claims.sub()),
+            request.into(),
+            &self.unit_of_work,
+        )
+        .await?;
+        let token = self
+            .jwt_service
+            .generate_jwt(&user, &claims.scopes())
+            .map_err(|_| Status::internal("internal error"))?;
+        Ok(Response::new(VerifyUserResult {
+            token,
+            user: Some(user.into()),
+            challenge_token: String::new(),
+        }))
+    }
+
+    #[tracing::instrument(name = "GRPC enroll two-factor authentication", skip(self))]
+    async fn enroll_two_factor(
+        &self,
+        request: Request<()>,
+    ) -> Result<Response<EnrollTwoFactorResult>, Status> {
+        let user_id = request.ensure_scope(Scope::AccountManage)?.user_id();
+        let enrollment = enroll_two_factor_handler(user_id, &self.unit_of_work).await?;
+        Ok(Response::new(enrollment.into()))
+    }
+
+    #[tracing::instrument(name = "GRPC confirm two-factor authentication", skip_all)]
+    async fn confirm_two_factor(
+        &self,
+        request: Request<ConfirmTwoFactorCommand>,
+    ) -> Result<Response<()>, Status> {
+        let user_id = request.ensure_scope(Scope::AccountManage)?.user_id();
+        confirm_two_factor_handler(user_id, request.into(), &self.unit_of_work).await?;
+        Ok(Response::new(()))
+    }
+
     #[tracing::instrument(name = "GRPC create post", skip(self))]
     async fn create_post(
         &self,
         request: Request<CreatePostCommand>,
     ) -> Result<Response<Post>, Status> {
-        let author_id = validate_credentials(&request, &self.jwt_service)?;
-        let post = create_post_handler(author_id, request.into(), &self.post_repo).await?;
+        let author_id = request.ensure_scope(Scope::PostsWrite)?.user_id();
+        let post = create_post_handler(
+            author_id,
+            request.into(),
+            &self.post_repo,
+            &self.post_events,
+        )
+        .await?;
+        self.metrics.posts_created(1);
         Ok(Response::new(post.into()))
     }
 
+    #[tracing::instrument(name = "GRPC bulk create posts", skip(self, request))]
+    async fn bulk_create_posts(
+        &self,
+        request: Request<Streaming<BulkCreatePostsItem>>,
+    ) -> Result<Response<BatchPostsResult>, Status> {
+        let author_id = request.ensure_scope(Scope::PostsWrite)?.user_id();
+        let mut items = request.into_inner();
+        let mut posts = Vec::new();
+        let mut transactional = None;
+        while let Some(item) = items.message().await? {
+            if posts.len() == MAX_BATCH_SIZE {
+                return Err(BulkCreatePostsError::InvalidBatchSize.into());
+            }
+            transactional.get_or_insert(item.transactional);
+            posts.push(item.into());
+        }
+
+        let results = bulk_create_posts_handler(
+            author_id,
+            BulkCreatePostsCommand::new(posts, transactional.unwrap_or_default()),
+            &self.post_repo,
+            &self.post_events,
+        )
+        .await?;
+        self.metrics
+            .posts_created(results.iter().filter(|result| result.is_ok()).count());
+        Ok(Response::new(BatchPostsResult {
+            results: results
+                .into_iter()
+                .map(|result| result.map_err(Status::from).into())
+                .collect(),
+        }))
+    }
+
     #[tracing::instrument(name = "GRPC update post", skip(self))]
     async fn update_post(
         &self,
         request: Request<UpdatePostCommand>,
     ) -> Result<Response<Post>, Status> {
-        let author_id = validate_credentials(&request, &self.jwt_service)?;
+        let author_id = request.ensure_scope(Scope::PostsWrite)?.user_id();
         let post_id: Identification = Uuid::try_parse(request.get_ref().id.as_str())
             .map_err(|_| Status::invalid_argument("invalid post id"))?
             .into();
-        let post = update_post_handler(post_id, author_id, request.into(), &self.post_repo).await?;
+        let post = update_post_handler(
+            post_id,
+            author_id,
+            request.into(),
+            &self.unit_of_work,
+            &self.post_events,
+        )
+        .await?;
         Ok(Response::new(post.into()))
     }
 
@@ -90,11 +241,11 @@ impl BlogService for GrpcBlogService {
         &self,
         request: Request<DeletePostCommand>,
     ) -> Result<Response<()>, Status> {
-        let author_id = validate_credentials(&request, &self.jwt_service)?;
+        let author_id = request.ensure_scope(Scope::PostsDelete)?.user_id();
         let post_id: Identification = Uuid::try_parse(request.get_ref().id.as_str())
             .map_err(|_| Status::invalid_argument("invalid post id"))?
             .into();
-        delete_post_handler(post_id, author_id, &self.post_repo).await?;
+        delete_post_handler(post_id, author_id, &self.unit_of_work, &self.post_events).await?;
         Ok(Response::new(()))
     }
 
@@ -107,6 +258,30 @@ impl BlogService for GrpcBlogService {
         Ok(Response::new(post.into()))
     }
 
+    #[tracing::instrument(name = "GRPC batch get posts", skip(self, request))]
+    async fn batch_get_posts(
+        &self,
+        request: Request<BatchGetPostsCommand>,
+    ) -> Result<Response<BatchPostsResult>, Status> {
+        let post_ids = request
+            .into_inner()
+            .ids
+            .iter()
+            .map(|id| {
+                Uuid::try_parse(id)
+                    .map(Identification::from)
+                    .map_err(|_| Status::invalid_argument(format!("invalid post id: {}", id)))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let results = batch_get_posts_handler(post_ids, &self.post_repo).await?;
+        Ok(Response::new(BatchPostsResult {
+            results: results
+                .into_iter()
+                .map(|result| result.map_err(Status::from).into())
+                .collect(),
+        }))
+    }
+
     #[tracing::instrument(name = "GRPC get post list", skip(self))]
     async fn get_post_list(
         &self,
@@ -127,22 +302,62 @@ impl BlogService for GrpcBlogService {
             limit: command.limit,
         }))
     }
-}
 
-fn validate_credentials<T>(
-    request: &Request<T>,
-    jwt_service: &JwtService,
-) -> Result<Identification, Status> {
-    match request.metadata().get("authorization") {
-        Some(token) => {
-            let token = token
-                .to_str()
-                .map_err(|_| Status::unauthenticated("invalid token format"))?;
-            let claims = jwt_service
-                .decode_jwt(token)
-                .map_err(|_| Status::unauthenticated("invalid token"))?;
-            Ok(Identification::from(claims.sub()))
-        }
-        None => Err(Status::unauthenticated("missing authorization header")),
+    #[tracing::instrument(name = "GRPC watch posts", skip(self))]
+    async fn watch_posts(
+        &self,
+        _request: Request<()>,
+    ) -> Result<Response<Self::WatchPostsStream>, Status> {
+        let stream = BroadcastStream::new(self.post_events.subscribe()).map(|event| match event {
+            Ok(event) => Ok(event.into()),
+            Err(BroadcastStreamRecvError::Lagged(skipped)) => Err(Status::data_loss(format!(
+                "watcher lagged behind, {skipped} events were skipped"
+            ))),
+        });
+        Ok(Response::new(Box::pin(stream)))
+    }
+
+    #[tracing::instrument(name = "GRPC create api token", skip(self))]
+    async fn create_api_token(
+        &self,
+        request: Request<CreateApiTokenCommand>,
+    ) -> Result<Response<CreateApiTokenResult>, Status> {
+        let principal = request.ensure_scope(Scope::AccountManage)?.clone();
+        let (api_token, token) = create_api_token_handler(
+            principal.user_id(),
+            principal.scopes(),
+            request.into(),
+            &self.api_token_repo,
+        )
+        .await?;
+        Ok(Response::new(CreateApiTokenResult {
+            token: token.expose_secret().to_string(),
+            api_token: Some(api_token.into()),
+        }))
+    }
+
+    #[tracing::instrument(name = "GRPC list api tokens", skip(self))]
+    async fn list_api_tokens(
+        &self,
+        request: Request<()>,
+    ) -> Result<Response<ListApiTokensResult>, Status> {
+        let user_id = request.ensure_scope(Scope::AccountManage)?.user_id();
+        let api_tokens = list_api_tokens_handler(user_id, &self.api_token_repo).await?;
+        Ok(Response::new(ListApiTokensResult {
+            api_tokens: api_tokens.into_iter().map(|token| token.into()).collect(),
+        }))
+    }
+
+    #[tracing::instrument(name = "GRPC revoke api token", skip(self))]
+    async fn revoke_api_token(
+        &self,
+        request: Request<RevokeApiTokenCommand>,
+    ) -> Result<Response<()>, Status> {
+        let user_id = request.ensure_scope(Scope::AccountManage)?.user_id();
+        let token_id: Identification = Uuid::try_parse(request.get_ref().id.as_str())
+            .map_err(|_| Status::invalid_argument("invalid token id"))?
+            .into();
+        revoke_api_token_handler(token_id, user_id, &self.unit_of_work).await?;
+        Ok(Response::new(()))
     }
 }
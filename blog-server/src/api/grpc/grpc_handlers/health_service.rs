@@ -0,0 +1,46 @@
+use crate::api::grpc::blog::blog_service_server::BlogServiceServer;
+use crate::api::grpc::grpc_handlers::blog_service::GrpcBlogService;
+use crate::application::contracts::HealthCheck;
+use std::sync::Arc;
+use std::time::Duration;
+use tonic::server::NamedService;
+use tonic_health::ServingStatus;
+use tonic_health::pb::health_server::{Health, HealthServer};
+use tonic_health::server::HealthReporter;
+
+/// How often the serving status is refreshed.
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Creates the standard `grpc.health.v1.Health` service.
+///
+/// Both the server as a whole (the empty service name) and `blog.BlogService` are reported as
+/// `NOT_SERVING` while the database is unreachable.
+pub(crate) fn health_service(health_check: Arc<dyn HealthCheck>) -> HealthServer<impl Health> {
+    let (reporter, service) = tonic_health::server::health_reporter();
+    tokio::spawn(report_health(reporter, health_check));
+    service
+}
+
+async fn report_health(reporter: HealthReporter, health_check: Arc<dyn HealthCheck>) {
+    let mut interval = tokio::time::interval(HEALTH_CHECK_INTERVAL);
+    let mut last_status = None;
+    loop {
+        interval.tick().await;
+        let status = match health_check.check().await {
+            Ok(()) => ServingStatus::Serving,
+            Err(err) => {
+                tracing::warn!("Health check failed: {}", err);
+                ServingStatus::NotServing
+            }
+        };
+        if last_status == Some(status) {
+            continue;
+        }
+        tracing::info!("gRPC serving status changed to {:?}", status);
+        reporter.set_service_status("", status).await;
+        reporter
+            .set_service_status(BlogServiceServer::<GrpcBlogService>::NAME, status)
+            .await;
+        last_status = Some(status);
+    }
+}
@@ -0,0 +1,6 @@
+pub(crate) mod auth;
+pub(crate) mod cors;
+pub(crate) mod http_forward;
+pub(crate) mod metrics;
+pub(crate) mod rate_limit;
+pub(crate) mod trace_context;
@@ -0,0 +1,186 @@
+use crate::api::{AuthenticationError, Principal, authenticate};
+use crate::application::contracts::{ApiTokenRepository, UnitOfWork};
+use crate::domain::value_objects::Scope;
+use crate::infrastructure::JwtService;
+use http::{Request, Response};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tonic::Status;
+use tower::{Layer, Service};
+
+/// Methods that can be called without credentials. Every other method requires a valid token.
+const PUBLIC_METHODS: &[&str] = &[
+    "/blog.BlogService/RegisterUser",
+    "/blog.BlogService/Login",
+    "/blog.BlogService/VerifyTwoFactor",
+    "/blog.BlogService/GetPost",
+    "/blog.BlogService/GetPostList",
+    "/blog.BlogService/BatchGetPosts",
+    "/blog.BlogService/WatchPosts",
+];
+/// Services whose methods are all public.
+const PUBLIC_SERVICES: &[&str] = &["/grpc.reflection.", "/grpc.health."];
+
+/// Authenticates the `authorization` metadata once per call of a non-public method and stores
+/// the resulting [`Principal`] in the request extensions, where handlers read it through
+/// [`RequestPrincipal`].
+#[derive(Clone)]
+pub(crate) struct AuthLayer {
+    jwt_service: Arc<JwtService>,
+    api_token_repo: Arc<dyn ApiTokenRepository>,
+    unit_of_work: Arc<dyn UnitOfWork>,
+}
+
+impl AuthLayer {
+    pub(crate) fn new(
+        jwt_service: Arc<JwtService>,
+        api_token_repo: Arc<dyn ApiTokenRepository>,
+        unit_of_work: Arc<dyn UnitOfWork>,
+    ) -> Self {
+        Self {
+            jwt_service,
+            api_token_repo,
+            unit_of_work,
+        }
+    }
+}
+
+impl<S> Layer<S> for AuthLayer {
+    type Service = AuthService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        AuthService {
+            inner,
+            jwt_service: Arc::clone(&self.jwt_service),
+            api_token_repo: Arc::clone(&self.api_token_repo),
+            unit_of_work: Arc::clone(&self.unit_of_work),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct AuthService<S> {
+    inner: S,
+    jwt_service: Arc<JwtService>,
+    api_token_repo: Arc<dyn ApiTokenRepository>,
+    unit_of_work: Arc<dyn UnitOfWork>,
+}
+
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for AuthService<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    ReqBody: Send + 'static,
+    ResBody: Default + Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request<ReqBody>) -> Self::Future {
+        // Public handlers never read the principal, so their credentials are not checked.
+        if !requires_authentication(req.uri().path()) {
+            return Box::pin(self.inner.call(req));
+        }
+        let token = req
+            .headers()
+            .get("authorization")
+            .map(|value| value.to_str().map(strip_bearer_prefix).map(str::to_string));
+        let jwt_service = Arc::clone(&self.jwt_service);
+        let api_token_repo = Arc::clone(&self.api_token_repo);
+        let unit_of_work = Arc::clone(&self.unit_of_work);
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        Box::pin(async move {
+            let principal = match token {
+                None => Err(Status::unauthenticated("missing authorization header")),
+                Some(Err(_)) => Err(Status::unauthenticated("invalid token format")),
+                Some(Ok(token)) => {
+                    authenticate(&token, &jwt_service, &api_token_repo, &unit_of_work)
+                        .await
+                        .map_err(|err| match err {
+                            AuthenticationError::InvalidToken => {
+                                Status::unauthenticated("invalid token")
+                            }
+                            AuthenticationError::Unavailable => {
+                                Status::unavailable("service temporarily unavailable")
+                            }
+                            AuthenticationError::Unexpected(err) => {
+                                tracing::error!("Error authenticating request: {}", err);
+                                Status::internal("internal error")
+                            }
+                        })
+                }
+            };
+
+            match principal {
+                Ok(principal) => {
+                    req.extensions_mut().insert(principal);
+                    inner.call(req).await
+                }
+                Err(status) => Ok(status.into_http()),
+            }
+        })
+    }
+}
+
+/// Gives gRPC handlers access to the principal stored by [`AuthLayer`].
+pub(crate) trait RequestPrincipal {
+    fn principal(&self) -> Result<&Principal, Status>;
+
+    /// Returns the principal if its token carries `scope`, `PERMISSION_DENIED` otherwise.
+    fn ensure_scope(&self, scope: Scope) -> Result<&Principal, Status> {
+        let principal = self.principal()?;
+        if principal.has_scope(scope) {
+            Ok(principal)
+        } else {
+            Err(Status::permission_denied(format!(
+                "missing scope {}",
+                scope.as_ref()
+            )))
+        }
+    }
+}
+
+impl<T> RequestPrincipal for tonic::Request<T> {
+    fn principal(&self) -> Result<&Principal, Status> {
+        self.extensions()
+            .get::<Principal>()
+            .ok_or_else(|| Status::unauthenticated("missing authorization header"))
+    }
+}
+
+fn requires_authentication(path: &str) -> bool {
+    !PUBLIC_METHODS.contains(&path)
+        && !PUBLIC_SERVICES
+            .iter()
+            .any(|service| path.starts_with(service))
+}
+
+fn strip_bearer_prefix(value: &str) -> &str {
+    value.strip_prefix("Bearer ").unwrap_or(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn public_methods_do_not_require_authentication() {
+        assert!(!requires_authentication("/blog.BlogService/Login"));
+        assert!(!requires_authentication("/blog.BlogService/GetPostList"));
+        assert!(!requires_authentication(
+            "/grpc.reflection.v1.ServerReflection/ServerReflectionInfo"
+        ));
+        assert!(!requires_authentication("/grpc.health.v1.Health/Check"));
+        assert!(requires_authentication("/blog.BlogService/CreatePost"));
+        assert!(requires_authentication("/blog.BlogService/Unknown"));
+    }
+}
@@ -0,0 +1,37 @@
+use crate::api::grpc::middleware::rate_limit::{
+    RATE_LIMIT_LIMIT, RATE_LIMIT_REMAINING, RATE_LIMIT_RESET,
+};
+use crate::infrastructure::AllowedOrigins;
+use http::Method;
+use http::header::HeaderName;
+use std::sync::Arc;
+use std::time::Duration;
+use tower_http::cors::{AllowHeaders, AllowOrigin, CorsLayer};
+
+/// Response headers a gRPC-Web client has to read; browsers hide every other header.
+const EXPOSED_HEADERS: &[&str] = &[
+    "grpc-status",
+    "grpc-message",
+    "grpc-status-details-bin",
+    RATE_LIMIT_LIMIT,
+    RATE_LIMIT_REMAINING,
+    RATE_LIMIT_RESET,
+];
+
+/// Allows browsers on the white-listed origins to call the gRPC API over gRPC-Web, matching
+/// the CORS policy of the HTTP API.
+pub(crate) fn cors_layer(allowed_origins: Arc<AllowedOrigins>) -> CorsLayer {
+    CorsLayer::new()
+        .allow_origin(AllowOrigin::predicate(move |origin, _| {
+            allowed_origins.is_allowed(origin.as_bytes())
+        }))
+        .allow_methods([Method::POST, Method::OPTIONS])
+        .allow_headers(AllowHeaders::any())
+        .expose_headers(
+            EXPOSED_HEADERS
+                .iter()
+                .map(|header| HeaderName::from_static(header))
+                .collect::<Vec<_>>(),
+        )
+        .max_age(Duration::from_secs(3600))
+}
@@ -0,0 +1,215 @@
+use crate::api::grpc::middleware::rate_limit::client_ip;
+use crate::api::{FORWARD_SECRET_HEADER, ForwardSecret};
+use http::header::{CONTENT_TYPE, HOST, HeaderMap, HeaderName, HeaderValue};
+use http::uri::{Authority, PathAndQuery, Scheme};
+use http::{Request, Response, StatusCode, Uri, Version};
+use hyper_util::client::legacy::Client;
+use hyper_util::client::legacy::connect::HttpConnector;
+use hyper_util::rt::TokioExecutor;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tonic::body::Body;
+use tonic::codegen::{Bytes, StdError};
+use tower::{Layer, Service};
+
+const X_FORWARDED_FOR: &str = "x-forwarded-for";
+const GRPC_CONTENT_TYPE: &str = "application/grpc";
+/// Headers that only apply to a single connection and must not be forwarded.
+const HOP_BY_HOP_HEADERS: [&str; 7] = [
+    "connection",
+    "keep-alive",
+    "proxy-connection",
+    "te",
+    "trailer",
+    "transfer-encoding",
+    "upgrade",
+];
+
+/// Forwards requests that are not gRPC (or gRPC-Web) to the HTTP server, so that both APIs can
+/// be served on one port. Without an HTTP server address every request is handled by gRPC.
+///
+/// Forwarded requests carry the client address in `X-Forwarded-For` and the [`ForwardSecret`],
+/// which tells the HTTP server that the address can be trusted.
+#[derive(Clone)]
+pub(crate) struct HttpForwardLayer {
+    upstream: Option<Upstream>,
+}
+
+impl HttpForwardLayer {
+    pub(crate) fn new(
+        http_address: Option<SocketAddr>,
+        secret: ForwardSecret,
+    ) -> anyhow::Result<Self> {
+        let upstream = match http_address {
+            Some(address) => Some(Upstream {
+                client: Client::builder(TokioExecutor::new()).build_http(),
+                authority: Authority::try_from(address.to_string())?,
+                secret: HeaderValue::from_str(secret.as_str())?,
+            }),
+            None => None,
+        };
+        Ok(Self { upstream })
+    }
+}
+
+impl<S> Layer<S> for HttpForwardLayer {
+    type Service = HttpForwardService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        HttpForwardService {
+            inner,
+            upstream: self.upstream.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct HttpForwardService<S> {
+    inner: S,
+    upstream: Option<Upstream>,
+}
+
+impl<S, ResBody> Service<Request<Body>> for HttpForwardService<S>
+where
+    S: Service<Request<Body>, Response = Response<ResBody>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    ResBody: tonic::codegen::Body<Data = Bytes> + Send + 'static,
+    ResBody::Error: Into<StdError>,
+{
+    type Response = Response<Body>;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        if let Some(upstream) = &self.upstream
+            && !is_grpc(req.headers())
+        {
+            let upstream = upstream.clone();
+            return Box::pin(async move { Ok(upstream.forward(req).await) });
+        }
+
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        Box::pin(async move { Ok(inner.call(req).await?.map(Body::new)) })
+    }
+}
+
+#[derive(Clone)]
+struct Upstream {
+    client: Client<HttpConnector, Body>,
+    authority: Authority,
+    secret: HeaderValue,
+}
+
+impl Upstream {
+    async fn forward(&self, req: Request<Body>) -> Response<Body> {
+        let (mut parts, body) = req.into_parts();
+        // HTTP/2 requests carry the host in the URI instead of a header.
+        if !parts.headers.contains_key(HOST)
+            && let Some(host) = parts
+                .uri
+                .authority()
+                .and_then(|authority| HeaderValue::from_str(authority.as_str()).ok())
+        {
+            parts.headers.insert(HOST, host);
+        }
+        let client_ip = client_ip(&parts.extensions, &parts.headers);
+        remove_hop_by_hop_headers(&mut parts.headers);
+        // Replaces whatever the client sent, so that the HTTP server can trust it.
+        parts.headers.insert(
+            HeaderName::from_static(FORWARD_SECRET_HEADER),
+            self.secret.clone(),
+        );
+        match client_ip.and_then(|ip| HeaderValue::from_str(&ip.to_string()).ok()) {
+            Some(ip) => parts
+                .headers
+                .insert(HeaderName::from_static(X_FORWARDED_FOR), ip),
+            None => parts.headers.remove(X_FORWARDED_FOR),
+        };
+        let mut uri = Uri::builder()
+            .scheme(Scheme::HTTP)
+            .authority(self.authority.clone());
+        if let Some(path_and_query) = parts.uri.path_and_query() {
+            uri = uri.path_and_query(path_and_query.clone());
+        } else {
+            uri = uri.path_and_query(PathAndQuery::from_static("/"));
+        }
+        parts.uri = match uri.build() {
+            Ok(uri) => uri,
+            Err(err) => {
+                tracing::error!("Failed to build the forwarded request URI: {}", err);
+                return bad_gateway();
+            }
+        };
+        parts.version = Version::HTTP_11;
+
+        match self.client.request(Request::from_parts(parts, body)).await {
+            Ok(response) => {
+                let (mut parts, body) = response.into_parts();
+                remove_hop_by_hop_headers(&mut parts.headers);
+                Response::from_parts(parts, Body::new(body))
+            }
+            Err(err) => {
+                tracing::error!("Failed to forward request to the HTTP server: {}", err);
+                bad_gateway()
+            }
+        }
+    }
+}
+
+fn is_grpc(headers: &HeaderMap) -> bool {
+    headers
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|content_type| content_type.starts_with(GRPC_CONTENT_TYPE))
+}
+
+fn remove_hop_by_hop_headers(headers: &mut HeaderMap) {
+    for header in HOP_BY_HOP_HEADERS {
+        headers.remove(header);
+    }
+}
+
+fn bad_gateway() -> Response<Body> {
+    let mut response = Response::new(Body::empty());
+    *response.status_mut() = StatusCode::BAD_GATEWAY;
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(content_type: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_str(content_type).unwrap());
+        headers
+    }
+
+    #[test]
+    fn is_grpc_matches_grpc_and_grpc_web_content_types() {
+        assert!(is_grpc(&headers("application/grpc")));
+        assert!(is_grpc(&headers("application/grpc+proto")));
+        assert!(is_grpc(&headers("application/grpc-web-text")));
+        assert!(!is_grpc(&headers("application/json")));
+        assert!(!is_grpc(&HeaderMap::new()));
+    }
+
+    #[test]
+    fn remove_hop_by_hop_headers_keeps_end_to_end_headers() {
+        let mut headers = headers("application/json");
+        headers.insert("connection", HeaderValue::from_static("keep-alive"));
+        headers.insert("transfer-encoding", HeaderValue::from_static("chunked"));
+
+        remove_hop_by_hop_headers(&mut headers);
+
+        assert_eq!(headers.len(), 1);
+        assert!(headers.contains_key(CONTENT_TYPE));
+    }
+}
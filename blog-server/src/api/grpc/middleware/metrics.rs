@@ -0,0 +1,196 @@
+use crate::api::grpc::proto;
+use crate::infrastructure::{Metrics, UNKNOWN_GRPC_METHOD};
+use http::{Request, Response};
+use prost::Message;
+use prost_types::FileDescriptorSet;
+use std::collections::HashSet;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Instant;
+use tonic::Code;
+use tower::{Layer, Service};
+
+const GRPC_STATUS: &str = "grpc-status";
+/// Descriptors of every service the server hosts.
+const SERVED_DESCRIPTOR_SETS: &[&[u8]] = &[
+    proto::FILE_DESCRIPTOR_SET,
+    tonic_health::pb::FILE_DESCRIPTOR_SET,
+    tonic_reflection::pb::v1::FILE_DESCRIPTOR_SET,
+];
+
+/// Counts gRPC calls per method and status code.
+///
+/// Failed calls carry their status in the response headers; a call whose status only arrives
+/// in the trailers, i.e. one that got as far as sending a message, is counted as `OK`. Calls to
+/// paths that are not a served method are counted as [`UNKNOWN_GRPC_METHOD`] whatever their
+/// status, so clients cannot grow the method label without bound.
+#[derive(Clone)]
+pub(crate) struct MetricsLayer {
+    metrics: Arc<Metrics>,
+    methods: Arc<HashSet<String>>,
+}
+
+impl MetricsLayer {
+    pub(crate) fn new(metrics: Arc<Metrics>) -> Result<Self, prost::DecodeError> {
+        Ok(Self {
+            metrics,
+            methods: Arc::new(served_methods()?),
+        })
+    }
+}
+
+/// Paths of the methods in [`SERVED_DESCRIPTOR_SETS`], e.g. `/blog.BlogService/Login`.
+fn served_methods() -> Result<HashSet<String>, prost::DecodeError> {
+    let mut methods = HashSet::new();
+    for descriptor_set in SERVED_DESCRIPTOR_SETS {
+        for file in FileDescriptorSet::decode(*descriptor_set)?.file {
+            for service in &file.service {
+                for method in &service.method {
+                    methods.insert(format!(
+                        "/{}.{}/{}",
+                        file.package(),
+                        service.name(),
+                        method.name()
+                    ));
+                }
+            }
+        }
+    }
+    Ok(methods)
+}
+
+impl<S> Layer<S> for MetricsLayer {
+    type Service = MetricsService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        MetricsService {
+            inner,
+            metrics: Arc::clone(&self.metrics),
+            methods: Arc::clone(&self.methods),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct MetricsService<S> {
+    inner: S,
+    metrics: Arc<Metrics>,
+    methods: Arc<HashSet<String>>,
+}
+
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for MetricsService<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    ReqBody: Send + 'static,
+    ResBody: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
+        let method = match self.methods.get(req.uri().path()) {
+            Some(method) => method.clone(),
+            None => UNKNOWN_GRPC_METHOD.to_string(),
+        };
+        let metrics = Arc::clone(&self.metrics);
+        let started_at = Instant::now();
+
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        Box::pin(async move {
+            let result = inner.call(req).await;
+            let code = match &result {
+                Ok(response) => response
+                    .headers()
+                    .get(GRPC_STATUS)
+                    .map(|value| Code::from_bytes(value.as_bytes()))
+                    .unwrap_or(Code::Ok),
+                Err(_) => Code::Internal,
+            };
+            metrics.observe_grpc_request(&method, code, started_at.elapsed());
+            result
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::grpc::middleware::auth::AuthLayer;
+    use crate::infrastructure::{
+        DatabasePools, JwtService, PostgresApiTokenRepository, PostgresUnitOfWork, TotpSecretCipher,
+    };
+    use secrecy::SecretString;
+    use sqlx::postgres::PgPoolOptions;
+    use std::convert::Infallible;
+    use tower::ServiceExt;
+
+    fn metrics() -> Arc<Metrics> {
+        let pg_pool = PgPoolOptions::new()
+            .connect_lazy("postgres://localhost/blog")
+            .unwrap();
+        Arc::new(Metrics::new(Arc::new(pg_pool)).unwrap())
+    }
+
+    async fn call(metrics: &Arc<Metrics>, path: &str) {
+        let pg_pool = PgPoolOptions::new()
+            .connect_lazy("postgres://localhost/blog")
+            .unwrap();
+        let auth_layer = AuthLayer::new(
+            Arc::new(JwtService::new(SecretString::from(
+                "0123456789abcdef0123456789abcdef",
+            ))),
+            Arc::new(PostgresApiTokenRepository::new(Arc::new(pg_pool.clone()))),
+            Arc::new(PostgresUnitOfWork::new(
+                Arc::new(DatabasePools::new(Arc::new(pg_pool), Vec::new())),
+                Arc::new(TotpSecretCipher::new(&SecretString::from("key")).unwrap()),
+            )),
+        );
+        let service = tower::ServiceBuilder::new()
+            .layer(MetricsLayer::new(Arc::clone(metrics)).unwrap())
+            .layer(auth_layer)
+            .service(tower::service_fn(|_: Request<()>| async {
+                Ok::<_, Infallible>(tonic::Status::unimplemented("").into_http::<()>())
+            }));
+        let request = Request::builder().uri(path).body(()).unwrap();
+
+        service.oneshot(request).await.unwrap();
+    }
+
+    #[test]
+    fn served_methods_cover_every_hosted_service() {
+        let methods = served_methods().unwrap();
+
+        assert!(methods.contains("/blog.BlogService/Login"));
+        assert!(methods.contains("/grpc.health.v1.Health/Check"));
+        assert!(methods.contains("/grpc.reflection.v1.ServerReflection/ServerReflectionInfo"));
+        assert!(!methods.contains("/blog.BlogService/Random"));
+    }
+
+    #[tokio::test]
+    async fn unknown_paths_are_labelled_unknown_whatever_the_status() {
+        let metrics = metrics();
+
+        call(&metrics, "/blog.BlogService/Random").await;
+        call(&metrics, "/random.Service/Method").await;
+        call(&metrics, "/blog.BlogService/Login").await;
+
+        let rendered = metrics.render().unwrap();
+        assert!(
+            rendered
+                .contains(r#"blog_grpc_requests_total{code="Unauthenticated",method="unknown"} 2"#)
+        );
+        assert!(rendered.contains(
+            r#"blog_grpc_requests_total{code="Unimplemented",method="/blog.BlogService/Login"} 1"#
+        ));
+        assert!(!rendered.contains("Random"));
+    }
+}
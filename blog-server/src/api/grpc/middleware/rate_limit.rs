@@ -0,0 +1,294 @@
+use crate::api::{forwarded_client_ip, rate_limit_subject};
+use crate::infrastructure::{
+    JwtService, RateLimitDecision, RateLimitOperation, RateLimitSubject, RateLimiter,
+};
+use http::header::{AsHeaderName, FORWARDED, HeaderMap, HeaderName, HeaderValue};
+use http::{Extensions, Request, Response};
+use std::future::Future;
+use std::net::{IpAddr, SocketAddr};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tonic::Status;
+use tonic::transport::server::{TcpConnectInfo, TlsConnectInfo};
+use tower::{Layer, Service};
+
+pub(super) const RATE_LIMIT_LIMIT: &str = "x-ratelimit-limit";
+pub(super) const RATE_LIMIT_REMAINING: &str = "x-ratelimit-remaining";
+pub(super) const RATE_LIMIT_RESET: &str = "x-ratelimit-reset";
+const X_FORWARDED_FOR: &str = "x-forwarded-for";
+const READ_METHODS: &[&str] = &[
+    "/blog.BlogService/GetPost",
+    "/blog.BlogService/GetPostList",
+    "/blog.BlogService/BatchGetPosts",
+    "/blog.BlogService/WatchPosts",
+];
+/// Services that are never rate limited, so that orchestrator probes are not throttled.
+const UNLIMITED_SERVICES: &[&str] = &["/grpc.health."];
+
+/// Limits calls with a valid JWT per user and all others per client address, the same way as
+/// the HTTP API. It needs no storage, so it runs outside
+/// [`AuthLayer`](super::auth::AuthLayer) and calls that fail authentication are limited too.
+#[derive(Clone)]
+pub(crate) struct RateLimitLayer {
+    rate_limiter: Arc<RateLimiter>,
+    jwt_service: Arc<JwtService>,
+}
+
+impl RateLimitLayer {
+    pub(crate) fn new(rate_limiter: Arc<RateLimiter>, jwt_service: Arc<JwtService>) -> Self {
+        Self {
+            rate_limiter,
+            jwt_service,
+        }
+    }
+}
+
+impl<S> Layer<S> for RateLimitLayer {
+    type Service = RateLimitService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RateLimitService {
+            inner,
+            rate_limiter: Arc::clone(&self.rate_limiter),
+            jwt_service: Arc::clone(&self.jwt_service),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct RateLimitService<S> {
+    inner: S,
+    rate_limiter: Arc<RateLimiter>,
+    jwt_service: Arc<JwtService>,
+}
+
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for RateLimitService<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    ReqBody: Send + 'static,
+    ResBody: Default + Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
+        if !self.rate_limiter.is_enabled()
+            || UNLIMITED_SERVICES
+                .iter()
+                .any(|service| req.uri().path().starts_with(service))
+        {
+            return Box::pin(self.inner.call(req));
+        }
+
+        let subject = resolve_subject(&req, &self.jwt_service);
+        let operation = if READ_METHODS.contains(&req.uri().path()) {
+            RateLimitOperation::Read
+        } else {
+            RateLimitOperation::Write
+        };
+        let decision = self.rate_limiter.check(subject, operation);
+
+        if !decision.is_allowed() {
+            tracing::warn!("Rate limit exceeded for {:?}", subject);
+            let mut response = Status::resource_exhausted("too many requests").into_http();
+            add_rate_limit_metadata(response.headers_mut(), &decision);
+            return Box::pin(async move { Ok(response) });
+        }
+
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        Box::pin(async move {
+            let mut response = inner.call(req).await?;
+            add_rate_limit_metadata(response.headers_mut(), &decision);
+            Ok(response)
+        })
+    }
+}
+
+fn resolve_subject<B>(req: &Request<B>, jwt_service: &JwtService) -> RateLimitSubject {
+    let token = req
+        .headers()
+        .get("authorization")
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.strip_prefix("Bearer ").unwrap_or(value));
+    rate_limit_subject(
+        token,
+        client_ip(req.extensions(), req.headers()),
+        jwt_service,
+    )
+}
+
+/// Address of the client: the TCP peer, or on a Unix socket, which only local processes can
+/// reach, the address forwarded by the reverse proxy in front of it.
+pub(super) fn client_ip(extensions: &Extensions, headers: &HeaderMap) -> Option<IpAddr> {
+    if let Some(addr) = remote_addr(extensions) {
+        return Some(addr.ip());
+    }
+    #[cfg(unix)]
+    if extensions
+        .get::<tonic::transport::server::UdsConnectInfo>()
+        .is_some()
+    {
+        return forwarded_client_ip(
+            last_header(headers, FORWARDED),
+            last_header(headers, X_FORWARDED_FOR),
+        );
+    }
+    None
+}
+
+fn last_header(headers: &HeaderMap, name: impl AsHeaderName) -> Option<&str> {
+    headers
+        .get_all(name)
+        .iter()
+        .next_back()
+        .and_then(|value| value.to_str().ok())
+}
+
+/// Address of the TCP peer, with or without TLS.
+fn remote_addr(extensions: &Extensions) -> Option<SocketAddr> {
+    extensions
+        .get::<TcpConnectInfo>()
+        .or_else(|| {
+            extensions
+                .get::<TlsConnectInfo<TcpConnectInfo>>()
+                .map(|info| info.get_ref())
+        })
+        .and_then(|info| info.remote_addr())
+}
+
+fn add_rate_limit_metadata(headers: &mut HeaderMap, decision: &RateLimitDecision) {
+    headers.insert(
+        HeaderName::from_static(RATE_LIMIT_LIMIT),
+        HeaderValue::from(decision.limit()),
+    );
+    headers.insert(
+        HeaderName::from_static(RATE_LIMIT_REMAINING),
+        HeaderValue::from(decision.remaining()),
+    );
+    headers.insert(
+        HeaderName::from_static(RATE_LIMIT_RESET),
+        HeaderValue::from(decision.reset_after_secs()),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::grpc::middleware::auth::AuthLayer;
+    use crate::configuration::RateLimitConfiguration;
+    use crate::infrastructure::{
+        DatabasePools, PostgresApiTokenRepository, PostgresUnitOfWork, TotpSecretCipher,
+    };
+    use secrecy::SecretString;
+    use sqlx::postgres::PgPoolOptions;
+    use std::convert::Infallible;
+    use tonic::Code;
+    use tower::ServiceExt;
+
+    fn headers() -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            X_FORWARDED_FOR,
+            HeaderValue::from_static("10.0.0.1, 203.0.113.7"),
+        );
+        headers
+    }
+
+    #[test]
+    fn client_ip_ignores_forwarded_headers_from_tcp_peers() {
+        let mut extensions = Extensions::new();
+        extensions.insert(TcpConnectInfo {
+            local_addr: None,
+            remote_addr: Some("198.51.100.1:4711".parse().unwrap()),
+        });
+
+        assert_eq!(
+            client_ip(&extensions, &headers()),
+            Some("198.51.100.1".parse().unwrap())
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn client_ip_trusts_the_proxy_on_a_unix_socket() {
+        let mut extensions = Extensions::new();
+        extensions.insert(tonic::transport::server::UdsConnectInfo {
+            peer_addr: None,
+            peer_cred: None,
+        });
+
+        assert_eq!(
+            client_ip(&extensions, &headers()),
+            Some("203.0.113.7".parse().unwrap())
+        );
+        assert_eq!(client_ip(&extensions, &HeaderMap::new()), None);
+    }
+
+    #[tokio::test]
+    async fn calls_that_fail_authentication_are_limited() {
+        let config: RateLimitConfiguration = serde_json::from_value(serde_json::json!({
+            "enabled": true,
+            "anonymous": {
+                "read": { "capacity": 1, "refill_per_minute": 1 },
+                "write": { "capacity": 1, "refill_per_minute": 1 }
+            },
+            "authenticated": {
+                "read": { "capacity": 10, "refill_per_minute": 10 },
+                "write": { "capacity": 10, "refill_per_minute": 10 }
+            }
+        }))
+        .unwrap();
+        let jwt_service = Arc::new(JwtService::new(SecretString::from(
+            "0123456789abcdef0123456789abcdef",
+        )));
+        let pg_pool = PgPoolOptions::new()
+            .connect_lazy("postgres://localhost/blog")
+            .unwrap();
+        let auth_layer = AuthLayer::new(
+            Arc::clone(&jwt_service),
+            Arc::new(PostgresApiTokenRepository::new(Arc::new(pg_pool.clone()))),
+            Arc::new(PostgresUnitOfWork::new(
+                Arc::new(DatabasePools::new(Arc::new(pg_pool), Vec::new())),
+                Arc::new(TotpSecretCipher::new(&SecretString::from("key")).unwrap()),
+            )),
+        );
+        let service = tower::ServiceBuilder::new()
+            .layer(RateLimitLayer::new(
+                Arc::new(RateLimiter::new(&config)),
+                jwt_service,
+            ))
+            .layer(auth_layer)
+            .service(tower::service_fn(|_: Request<()>| async {
+                Ok::<_, Infallible>(Response::new(()))
+            }));
+        let request = || {
+            let mut request = Request::builder()
+                .uri("/blog.BlogService/CreatePost")
+                .header("authorization", "Bearer not-a-token")
+                .body(())
+                .unwrap();
+            request.extensions_mut().insert(TcpConnectInfo {
+                local_addr: None,
+                remote_addr: Some("198.51.100.1:4711".parse().unwrap()),
+            });
+            request
+        };
+
+        let first = service.clone().oneshot(request()).await.unwrap();
+        let second = service.oneshot(request()).await.unwrap();
+
+        let code = |response: &Response<()>| {
+            Status::from_header_map(response.headers()).map(|status| status.code())
+        };
+        assert_eq!(code(&first), Some(Code::Unauthenticated));
+        assert_eq!(code(&second), Some(Code::ResourceExhausted));
+    }
+}
@@ -0,0 +1,28 @@
+use http::Request;
+use opentelemetry::global;
+use opentelemetry::trace::TraceContextExt;
+use opentelemetry_http::HeaderExtractor;
+use tracing::Span;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+/// Creates the root span of a gRPC call, continuing the caller's trace when the request carries
+/// a W3C `traceparent` header. Like the HTTP request span, it records the `trace_id` so that
+/// logs can be matched to exported traces.
+pub(crate) fn grpc_request_span<B>(request: &Request<B>) -> Span {
+    let span = tracing::info_span!(
+        "gRPC request",
+        method = %request.method(),
+        uri = %request.uri(),
+        version = ?request.version(),
+        trace_id = tracing::field::Empty,
+    );
+    let parent = global::get_text_map_propagator(|propagator| {
+        propagator.extract(&HeaderExtractor(request.headers()))
+    });
+    // Fails only when spans are not exported, in which case there is nothing to connect.
+    if span.set_parent(parent).is_ok() {
+        let trace_id = span.context().span().span_context().trace_id();
+        span.record("trace_id", trace_id.to_string());
+    }
+    span
+}
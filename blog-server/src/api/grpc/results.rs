@@ -1,5 +1,12 @@
-use crate::api::grpc::blog::{Post, User};
+use crate::api::grpc::blog::batch_post_result::Result as BatchResult;
+use crate::api::grpc::blog::post_event::Kind;
+use crate::api::grpc::blog::{
+    ApiToken, BatchItemError, BatchPostResult, EnrollTwoFactorResult, FieldViolation, Post,
+    PostEvent, User,
+};
+use crate::application::auth::TwoFactorEnrollment;
 use crate::domain::value_objects::DateTime;
+use tonic_types::StatusExt;
 
 impl From<crate::domain::entities::User> for User {
     fn from(user: crate::domain::entities::User) -> Self {
@@ -11,6 +18,15 @@ impl From<crate::domain::entities::User> for User {
     }
 }
 
+impl From<TwoFactorEnrollment> for EnrollTwoFactorResult {
+    fn from(enrollment: TwoFactorEnrollment) -> Self {
+        EnrollTwoFactorResult {
+            otpauth_uri: enrollment.otpauth_uri().to_string(),
+            recovery_codes: enrollment.recovery_codes().to_vec(),
+        }
+    }
+}
+
 impl From<crate::domain::entities::Post> for Post {
     fn from(post: crate::domain::entities::Post) -> Self {
         Post {
@@ -24,6 +40,65 @@ impl From<crate::domain::entities::Post> for Post {
     }
 }
 
+impl From<Result<crate::domain::entities::Post, tonic::Status>> for BatchPostResult {
+    fn from(result: Result<crate::domain::entities::Post, tonic::Status>) -> Self {
+        let result = match result {
+            Ok(post) => BatchResult::Post(post.into()),
+            Err(status) => BatchResult::Error(BatchItemError {
+                code: status.code() as i32,
+                message: status.message().to_string(),
+                violations: status
+                    .get_details_bad_request()
+                    .map(|bad_request| bad_request.field_violations)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|violation| FieldViolation {
+                        field: violation.field,
+                        reason: violation.reason,
+                        description: violation.description,
+                    })
+                    .collect(),
+            }),
+        };
+        BatchPostResult {
+            result: Some(result),
+        }
+    }
+}
+
+impl From<crate::application::contracts::PostEvent> for PostEvent {
+    fn from(event: crate::application::contracts::PostEvent) -> Self {
+        use crate::application::contracts::PostEvent as Event;
+        let (kind, post) = match event {
+            Event::Created(post) => (Kind::Created, post),
+            Event::Updated(post) => (Kind::Updated, post),
+            Event::Deleted(post) => (Kind::Deleted, post),
+        };
+        PostEvent {
+            kind: kind.into(),
+            post: Some(post.into()),
+        }
+    }
+}
+
+impl From<crate::domain::entities::ApiToken> for ApiToken {
+    fn from(token: crate::domain::entities::ApiToken) -> Self {
+        ApiToken {
+            id: token.id().as_ref().to_string(),
+            name: token.name().as_ref().to_string(),
+            scopes: token
+                .scopes()
+                .iter()
+                .map(|scope| scope.as_ref().to_string())
+                .collect(),
+            created_at: Some(token.created_at().into()),
+            expires_at: token.expires_at().map(|x| x.into()),
+            last_used_at: token.last_used_at().map(|x| x.into()),
+            revoked_at: token.revoked_at().map(|x| x.into()),
+        }
+    }
+}
+
 impl From<&DateTime> for prost_types::Timestamp {
     fn from(value: &DateTime) -> Self {
         let value = value.as_ref();
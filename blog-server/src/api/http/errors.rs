@@ -1,64 +1,185 @@
-use crate::application::auth::{RegisterUserError, VerifyUserError};
+use crate::application::api_tokens::{
+    CreateApiTokenError, ListApiTokensError, RevokeApiTokenError,
+};
+use crate::application::auth::{
+    ConfirmTwoFactorError, EnrollTwoFactorError, RegisterUserError, VerifyTwoFactorError,
+    VerifyUserError,
+};
 use crate::application::blog::{
-    CreatePostError, DeletePostError, GetPostError, GetPostListError, UpdatePostError,
+    BatchGetPostsError, BulkCreatePostItemError, BulkCreatePostsError, CreatePostError,
+    DeletePostError, GetPostError, GetPostListError, UpdatePostError,
 };
+use crate::domain::value_objects::{FieldViolation, describe_violations};
 use actix_web::ResponseError;
-use actix_web::http::header::ContentType;
+use actix_web::http::StatusCode;
+use actix_web::http::header::{CONTENT_TYPE, HeaderValue};
 use serde::Serialize;
 use tracing_log::log::error;
 
+const PROBLEM_CONTENT_TYPE: &str = "application/problem+json";
+/// Code of every error caused by invalid fields; the fields are listed in `violations`.
+const VALIDATION_FAILED: &str = "VALIDATION_FAILED";
+
 #[derive(Debug, thiserror::Error)]
 pub(crate) enum ApiError {
     #[error("{0}")]
     UnprocessableEntity(String),
+    #[error("{}", describe_violations(.0))]
+    InvalidField(Vec<FieldViolation>),
     #[error("{0}")]
     Conflict(String),
     #[error("internal server error")]
     InternalServerError(String),
+    #[error("service temporarily unavailable")]
+    ServiceUnavailable,
     #[error("unauthorized")]
     Unauthorized(String),
+    #[error("unauthorized")]
+    InvalidCredentials(FieldViolation),
     #[error("forbidden: {0}")]
     Forbidden(String),
     #[error("not found: {0}")]
     NotFound(String),
 }
 
+impl ApiError {
+    /// Machine-readable error code, derived from the status unless the error is more specific.
+    pub(crate) fn code(&self) -> &'static str {
+        match self {
+            ApiError::InvalidField(_) => VALIDATION_FAILED,
+            _ => status_code_name(self.status_code()),
+        }
+    }
+
+    pub(crate) fn violations(&self) -> &[FieldViolation] {
+        match self {
+            ApiError::InvalidField(violations) => violations,
+            ApiError::InvalidCredentials(violation) => std::slice::from_ref(violation),
+            _ => &[],
+        }
+    }
+}
+
 impl ResponseError for ApiError {
-    fn status_code(&self) -> actix_web::http::StatusCode {
+    fn status_code(&self) -> StatusCode {
         match self {
-            ApiError::UnprocessableEntity(_) => actix_web::http::StatusCode::UNPROCESSABLE_ENTITY,
-            ApiError::InternalServerError(_) => actix_web::http::StatusCode::INTERNAL_SERVER_ERROR,
-            ApiError::Conflict(_) => actix_web::http::StatusCode::CONFLICT,
-            ApiError::Unauthorized(_) => actix_web::http::StatusCode::UNAUTHORIZED,
-            ApiError::Forbidden(_) => actix_web::http::StatusCode::FORBIDDEN,
-            ApiError::NotFound(_) => actix_web::http::StatusCode::NOT_FOUND,
+            ApiError::UnprocessableEntity(_) | ApiError::InvalidField(_) => {
+                StatusCode::UNPROCESSABLE_ENTITY
+            }
+            ApiError::InternalServerError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            ApiError::ServiceUnavailable => StatusCode::SERVICE_UNAVAILABLE,
+            ApiError::Conflict(_) => StatusCode::CONFLICT,
+            ApiError::Unauthorized(_) | ApiError::InvalidCredentials(_) => StatusCode::UNAUTHORIZED,
+            ApiError::Forbidden(_) => StatusCode::FORBIDDEN,
+            ApiError::NotFound(_) => StatusCode::NOT_FOUND,
         }
     }
 
     fn error_response(&self) -> actix_web::HttpResponse {
         error!("Error: {:?}", self);
-        create_error_response(self.status_code(), self.to_string())
+        create_problem_response(ProblemDetails::new(
+            self.status_code(),
+            self.code(),
+            self.to_string(),
+            self.violations(),
+        ))
     }
 }
 
 pub(crate) fn create_error_response(
-    status_code: actix_web::http::StatusCode,
+    status_code: StatusCode,
     error: String,
 ) -> actix_web::HttpResponse {
-    actix_web::HttpResponse::build(status_code)
-        .insert_header(ContentType::json())
-        .json(ErrorResponse { error })
+    create_problem_response(ProblemDetails::new(
+        status_code,
+        status_code_name(status_code),
+        error,
+        &[],
+    ))
 }
 
+fn create_problem_response(problem: ProblemDetails) -> actix_web::HttpResponse {
+    let mut response = actix_web::HttpResponse::build(problem.status_code()).json(&problem);
+    response
+        .headers_mut()
+        .insert(CONTENT_TYPE, HeaderValue::from_static(PROBLEM_CONTENT_TYPE));
+    response
+}
+
+/// Turns a status into an error code, e.g. `404 Not Found` into `NOT_FOUND`.
+fn status_code_name(status_code: StatusCode) -> &'static str {
+    match status_code {
+        StatusCode::UNPROCESSABLE_ENTITY => "UNPROCESSABLE_ENTITY",
+        StatusCode::CONFLICT => "CONFLICT",
+        StatusCode::UNAUTHORIZED => "UNAUTHORIZED",
+        StatusCode::FORBIDDEN => "FORBIDDEN",
+        StatusCode::NOT_FOUND => "NOT_FOUND",
+        StatusCode::TOO_MANY_REQUESTS => "TOO_MANY_REQUESTS",
+        StatusCode::SERVICE_UNAVAILABLE => "SERVICE_UNAVAILABLE",
+        _ => "INTERNAL",
+    }
+}
+
+/// RFC 7807 problem details. `error` repeats `detail` for clients of the former
+/// `{ "error": "..." }` body.
 #[derive(Serialize)]
-struct ErrorResponse {
+struct ProblemDetails {
+    #[serde(rename = "type")]
+    problem_type: &'static str,
+    title: &'static str,
+    status: u16,
+    detail: String,
+    code: &'static str,
     error: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    violations: Vec<ViolationResponse>,
+}
+
+impl ProblemDetails {
+    fn new(
+        status_code: StatusCode,
+        code: &'static str,
+        detail: String,
+        violations: &[FieldViolation],
+    ) -> Self {
+        Self {
+            problem_type: "about:blank",
+            title: status_code.canonical_reason().unwrap_or_default(),
+            status: status_code.as_u16(),
+            error: detail.clone(),
+            detail,
+            code,
+            violations: violations.iter().map(ViolationResponse::from).collect(),
+        }
+    }
+
+    fn status_code(&self) -> StatusCode {
+        StatusCode::from_u16(self.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
+    }
+}
+
+#[derive(Serialize)]
+pub(crate) struct ViolationResponse {
+    field: &'static str,
+    code: &'static str,
+    detail: String,
+}
+
+impl From<&FieldViolation> for ViolationResponse {
+    fn from(violation: &FieldViolation) -> Self {
+        Self {
+            field: violation.field(),
+            code: violation.code(),
+            detail: violation.description().to_string(),
+        }
+    }
 }
 
 impl From<RegisterUserError> for ApiError {
     fn from(err: RegisterUserError) -> Self {
         match err {
-            RegisterUserError::InvalidUser(error) => ApiError::UnprocessableEntity(error),
+            RegisterUserError::InvalidUser(violations) => ApiError::InvalidField(violations),
+            RegisterUserError::Unavailable => ApiError::ServiceUnavailable,
             RegisterUserError::Unexpected(error) => ApiError::InternalServerError(error),
             RegisterUserError::UsernameOrEmailExist => ApiError::Conflict(err.to_string()),
         }
@@ -69,29 +190,148 @@ impl From<VerifyUserError> for ApiError {
     fn from(err: VerifyUserError) -> Self {
         match err {
             VerifyUserError::InvalidUserNameOrPassword(error) => ApiError::Unauthorized(error),
+            VerifyUserError::InvalidCredentials(violation) => {
+                ApiError::InvalidCredentials(violation)
+            }
             VerifyUserError::UserNotFound => ApiError::Unauthorized(err.to_string()),
+            VerifyUserError::InvalidScope(error) => {
+                ApiError::InvalidField(vec![FieldViolation::from(&error)])
+            }
+            VerifyUserError::Unavailable => ApiError::ServiceUnavailable,
             VerifyUserError::Unexpected(error) => ApiError::InternalServerError(error),
         }
     }
 }
 
+impl From<EnrollTwoFactorError> for ApiError {
+    fn from(err: EnrollTwoFactorError) -> Self {
+        match err {
+            EnrollTwoFactorError::UserNotFound => ApiError::NotFound(err.to_string()),
+            EnrollTwoFactorError::AlreadyEnabled => ApiError::Conflict(err.to_string()),
+            EnrollTwoFactorError::Unavailable => ApiError::ServiceUnavailable,
+            EnrollTwoFactorError::Unexpected(error) => ApiError::InternalServerError(error),
+        }
+    }
+}
+
+impl From<ConfirmTwoFactorError> for ApiError {
+    fn from(err: ConfirmTwoFactorError) -> Self {
+        match err {
+            ConfirmTwoFactorError::UserNotFound => ApiError::NotFound(err.to_string()),
+            ConfirmTwoFactorError::NotEnrolled | ConfirmTwoFactorError::AlreadyEnabled => {
+                ApiError::Conflict(err.to_string())
+            }
+            ConfirmTwoFactorError::InvalidCode => ApiError::UnprocessableEntity(err.to_string()),
+            ConfirmTwoFactorError::Unavailable => ApiError::ServiceUnavailable,
+            ConfirmTwoFactorError::Unexpected(error) => ApiError::InternalServerError(error),
+        }
+    }
+}
+
+impl From<VerifyTwoFactorError> for ApiError {
+    fn from(err: VerifyTwoFactorError) -> Self {
+        match err {
+            VerifyTwoFactorError::UserNotFound
+            | VerifyTwoFactorError::NotEnabled
+            | VerifyTwoFactorError::InvalidCode => ApiError::Unauthorized(err.to_string()),
+            VerifyTwoFactorError::Unavailable => ApiError::ServiceUnavailable,
+            VerifyTwoFactorError::Unexpected(error) => ApiError::InternalServerError(error),
+        }
+    }
+}
+
+impl From<CreateApiTokenError> for ApiError {
+    fn from(err: CreateApiTokenError) -> Self {
+        match err {
+            CreateApiTokenError::InvalidName(error) => {
+                ApiError::InvalidField(vec![FieldViolation::from(&error)])
+            }
+            CreateApiTokenError::InvalidScope(error) => {
+                ApiError::InvalidField(vec![FieldViolation::from(&error)])
+            }
+            CreateApiTokenError::InvalidExpiration(_) => {
+                ApiError::UnprocessableEntity(err.to_string())
+            }
+            CreateApiTokenError::ScopeNotGranted(_) => ApiError::Forbidden(err.to_string()),
+            CreateApiTokenError::Unavailable => ApiError::ServiceUnavailable,
+            CreateApiTokenError::Unexpected(error) => ApiError::InternalServerError(error),
+        }
+    }
+}
+
+impl From<ListApiTokensError> for ApiError {
+    fn from(err: ListApiTokensError) -> Self {
+        match err {
+            ListApiTokensError::Unavailable => ApiError::ServiceUnavailable,
+            ListApiTokensError::Unexpected(error) => ApiError::InternalServerError(error),
+        }
+    }
+}
+
+impl From<RevokeApiTokenError> for ApiError {
+    fn from(err: RevokeApiTokenError) -> Self {
+        match err {
+            RevokeApiTokenError::NotFound => ApiError::NotFound(err.to_string()),
+            RevokeApiTokenError::Unavailable => ApiError::ServiceUnavailable,
+            RevokeApiTokenError::Unexpected(error) => ApiError::InternalServerError(error),
+        }
+    }
+}
+
 impl From<CreatePostError> for ApiError {
     fn from(err: CreatePostError) -> Self {
         match err {
             CreatePostError::InvalidTitle(error) => {
-                ApiError::UnprocessableEntity(error.to_string())
+                ApiError::InvalidField(vec![FieldViolation::from(&error)])
             }
+            CreatePostError::Unavailable => ApiError::ServiceUnavailable,
             CreatePostError::Unexpected(error) => ApiError::InternalServerError(error),
         }
     }
 }
 
+impl From<BulkCreatePostsError> for ApiError {
+    fn from(err: BulkCreatePostsError) -> Self {
+        match err {
+            BulkCreatePostsError::InvalidBatchSize => {
+                ApiError::UnprocessableEntity(err.to_string())
+            }
+            BulkCreatePostsError::Unavailable => ApiError::ServiceUnavailable,
+            BulkCreatePostsError::Unexpected(error) => ApiError::InternalServerError(error),
+        }
+    }
+}
+
+impl From<BulkCreatePostItemError> for ApiError {
+    fn from(err: BulkCreatePostItemError) -> Self {
+        match err {
+            BulkCreatePostItemError::InvalidTitle(error) => {
+                ApiError::InvalidField(vec![FieldViolation::from(&error)])
+            }
+            BulkCreatePostItemError::Aborted => ApiError::Conflict(err.to_string()),
+            BulkCreatePostItemError::Unavailable => ApiError::ServiceUnavailable,
+            BulkCreatePostItemError::Unexpected(error) => ApiError::InternalServerError(error),
+        }
+    }
+}
+
+impl From<BatchGetPostsError> for ApiError {
+    fn from(err: BatchGetPostsError) -> Self {
+        match err {
+            BatchGetPostsError::InvalidBatchSize => ApiError::UnprocessableEntity(err.to_string()),
+            BatchGetPostsError::Unavailable => ApiError::ServiceUnavailable,
+            BatchGetPostsError::Unexpected(error) => ApiError::InternalServerError(error),
+        }
+    }
+}
+
 impl From<UpdatePostError> for ApiError {
     fn from(err: UpdatePostError) -> Self {
         match err {
+            UpdatePostError::Unavailable => ApiError::ServiceUnavailable,
             UpdatePostError::Unexpected(error) => ApiError::InternalServerError(error),
             UpdatePostError::InvalidTitle(error) => {
-                ApiError::UnprocessableEntity(error.to_string())
+                ApiError::InvalidField(vec![FieldViolation::from(&error)])
             }
             UpdatePostError::NotFound => ApiError::NotFound(err.to_string()),
             UpdatePostError::NotAllowed => ApiError::Forbidden(err.to_string()),
@@ -102,6 +342,7 @@ impl From<UpdatePostError> for ApiError {
 impl From<DeletePostError> for ApiError {
     fn from(err: DeletePostError) -> Self {
         match err {
+            DeletePostError::Unavailable => ApiError::ServiceUnavailable,
             DeletePostError::Unexpected(error) => ApiError::InternalServerError(error),
             DeletePostError::NotFound => ApiError::NotFound(err.to_string()),
             DeletePostError::NotAllowed => ApiError::Forbidden(err.to_string()),
@@ -112,6 +353,7 @@ impl From<DeletePostError> for ApiError {
 impl From<GetPostError> for ApiError {
     fn from(value: GetPostError) -> Self {
         match value {
+            GetPostError::Unavailable => ApiError::ServiceUnavailable,
             GetPostError::Unexpected(error) => ApiError::InternalServerError(error),
             GetPostError::NotFound => ApiError::NotFound(value.to_string()),
         }
@@ -121,6 +363,7 @@ impl From<GetPostError> for ApiError {
 impl From<GetPostListError> for ApiError {
     fn from(value: GetPostListError) -> Self {
         match value {
+            GetPostListError::Unavailable => ApiError::ServiceUnavailable,
             GetPostListError::Unexpected(error) => ApiError::InternalServerError(error.to_string()),
         }
     }
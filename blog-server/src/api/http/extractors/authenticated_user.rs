@@ -1,18 +1,36 @@
-use crate::domain::value_objects::Identification;
+use crate::api::Principal;
+use crate::api::http::errors::ApiError;
+use crate::domain::value_objects::{Identification, Scope};
 use actix_web::dev::Payload;
 use actix_web::error::ErrorUnauthorized;
 use actix_web::{Error, FromRequest, HttpMessage, HttpRequest};
 use std::future::{Ready, ready};
-use uuid::Uuid;
 
 #[derive(Debug)]
 pub(crate) struct AuthenticatedUser {
-    id: Identification,
+    principal: Principal,
+}
+
+impl AuthenticatedUser {
+    pub(crate) fn scopes(&self) -> &[Scope] {
+        self.principal.scopes()
+    }
+
+    pub(crate) fn ensure_scope(&self, scope: Scope) -> Result<(), ApiError> {
+        if self.principal.has_scope(scope) {
+            Ok(())
+        } else {
+            Err(ApiError::Forbidden(format!(
+                "missing scope {}",
+                scope.as_ref()
+            )))
+        }
+    }
 }
 
 impl From<AuthenticatedUser> for Identification {
     fn from(value: AuthenticatedUser) -> Self {
-        value.id
+        value.principal.user_id()
     }
 }
 
@@ -20,9 +38,9 @@ impl FromRequest for AuthenticatedUser {
     type Error = Error;
     type Future = Ready<Result<Self, Self::Error>>;
     fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
-        if let Some(id) = req.extensions().get::<Uuid>() {
+        if let Some(principal) = req.extensions().get::<Principal>() {
             return ready(Ok(AuthenticatedUser {
-                id: Identification::from(*id),
+                principal: principal.clone(),
             }));
         }
         ready(Err(ErrorUnauthorized("no claims")))
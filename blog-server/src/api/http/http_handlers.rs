@@ -1,2 +1,6 @@
 pub(crate) mod auth;
+pub(crate) mod events;
+pub(crate) mod health;
+pub(crate) mod metrics;
 pub(crate) mod posts;
+pub(crate) mod tokens;
@@ -1,5 +1,9 @@
 mod login;
+mod oidc;
 mod register;
+mod two_factor;
 
 pub(crate) use login::*;
+pub(crate) use oidc::*;
 pub(crate) use register::*;
+pub(crate) use two_factor::*;
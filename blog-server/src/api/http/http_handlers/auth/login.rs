@@ -1,21 +1,47 @@
 use crate::api::http::errors::ApiError;
 use crate::application::auth::{VerifyUserCommand, verify_user_handler};
 use crate::application::contracts::UserRepository;
-use crate::infrastructure::JwtService;
+use crate::domain::entities::User;
+use crate::domain::value_objects::Scope;
+use crate::infrastructure::{JwtService, Metrics};
 use actix_web::{HttpResponse, post, web};
 use std::sync::Arc;
 use uuid::Uuid;
 
 #[post("/login")]
-#[tracing::instrument(name = "Login user", skip(users_repo, jwt_service))]
+#[tracing::instrument(name = "Login user", skip(users_repo, jwt_service, metrics))]
 pub(crate) async fn login(
     request: web::Json<VerifyUserCommand>,
     users_repo: web::Data<Arc<dyn UserRepository>>,
     jwt_service: web::Data<Arc<JwtService>>,
+    metrics: web::Data<Arc<Metrics>>,
+) -> Result<HttpResponse, ApiError> {
+    let (user, scopes) = verify_user_handler(request.0, users_repo.get_ref())
+        .await
+        .inspect_err(|err| {
+            if err.is_rejected_login() {
+                metrics.login_failed();
+            }
+        })?;
+    if user.is_totp_enabled() {
+        let challenge_token = jwt_service
+            .generate_challenge_token(&user, &scopes)
+            .map_err(|e| ApiError::InternalServerError(e.to_string()))?;
+        return Ok(HttpResponse::Ok().json(ChallengeResponse {
+            challenge_token: &challenge_token,
+        }));
+    }
+
+    create_token_response(&user, &scopes, jwt_service.get_ref())
+}
+
+pub(super) fn create_token_response(
+    user: &User,
+    scopes: &[Scope],
+    jwt_service: &JwtService,
 ) -> Result<HttpResponse, ApiError> {
-    let user = verify_user_handler(request.0, users_repo.get_ref()).await?;
     let token = jwt_service
-        .generate_jwt(&user)
+        .generate_jwt(user, scopes)
         .map_err(|e| ApiError::InternalServerError(e.to_string()))?;
 
     let response = Response {
@@ -36,6 +62,11 @@ struct Response<'a> {
     user: UserResponse<'a>,
 }
 
+#[derive(serde::Serialize)]
+struct ChallengeResponse<'a> {
+    challenge_token: &'a str,
+}
+
 #[derive(serde::Serialize)]
 struct UserResponse<'a> {
     id: &'a Uuid,
@@ -0,0 +1,189 @@
+use crate::api::http::errors::ApiError;
+use crate::api::http::extractors::AuthenticatedUser;
+use crate::application::auth::{
+    OidcSignIn, SignInWithOidcCommand, SignInWithOidcError, sign_in_with_oidc_handler,
+};
+use crate::application::contracts::{IdentityProvider, IdentityProviderError, UnitOfWork};
+use crate::configuration::OidcConfiguration;
+use crate::domain::entities::User;
+use crate::domain::value_objects::Scope;
+use crate::infrastructure::JwtService;
+use actix_web::http::header::LOCATION;
+use actix_web::{HttpResponse, get, post, web};
+use std::sync::Arc;
+
+#[get("/oidc/login")]
+#[tracing::instrument(name = "Start OIDC sign in", skip(identity_provider))]
+pub(crate) async fn oidc_login(
+    identity_provider: Option<web::Data<Arc<dyn IdentityProvider>>>,
+) -> Result<HttpResponse, ApiError> {
+    let identity_provider = identity_provider
+        .ok_or_else(|| ApiError::NotFound("single sign-on is not configured".to_string()))?;
+    let url = identity_provider
+        .authorization_url(None)
+        .await
+        .map_err(|e| ApiError::InternalServerError(e.to_string()))?;
+    Ok(HttpResponse::Found()
+        .insert_header((LOCATION, url))
+        .finish())
+}
+
+/// Starts linking an external identity to the signed-in user. The client sends the browser
+/// to the returned URL, and the callback finishes with `#linked` in the URL fragment.
+#[post("/link")]
+#[tracing::instrument(name = "Start OIDC identity linking", skip(identity_provider))]
+pub(crate) async fn oidc_link(
+    user: AuthenticatedUser,
+    identity_provider: Option<web::Data<Arc<dyn IdentityProvider>>>,
+) -> Result<HttpResponse, ApiError> {
+    user.ensure_scope(Scope::AccountManage)?;
+    let identity_provider = identity_provider
+        .ok_or_else(|| ApiError::NotFound("single sign-on is not configured".to_string()))?;
+    let url = identity_provider
+        .authorization_url(Some(user.into()))
+        .await
+        .map_err(|e| ApiError::InternalServerError(e.to_string()))?;
+    Ok(HttpResponse::Ok().json(LinkResponse {
+        authorization_url: &url,
+    }))
+}
+
+/// Completes the sign-in and sends the browser back to the frontend with `#token=<jwt>`,
+/// `#challenge=<token>` when the user has two-factor authentication enabled, `#linked` or
+/// `#error=<code>` in the URL fragment.
+#[get("/oidc/callback")]
+#[tracing::instrument(
+    name = "Complete OIDC sign in",
+    skip(query, identity_provider, oidc_config, unit_of_work, jwt_service)
+)]
+pub(crate) async fn oidc_callback(
+    query: web::Query<OidcCallbackQuery>,
+    identity_provider: Option<web::Data<Arc<dyn IdentityProvider>>>,
+    oidc_config: Option<web::Data<OidcConfiguration>>,
+    unit_of_work: web::Data<Arc<dyn UnitOfWork>>,
+    jwt_service: web::Data<Arc<JwtService>>,
+) -> Result<HttpResponse, ApiError> {
+    let (Some(identity_provider), Some(oidc_config)) = (identity_provider, oidc_config) else {
+        return Err(ApiError::NotFound(
+            "single sign-on is not configured".to_string(),
+        ));
+    };
+    let query = query.into_inner();
+    let fragment = match (query.code, query.state, query.error) {
+        (_, _, Some(error)) => {
+            tracing::warn!("Identity provider returned an error: {}", error);
+            format!("error={}", sanitize_error_code(&error))
+        }
+        (Some(code), Some(state), None) => {
+            let cmd = SignInWithOidcCommand::new(code, state);
+            match sign_in_with_oidc_handler(
+                cmd,
+                identity_provider.get_ref(),
+                unit_of_work.get_ref(),
+            )
+            .await
+            {
+                Ok(OidcSignIn::SignedIn(user)) => signed_in_fragment(&user, &jwt_service),
+                Ok(OidcSignIn::Linked) => "linked".to_string(),
+                Err(err) => {
+                    tracing::warn!("OIDC sign in failed: {}", err);
+                    format!("error={}", error_code(&err))
+                }
+            }
+        }
+        _ => "error=invalid_request".to_string(),
+    };
+
+    Ok(HttpResponse::Found()
+        .insert_header((
+            LOCATION,
+            format!("{}#{}", oidc_config.get_post_login_redirect_url(), fragment),
+        ))
+        .finish())
+}
+
+fn signed_in_fragment(user: &User, jwt_service: &JwtService) -> String {
+    let scopes = Scope::all();
+    let token = if user.is_totp_enabled() {
+        jwt_service
+            .generate_challenge_token(user, &scopes)
+            .map(|token| format!("challenge={}", token))
+    } else {
+        jwt_service
+            .generate_jwt(user, &scopes)
+            .map(|token| format!("token={}", token))
+    };
+    token.unwrap_or_else(|err| {
+        tracing::error!("Failed to generate token: {}", err);
+        "error=server_error".to_string()
+    })
+}
+
+fn error_code(err: &SignInWithOidcError) -> &'static str {
+    match err {
+        SignInWithOidcError::Provider(IdentityProviderError::InvalidState) => "invalid_state",
+        SignInWithOidcError::Provider(IdentityProviderError::Rejected(_))
+        | SignInWithOidcError::Provider(IdentityProviderError::InvalidIdToken(_)) => {
+            "invalid_response"
+        }
+        SignInWithOidcError::MissingEmail | SignInWithOidcError::InvalidEmail(_) => {
+            "email_required"
+        }
+        SignInWithOidcError::EmailNotVerified => "email_not_verified",
+        SignInWithOidcError::AccountExists => "account_exists",
+        SignInWithOidcError::IdentityInUse => "identity_in_use",
+        SignInWithOidcError::Conflict => "conflict",
+        SignInWithOidcError::Unavailable => "temporarily_unavailable",
+        SignInWithOidcError::Provider(IdentityProviderError::Unexpected(_))
+        | SignInWithOidcError::Unexpected(_) => "server_error",
+    }
+}
+
+/// Keeps provider error codes such as `access_denied` safe to put in a URL fragment.
+fn sanitize_error_code(error: &str) -> String {
+    error
+        .chars()
+        .filter(|ch| ch.is_ascii_alphanumeric() || *ch == '_')
+        .collect()
+}
+
+#[derive(serde::Serialize)]
+struct LinkResponse<'a> {
+    authorization_url: &'a str,
+}
+
+#[derive(serde::Deserialize, Debug)]
+pub(crate) struct OidcCallbackQuery {
+    code: Option<String>,
+    state: Option<String>,
+    error: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::value_objects::{Email, PasswordHash, TotpSecret, UserName};
+    use secrecy::SecretString;
+
+    fn user() -> User {
+        User::new(
+            UserName::try_from("jane_doe".to_string()).unwrap(),
+            Email::try_from("jane@example.com".to_string()).unwrap(),
+            PasswordHash::from(SecretString::from("hash")),
+        )
+    }
+
+    #[test]
+    fn signed_in_fragment_challenges_two_factor_users() {
+        let jwt_service = JwtService::new(SecretString::from("0123456789abcdef0123456789abcdef"));
+        let mut user = user();
+        assert!(signed_in_fragment(&user, &jwt_service).starts_with("token="));
+
+        user.enroll_totp(TotpSecret::from(SecretString::from("JBSWY3DPEHPK3PXP")));
+        user.enable_totp();
+        let fragment = signed_in_fragment(&user, &jwt_service);
+
+        let challenge_token = fragment.strip_prefix("challenge=").unwrap();
+        assert!(jwt_service.decode_challenge_token(challenge_token).is_ok());
+    }
+}
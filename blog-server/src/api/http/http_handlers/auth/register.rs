@@ -1,15 +1,15 @@
 use crate::api::http::errors::ApiError;
 use crate::application::auth::{RegisterUserCommand, register_user_handler};
-use crate::application::contracts::UserRepository;
+use crate::application::contracts::UnitOfWork;
 use actix_web::{HttpResponse, post, web};
 use std::sync::Arc;
 
 #[post("/register")]
-#[tracing::instrument(name = "Register a new user", skip(users_repo))]
+#[tracing::instrument(name = "Register a new user", skip(unit_of_work))]
 pub(crate) async fn register_user(
     request: web::Json<RegisterUserCommand>,
-    users_repo: web::Data<Arc<dyn UserRepository>>,
+    unit_of_work: web::Data<Arc<dyn UnitOfWork>>,
 ) -> Result<HttpResponse, ApiError> {
-    let _ = register_user_handler(request.0, users_repo.get_ref()).await?;
+    let _ = register_user_handler(request.0, unit_of_work.get_ref()).await?;
     Ok(HttpResponse::Created().finish())
 }
@@ -0,0 +1,72 @@
+use super::login::create_token_response;
+use crate::api::http::errors::ApiError;
+use crate::api::http::extractors::AuthenticatedUser;
+use crate::application::auth::{
+    ConfirmTwoFactorCommand, VerifyTwoFactorCommand, confirm_two_factor_handler,
+    enroll_two_factor_handler, verify_two_factor_handler,
+};
+use crate::application::contracts::UnitOfWork;
+use crate::domain::value_objects::{Identification, Scope};
+use crate::infrastructure::{ChallengeAttempts, JwtService};
+use actix_web::{HttpResponse, post, web};
+use std::sync::Arc;
+
+#[post("/enroll")]
+#[tracing::instrument(name = "Enroll two-factor authentication", skip(unit_of_work))]
+pub(crate) async fn enroll_two_factor(
+    user: AuthenticatedUser,
+    unit_of_work: web::Data<Arc<dyn UnitOfWork>>,
+) -> Result<HttpResponse, ApiError> {
+    user.ensure_scope(Scope::AccountManage)?;
+    let enrollment = enroll_two_factor_handler(user.into(), unit_of_work.get_ref()).await?;
+    Ok(HttpResponse::Ok().json(EnrollResponse {
+        otpauth_uri: enrollment.otpauth_uri(),
+        recovery_codes: enrollment.recovery_codes(),
+    }))
+}
+
+#[post("/confirm")]
+#[tracing::instrument(
+    name = "Confirm two-factor authentication",
+    skip(request, unit_of_work)
+)]
+pub(crate) async fn confirm_two_factor(
+    user: AuthenticatedUser,
+    request: web::Json<ConfirmTwoFactorCommand>,
+    unit_of_work: web::Data<Arc<dyn UnitOfWork>>,
+) -> Result<HttpResponse, ApiError> {
+    user.ensure_scope(Scope::AccountManage)?;
+    confirm_two_factor_handler(user.into(), request.0, unit_of_work.get_ref()).await?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[post("/2fa/verify")]
+#[tracing::instrument(name = "Verify two-factor code", skip_all)]
+pub(crate) async fn verify_two_factor(
+    request: web::Json<VerifyTwoFactorCommand>,
+    unit_of_work: web::Data<Arc<dyn UnitOfWork>>,
+    jwt_service: web::Data<Arc<JwtService>>,
+    challenge_attempts: web::Data<Arc<ChallengeAttempts>>,
+) -> Result<HttpResponse, ApiError> {
+    let claims = jwt_service
+        .decode_challenge_token(request.challenge_token())
+        .map_err(|e| ApiError::Unauthorized(e.to_string()))?;
+    if !challenge_attempts.try_attempt(claims.id()) {
+        return Err(ApiError::Unauthorized(
+            "too many attempts, log in again".to_string(),
+        ));
+    }
+    let user = verify_two_factor_handler(
+        Identification::from(claims.sub()),
+        request.0,
+        unit_of_work.get_ref(),
+    )
+    .await?;
+    create_token_response(&user, &claims.scopes(), jwt_service.get_ref())
+}
+
+#[derive(serde::Serialize)]
+struct EnrollResponse<'a> {
+    otpauth_uri: &'a str,
+    recovery_codes: &'a [String],
+}
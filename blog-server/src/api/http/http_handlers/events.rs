@@ -0,0 +1,3 @@
+mod watch_posts;
+
+pub(crate) use watch_posts::*;
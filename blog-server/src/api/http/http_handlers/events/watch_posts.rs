@@ -0,0 +1,111 @@
+use crate::api::http::http_handlers::posts::PostResponse;
+use crate::application::contracts::{PostEvent, PostEventBus};
+use actix_web::web::Bytes;
+use actix_web::{HttpResponse, get, web};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio::time::{Instant, Interval};
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::wrappers::errors::BroadcastStreamRecvError;
+use tokio_stream::{Stream, StreamExt};
+
+/// Proxies and load balancers drop connections that stay idle for too long, so a comment is
+/// sent whenever no event was sent for this long.
+const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(15);
+/// SSE comment line, which subscribers ignore.
+const KEEPALIVE: &str = ": keepalive\n\n";
+
+/// Streams post lifecycle events as Server-Sent Events.
+///
+/// Each event is named after the change (`created`, `updated` or `deleted`) and carries the
+/// post as JSON. A `lagged` event tells the subscriber that events were skipped and its
+/// view should be reloaded. A `: keepalive` comment is sent while no events arrive.
+#[get("/events")]
+#[tracing::instrument(name = "Watch posts", skip(post_events))]
+pub(crate) async fn watch_posts(post_events: web::Data<Arc<dyn PostEventBus>>) -> HttpResponse {
+    let keepalive =
+        tokio::time::interval_at(Instant::now() + KEEPALIVE_INTERVAL, KEEPALIVE_INTERVAL);
+
+    HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header(("Cache-Control", "no-cache"))
+        .streaming(event_stream(post_events.subscribe(), keepalive))
+}
+
+/// Ends when the event bus is closed, so that watchers disconnect on shutdown.
+fn event_stream(
+    receiver: broadcast::Receiver<PostEvent>,
+    keepalive: Interval,
+) -> impl Stream<Item = Result<Bytes, actix_web::Error>> {
+    BroadcastStream::new(receiver)
+        .timeout_repeating(keepalive)
+        .map(|event| {
+            let message = match event {
+                Ok(Ok(event)) => {
+                    format_event(&event).map_err(actix_web::error::ErrorInternalServerError)?
+                }
+                Ok(Err(BroadcastStreamRecvError::Lagged(skipped))) => {
+                    tracing::warn!("SSE subscriber lagged behind by {} events", skipped);
+                    format!("event: lagged\ndata: {{\"skipped\":{}}}\n\n", skipped)
+                }
+                Err(_) => KEEPALIVE.to_string(),
+            };
+            Ok(Bytes::from(message))
+        })
+}
+
+fn format_event(event: &PostEvent) -> Result<String, serde_json::Error> {
+    let (name, post) = match event {
+        PostEvent::Created(post) => ("created", post),
+        PostEvent::Updated(post) => ("updated", post),
+        PostEvent::Deleted(post) => ("deleted", post),
+    };
+    let data = serde_json::to_string(&PostResponse::from(post))?;
+    Ok(format!("event: {}\ndata: {}\n\n", name, data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::entities::Post;
+    use crate::domain::value_objects::{Content, Identification, Title};
+
+    #[test]
+    fn format_event_as_server_sent_event() {
+        let post = Post::new(
+            Title::try_from("Hello".to_string()).unwrap(),
+            Content::from("world".to_string()),
+            Identification::new(),
+        );
+
+        let message = format_event(&PostEvent::Updated(post.clone())).unwrap();
+
+        let data = message
+            .strip_prefix("event: updated\ndata: ")
+            .and_then(|rest| rest.strip_suffix("\n\n"))
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_str(data).unwrap();
+        assert_eq!(json["id"], post.id().as_ref().to_string());
+        assert_eq!(json["title"], "Hello");
+    }
+
+    #[tokio::test]
+    async fn event_stream_sends_keepalive_until_the_bus_closes() {
+        let (sender, receiver) = broadcast::channel(1);
+        let stream = event_stream(
+            receiver,
+            tokio::time::interval_at(
+                Instant::now() + Duration::from_millis(10),
+                Duration::from_millis(10),
+            ),
+        );
+        tokio::pin!(stream);
+
+        let message = stream.next().await.unwrap().unwrap();
+        assert_eq!(message, Bytes::from_static(KEEPALIVE.as_bytes()));
+
+        drop(sender);
+        assert!(stream.next().await.is_none());
+    }
+}
@@ -0,0 +1,7 @@
+mod liveness;
+mod readiness;
+mod response;
+
+pub(crate) use liveness::*;
+pub(crate) use readiness::*;
+pub(crate) use response::*;
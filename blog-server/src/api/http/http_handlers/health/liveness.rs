@@ -0,0 +1,8 @@
+use crate::api::http::http_handlers::health::HealthResponse;
+use actix_web::{HttpResponse, get};
+
+/// Reports that the process is up; never touches the database.
+#[get("/live")]
+pub(crate) async fn liveness() -> HttpResponse {
+    HttpResponse::Ok().json(HealthResponse::ok())
+}
@@ -0,0 +1,23 @@
+use crate::api::http::errors::create_error_response;
+use crate::api::http::http_handlers::health::HealthResponse;
+use crate::application::contracts::HealthCheck;
+use actix_web::http::StatusCode;
+use actix_web::{HttpResponse, get, web};
+use std::sync::Arc;
+
+/// Reports whether the server can handle requests, answering `503 Service Unavailable`
+/// while the database is unreachable.
+#[get("/ready")]
+#[tracing::instrument(name = "Check readiness", skip(health_check))]
+pub(crate) async fn readiness(health_check: web::Data<Arc<dyn HealthCheck>>) -> HttpResponse {
+    match health_check.check().await {
+        Ok(()) => HttpResponse::Ok().json(HealthResponse::ok()),
+        Err(err) => {
+            tracing::warn!("Readiness check failed: {}", err);
+            create_error_response(
+                StatusCode::SERVICE_UNAVAILABLE,
+                "database is unavailable".to_string(),
+            )
+        }
+    }
+}
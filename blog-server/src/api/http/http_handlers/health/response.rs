@@ -0,0 +1,10 @@
+#[derive(serde::Serialize)]
+pub(crate) struct HealthResponse {
+    status: &'static str,
+}
+
+impl HealthResponse {
+    pub(crate) fn ok() -> Self {
+        Self { status: "ok" }
+    }
+}
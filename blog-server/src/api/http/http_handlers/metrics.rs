@@ -0,0 +1,3 @@
+mod export_metrics;
+
+pub(crate) use export_metrics::*;
@@ -0,0 +1,20 @@
+use crate::api::http::errors::ApiError;
+use crate::infrastructure::Metrics;
+use actix_web::{HttpResponse, get, web};
+use std::sync::Arc;
+
+/// Content type of the Prometheus text exposition format.
+const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Exposes the server metrics for Prometheus to scrape.
+#[get("/metrics")]
+pub(crate) async fn export_metrics(
+    metrics: web::Data<Arc<Metrics>>,
+) -> Result<HttpResponse, ApiError> {
+    let body = metrics
+        .render()
+        .map_err(|e| ApiError::InternalServerError(e.to_string()))?;
+    Ok(HttpResponse::Ok()
+        .content_type(PROMETHEUS_CONTENT_TYPE)
+        .body(body))
+}
@@ -1,3 +1,6 @@
+mod batch_get_posts;
+mod bulk_create_posts;
+mod conditional;
 mod create_post;
 mod delete_post;
 mod get_post;
@@ -5,6 +8,9 @@ mod get_post_list;
 mod response;
 mod update_post;
 
+pub(crate) use batch_get_posts::*;
+pub(crate) use bulk_create_posts::*;
+pub(crate) use conditional::*;
 pub(crate) use create_post::*;
 pub(crate) use delete_post::*;
 pub(crate) use get_post::*;
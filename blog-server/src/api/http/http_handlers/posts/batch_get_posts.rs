@@ -0,0 +1,39 @@
+use crate::api::http::errors::ApiError;
+use crate::api::http::http_handlers::posts::BatchResponse;
+use crate::application::blog::batch_get_posts_handler;
+use crate::application::contracts::PostRepository;
+use crate::domain::value_objects::Identification;
+use actix_web::http::StatusCode;
+use actix_web::{HttpResponse, get, web};
+use serde::Deserialize;
+use std::sync::Arc;
+use uuid::Uuid;
+
+#[get("/posts/batch")]
+#[tracing::instrument(name = "Batch get posts", skip(post_repo))]
+pub(crate) async fn batch_get_posts(
+    query: web::Query<BatchQuery>,
+    post_repo: web::Data<Arc<dyn PostRepository>>,
+) -> Result<HttpResponse, ApiError> {
+    let post_ids = query
+        .ids
+        .split(',')
+        .map(|id| {
+            Uuid::try_parse(id.trim())
+                .map(Identification::from)
+                .map_err(|_| ApiError::UnprocessableEntity(format!("invalid post id: {}", id)))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let results = batch_get_posts_handler(post_ids, post_repo.get_ref())
+        .await?
+        .into_iter()
+        .map(|result| result.map_err(ApiError::from))
+        .collect::<Vec<_>>();
+    Ok(HttpResponse::Ok().json(BatchResponse::from(&results, StatusCode::OK)))
+}
+
+#[derive(Debug, Deserialize)]
+struct BatchQuery {
+    /// Comma-separated post ids.
+    ids: String,
+}
@@ -0,0 +1,33 @@
+use crate::api::http::errors::ApiError;
+use crate::api::http::extractors::AuthenticatedUser;
+use crate::api::http::http_handlers::posts::BatchResponse;
+use crate::application::blog::{BulkCreatePostsCommand, bulk_create_posts_handler};
+use crate::application::contracts::{PostEventBus, PostRepository};
+use crate::domain::value_objects::Scope;
+use crate::infrastructure::Metrics;
+use actix_web::http::StatusCode;
+use actix_web::{HttpResponse, post, web};
+use std::sync::Arc;
+
+#[post("/batch")]
+#[tracing::instrument(
+    name = "Bulk create posts",
+    skip(request, post_repo, post_events, metrics)
+)]
+pub(crate) async fn bulk_create_posts(
+    user: AuthenticatedUser,
+    request: web::Json<BulkCreatePostsCommand>,
+    post_repo: web::Data<Arc<dyn PostRepository>>,
+    post_events: web::Data<Arc<dyn PostEventBus>>,
+    metrics: web::Data<Arc<Metrics>>,
+) -> Result<HttpResponse, ApiError> {
+    user.ensure_scope(Scope::PostsWrite)?;
+    let results =
+        bulk_create_posts_handler(user.into(), request.into_inner(), &post_repo, &post_events)
+            .await?
+            .into_iter()
+            .map(|result| result.map_err(ApiError::from))
+            .collect::<Vec<_>>();
+    metrics.posts_created(results.iter().filter(|result| result.is_ok()).count());
+    Ok(HttpResponse::Ok().json(BatchResponse::from(&results, StatusCode::CREATED)))
+}
@@ -0,0 +1,73 @@
+use crate::api::http::errors::ApiError;
+use actix_web::http::header::{
+    CacheControl, CacheDirective, ContentType, ETag, EntityTag, Header, IfNoneMatch,
+};
+use actix_web::{HttpRequest, HttpResponse};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::time::Duration;
+
+/// Serialises `body` as JSON tagged with a hash of its content, answering `304 Not Modified`
+/// without a body when the client's `If-None-Match` already names that tag.
+pub(crate) fn conditional_json_response(
+    req: &HttpRequest,
+    body: &impl Serialize,
+    max_age: Duration,
+) -> Result<HttpResponse, ApiError> {
+    let body =
+        serde_json::to_vec(body).map_err(|err| ApiError::InternalServerError(err.to_string()))?;
+    let etag = EntityTag::new_strong(format!("{:x}", Sha256::digest(&body)));
+    let is_not_modified = is_not_modified(IfNoneMatch::parse(req).ok().as_ref(), &etag);
+
+    let mut response = if is_not_modified {
+        HttpResponse::NotModified()
+    } else {
+        HttpResponse::Ok()
+    };
+    response
+        .insert_header(ETag(etag))
+        .insert_header(CacheControl(vec![
+            CacheDirective::Public,
+            CacheDirective::MaxAge(u32::try_from(max_age.as_secs()).unwrap_or(u32::MAX)),
+        ]));
+    if is_not_modified {
+        Ok(response.finish())
+    } else {
+        Ok(response.content_type(ContentType::json()).body(body))
+    }
+}
+
+/// `If-None-Match` uses the weak comparison, so a tag also matches its weak form.
+fn is_not_modified(if_none_match: Option<&IfNoneMatch>, etag: &EntityTag) -> bool {
+    match if_none_match {
+        Some(IfNoneMatch::Any) => true,
+        Some(IfNoneMatch::Items(tags)) => tags.iter().any(|tag| tag.weak_eq(etag)),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_not_modified_matches_any_and_weak_tags() {
+        let etag = EntityTag::new_strong("abc".to_string());
+
+        assert!(!is_not_modified(None, &etag));
+        assert!(is_not_modified(Some(&IfNoneMatch::Any), &etag));
+        assert!(is_not_modified(
+            Some(&IfNoneMatch::Items(vec![
+                EntityTag::new_strong("other".to_string()),
+                EntityTag::new_weak("abc".to_string()),
+            ])),
+            &etag
+        ));
+        assert!(!is_not_modified(
+            Some(&IfNoneMatch::Items(vec![EntityTag::new_strong(
+                "other".to_string()
+            )])),
+            &etag
+        ));
+    }
+}
@@ -2,18 +2,25 @@ use crate::api::http::errors::ApiError;
 use crate::api::http::extractors::AuthenticatedUser;
 use crate::api::http::http_handlers::posts::PostResponse;
 use crate::application::blog::{CreatePostCommand, create_post_handler};
-use crate::application::contracts::PostRepository;
+use crate::application::contracts::{PostEventBus, PostRepository};
+use crate::domain::value_objects::Scope;
+use crate::infrastructure::Metrics;
 use actix_web::{HttpResponse, post, web};
 use std::sync::Arc;
 
 #[post("")]
-#[tracing::instrument(name = "Create post", skip(post_repo))]
+#[tracing::instrument(name = "Create post", skip(post_repo, post_events, metrics))]
 pub(crate) async fn create_post(
     user: AuthenticatedUser,
     request: web::Json<CreatePostCommand>,
     post_repo: web::Data<Arc<dyn PostRepository>>,
+    post_events: web::Data<Arc<dyn PostEventBus>>,
+    metrics: web::Data<Arc<Metrics>>,
 ) -> Result<HttpResponse, ApiError> {
-    let post = create_post_handler(user.into(), request.into_inner(), &post_repo).await?;
+    user.ensure_scope(Scope::PostsWrite)?;
+    let post =
+        create_post_handler(user.into(), request.into_inner(), &post_repo, &post_events).await?;
+    metrics.posts_created(1);
     Ok(HttpResponse::Created()
         .append_header(("Location", format!("/api/posts/{}", post.id().as_ref())))
         .json(PostResponse::from(&post)))
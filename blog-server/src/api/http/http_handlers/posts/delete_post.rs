@@ -1,23 +1,26 @@
 use crate::api::http::errors::ApiError;
 use crate::api::http::extractors::AuthenticatedUser;
 use crate::application::blog::delete_post_handler;
-use crate::application::contracts::PostRepository;
-use crate::domain::value_objects::Identification;
+use crate::application::contracts::{PostEventBus, UnitOfWork};
+use crate::domain::value_objects::{Identification, Scope};
 use actix_web::{HttpResponse, delete, web};
 use std::sync::Arc;
 use uuid::Uuid;
 
 #[delete("/{id}")]
-#[tracing::instrument(name = "Delete post", skip(post_repo))]
+#[tracing::instrument(name = "Delete post", skip(unit_of_work, post_events))]
 pub(crate) async fn delete_post(
     path: web::Path<Uuid>,
     user: AuthenticatedUser,
-    post_repo: web::Data<Arc<dyn PostRepository>>,
+    unit_of_work: web::Data<Arc<dyn UnitOfWork>>,
+    post_events: web::Data<Arc<dyn PostEventBus>>,
 ) -> Result<HttpResponse, ApiError> {
+    user.ensure_scope(Scope::PostsDelete)?;
     delete_post_handler(
         Identification::from(path.into_inner()),
         user.into(),
-        &post_repo,
+        &unit_of_work,
+        &post_events,
     )
     .await?;
     Ok(HttpResponse::NoContent().finish())
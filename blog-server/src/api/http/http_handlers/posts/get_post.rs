@@ -1,19 +1,123 @@
 use crate::api::http::errors::ApiError;
-use crate::api::http::http_handlers::posts::PostResponse;
+use crate::api::http::http_handlers::posts::{PostResponse, conditional_json_response};
 use crate::application::blog::get_post_handler;
 use crate::application::contracts::PostRepository;
+use crate::configuration::CacheConfiguration;
 use crate::domain::value_objects::Identification;
-use actix_web::{HttpResponse, get, web};
+use actix_web::{HttpRequest, HttpResponse, get, web};
 use std::sync::Arc;
 use uuid::Uuid;
 
 #[get("/posts/{id}")]
-#[tracing::instrument(name = "Get post", skip(post_repo))]
+#[tracing::instrument(name = "Get post", skip(req, post_repo, cache_config))]
 pub(crate) async fn get_post(
+    req: HttpRequest,
     path: web::Path<Uuid>,
     post_repo: web::Data<Arc<dyn PostRepository>>,
+    cache_config: web::Data<CacheConfiguration>,
 ) -> Result<HttpResponse, ApiError> {
     let post =
         get_post_handler(Identification::from(path.into_inner()), post_repo.get_ref()).await?;
-    Ok(HttpResponse::Ok().json(PostResponse::from(&post)))
+    conditional_json_response(&req, &PostResponse::from(&post), cache_config.get_max_age())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::configuration::Configuration;
+    use crate::domain::entities::Post;
+    use crate::domain::value_objects::{Content, Title};
+    use crate::infrastructure::{CachedPostRepository, InMemoryPostCacheStore, PostCacheStore};
+    use actix_web::http::StatusCode;
+    use actix_web::http::header::{ETAG, IF_NONE_MATCH};
+    use actix_web::{App, test};
+    use async_trait::async_trait;
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    struct StubPostRepository {
+        post: Mutex<Post>,
+    }
+
+    #[async_trait]
+    impl PostRepository for StubPostRepository {
+        async fn create(&self, _: &Post) -> Result<(), anyhow::Error> {
+            unimplemented!()
+        }
+
+        async fn create_many(&self, _: &[Post]) -> Result<(), anyhow::Error> {
+            unimplemented!()
+        }
+
+        async fn get(&self, _: &Identification) -> Result<Option<Post>, anyhow::Error> {
+            Ok(Some(self.post.lock().unwrap().clone()))
+        }
+
+        async fn get_many(&self, _: &[Identification]) -> Result<Vec<Post>, anyhow::Error> {
+            unimplemented!()
+        }
+
+        async fn list(&self, _: usize, _: usize) -> Result<(Vec<Post>, usize), anyhow::Error> {
+            unimplemented!()
+        }
+    }
+
+    #[actix_web::test]
+    async fn get_post_answers_not_modified_until_the_post_changes() {
+        let post = Post::new(
+            Title::try_from("title".to_string()).unwrap(),
+            Content::from("content".to_string()),
+            Identification::new(),
+        );
+        let stub = Arc::new(StubPostRepository {
+            post: Mutex::new(post.clone()),
+        });
+        let cache: Arc<dyn PostCacheStore> =
+            Arc::new(InMemoryPostCacheStore::new(10, Duration::from_secs(60)));
+        let post_repo: Arc<dyn PostRepository> =
+            Arc::new(CachedPostRepository::new(stub.clone(), Arc::clone(&cache)));
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(post_repo))
+                .app_data(web::Data::new(
+                    Configuration::for_tests("")
+                        .get_cache_configuration()
+                        .clone(),
+                ))
+                .service(get_post),
+        )
+        .await;
+        let uri = format!("/posts/{}", post.id().as_ref());
+
+        let response =
+            test::call_service(&app, test::TestRequest::get().uri(&uri).to_request()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let etag = response.headers().get(ETAG).unwrap().clone();
+
+        let request = test::TestRequest::get()
+            .uri(&uri)
+            .insert_header((IF_NONE_MATCH, etag.clone()))
+            .to_request();
+        let response = test::call_service(&app, request).await;
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert!(test::read_body(response).await.is_empty());
+
+        *stub.post.lock().unwrap() = Post::restore(
+            post.id().clone(),
+            Title::try_from("updated".to_string()).unwrap(),
+            post.content().clone(),
+            post.author_id().clone(),
+            post.created_at().clone(),
+            post.updated_at().clone(),
+        );
+        cache.remove_post(post.id()).await;
+
+        let request = test::TestRequest::get()
+            .uri(&uri)
+            .insert_header((IF_NONE_MATCH, etag.clone()))
+            .to_request();
+        let response = test::call_service(&app, request).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_ne!(response.headers().get(ETAG), Some(&etag));
+    }
 }
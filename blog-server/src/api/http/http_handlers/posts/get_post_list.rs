@@ -1,16 +1,19 @@
 use crate::api::http::errors::ApiError;
-use crate::api::http::http_handlers::posts::PostResponse;
+use crate::api::http::http_handlers::posts::{PostResponse, conditional_json_response};
 use crate::application::blog::get_post_list_handler;
 use crate::application::contracts::PostRepository;
-use actix_web::{HttpResponse, get, web};
+use crate::configuration::CacheConfiguration;
+use actix_web::{HttpRequest, HttpResponse, get, web};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
 #[get("/posts")]
-#[tracing::instrument(name = "Get post list", skip(post_repo))]
+#[tracing::instrument(name = "Get post list", skip(req, post_repo, cache_config))]
 pub(crate) async fn get_post_list(
+    req: HttpRequest,
     pagination: web::Query<Pagination>,
     post_repo: web::Data<Arc<dyn PostRepository>>,
+    cache_config: web::Data<CacheConfiguration>,
 ) -> Result<HttpResponse, ApiError> {
     let (posts, total) =
         get_post_list_handler(pagination.limit, pagination.offset, post_repo.get_ref())
@@ -23,7 +26,7 @@ pub(crate) async fn get_post_list(
         offset: pagination.offset,
     };
 
-    Ok(HttpResponse::Ok().json(response))
+    conditional_json_response(&req, &response, cache_config.get_max_age())
 }
 
 #[derive(Debug, Deserialize)]
@@ -1,4 +1,7 @@
+use crate::api::http::errors::{ApiError, ViolationResponse};
 use crate::domain::entities::Post;
+use actix_web::ResponseError;
+use actix_web::http::StatusCode;
 use uuid::Uuid;
 
 #[derive(serde::Serialize)]
@@ -23,3 +26,58 @@ impl<'a> PostResponse<'a> {
         }
     }
 }
+
+/// Outcome of one item of a batch request, with the status the item would get on its own.
+#[derive(serde::Serialize)]
+pub(crate) struct BatchItemResponse<'a> {
+    status: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    post: Option<PostResponse<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    code: Option<&'static str>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    violations: Vec<ViolationResponse>,
+}
+
+impl<'a> BatchItemResponse<'a> {
+    pub(crate) fn from(result: &'a Result<Post, ApiError>, success: StatusCode) -> Self {
+        match result {
+            Ok(post) => Self {
+                status: success.as_u16(),
+                post: Some(PostResponse::from(post)),
+                error: None,
+                code: None,
+                violations: Vec::new(),
+            },
+            Err(err) => Self {
+                status: err.status_code().as_u16(),
+                post: None,
+                error: Some(err.to_string()),
+                code: Some(err.code()),
+                violations: err
+                    .violations()
+                    .iter()
+                    .map(ViolationResponse::from)
+                    .collect(),
+            },
+        }
+    }
+}
+
+#[derive(serde::Serialize)]
+pub(crate) struct BatchResponse<'a> {
+    results: Vec<BatchItemResponse<'a>>,
+}
+
+impl<'a> BatchResponse<'a> {
+    pub(crate) fn from(results: &'a [Result<Post, ApiError>], success: StatusCode) -> Self {
+        Self {
+            results: results
+                .iter()
+                .map(|result| BatchItemResponse::from(result, success))
+                .collect(),
+        }
+    }
+}
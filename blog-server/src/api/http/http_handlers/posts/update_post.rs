@@ -2,25 +2,28 @@ use crate::api::http::errors::ApiError;
 use crate::api::http::extractors::AuthenticatedUser;
 use crate::api::http::http_handlers::posts::PostResponse;
 use crate::application::blog::{UpdatePostCommand, update_post_handler};
-use crate::application::contracts::PostRepository;
-use crate::domain::value_objects::Identification;
+use crate::application::contracts::{PostEventBus, UnitOfWork};
+use crate::domain::value_objects::{Identification, Scope};
 use actix_web::{HttpResponse, put, web};
 use std::sync::Arc;
 use uuid::Uuid;
 
 #[put("/{id}")]
-#[tracing::instrument(name = "Update post", skip(post_repo))]
+#[tracing::instrument(name = "Update post", skip(unit_of_work, post_events))]
 pub(crate) async fn update_post(
     path: web::Path<Uuid>,
     user: AuthenticatedUser,
     request: web::Json<UpdatePostCommand>,
-    post_repo: web::Data<Arc<dyn PostRepository>>,
+    unit_of_work: web::Data<Arc<dyn UnitOfWork>>,
+    post_events: web::Data<Arc<dyn PostEventBus>>,
 ) -> Result<HttpResponse, ApiError> {
+    user.ensure_scope(Scope::PostsWrite)?;
     let post = update_post_handler(
         Identification::from(path.into_inner()),
         user.into(),
         request.into_inner(),
-        &post_repo,
+        &unit_of_work,
+        &post_events,
     )
     .await?;
     Ok(HttpResponse::Ok().json(PostResponse::from(&post)))
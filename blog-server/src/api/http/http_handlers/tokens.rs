@@ -0,0 +1,9 @@
+mod create_token;
+mod list_tokens;
+mod response;
+mod revoke_token;
+
+pub(crate) use create_token::*;
+pub(crate) use list_tokens::*;
+pub(crate) use response::*;
+pub(crate) use revoke_token::*;
@@ -0,0 +1,38 @@
+use crate::api::http::errors::ApiError;
+use crate::api::http::extractors::AuthenticatedUser;
+use crate::api::http::http_handlers::tokens::ApiTokenResponse;
+use crate::application::api_tokens::{CreateApiTokenCommand, create_api_token_handler};
+use crate::application::contracts::ApiTokenRepository;
+use crate::domain::value_objects::Scope;
+use actix_web::{HttpResponse, post, web};
+use secrecy::ExposeSecret;
+use std::sync::Arc;
+
+#[post("")]
+#[tracing::instrument(name = "Create api token", skip(token_repo))]
+pub(crate) async fn create_token(
+    user: AuthenticatedUser,
+    request: web::Json<CreateApiTokenCommand>,
+    token_repo: web::Data<Arc<dyn ApiTokenRepository>>,
+) -> Result<HttpResponse, ApiError> {
+    user.ensure_scope(Scope::AccountManage)?;
+    let granted_scopes = user.scopes().to_vec();
+    let (api_token, token) = create_api_token_handler(
+        user.into(),
+        &granted_scopes,
+        request.into_inner(),
+        &token_repo,
+    )
+    .await?;
+    Ok(HttpResponse::Created().json(Response {
+        token: token.expose_secret(),
+        api_token: ApiTokenResponse::from(&api_token),
+    }))
+}
+
+#[derive(serde::Serialize)]
+struct Response<'a> {
+    token: &'a str,
+    #[serde(flatten)]
+    api_token: ApiTokenResponse<'a>,
+}
@@ -0,0 +1,23 @@
+use crate::api::http::errors::ApiError;
+use crate::api::http::extractors::AuthenticatedUser;
+use crate::api::http::http_handlers::tokens::ApiTokenResponse;
+use crate::application::api_tokens::list_api_tokens_handler;
+use crate::application::contracts::ApiTokenRepository;
+use crate::domain::value_objects::Scope;
+use actix_web::{HttpResponse, get, web};
+use std::sync::Arc;
+
+#[get("")]
+#[tracing::instrument(name = "List api tokens", skip(token_repo))]
+pub(crate) async fn list_tokens(
+    user: AuthenticatedUser,
+    token_repo: web::Data<Arc<dyn ApiTokenRepository>>,
+) -> Result<HttpResponse, ApiError> {
+    user.ensure_scope(Scope::AccountManage)?;
+    let tokens = list_api_tokens_handler(user.into(), &token_repo).await?;
+    let response = tokens
+        .iter()
+        .map(ApiTokenResponse::from)
+        .collect::<Vec<ApiTokenResponse>>();
+    Ok(HttpResponse::Ok().json(response))
+}
@@ -0,0 +1,27 @@
+use crate::domain::entities::ApiToken;
+use uuid::Uuid;
+
+#[derive(serde::Serialize)]
+pub(crate) struct ApiTokenResponse<'a> {
+    id: &'a Uuid,
+    name: &'a str,
+    scopes: Vec<&'a str>,
+    created_at: &'a chrono::DateTime<chrono::Utc>,
+    expires_at: Option<&'a chrono::DateTime<chrono::Utc>>,
+    last_used_at: Option<&'a chrono::DateTime<chrono::Utc>>,
+    revoked_at: Option<&'a chrono::DateTime<chrono::Utc>>,
+}
+
+impl<'a> ApiTokenResponse<'a> {
+    pub(crate) fn from(token: &'a ApiToken) -> ApiTokenResponse<'a> {
+        Self {
+            id: token.id().as_ref(),
+            name: token.name().as_ref(),
+            scopes: token.scopes().iter().map(|scope| scope.as_ref()).collect(),
+            created_at: token.created_at().as_ref(),
+            expires_at: token.expires_at().map(|x| x.as_ref()),
+            last_used_at: token.last_used_at().map(|x| x.as_ref()),
+            revoked_at: token.revoked_at().map(|x| x.as_ref()),
+        }
+    }
+}
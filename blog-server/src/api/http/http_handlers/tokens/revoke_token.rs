@@ -0,0 +1,25 @@
+use crate::api::http::errors::ApiError;
+use crate::api::http::extractors::AuthenticatedUser;
+use crate::application::api_tokens::revoke_api_token_handler;
+use crate::application::contracts::UnitOfWork;
+use crate::domain::value_objects::{Identification, Scope};
+use actix_web::{HttpResponse, delete, web};
+use std::sync::Arc;
+use uuid::Uuid;
+
+#[delete("/{id}")]
+#[tracing::instrument(name = "Revoke api token", skip(unit_of_work))]
+pub(crate) async fn revoke_token(
+    path: web::Path<Uuid>,
+    user: AuthenticatedUser,
+    unit_of_work: web::Data<Arc<dyn UnitOfWork>>,
+) -> Result<HttpResponse, ApiError> {
+    user.ensure_scope(Scope::AccountManage)?;
+    revoke_api_token_handler(
+        Identification::from(path.into_inner()),
+        user.into(),
+        &unit_of_work,
+    )
+    .await?;
+    Ok(HttpResponse::NoContent().finish())
+}
@@ -1 +1,3 @@
 pub(crate) mod auth;
+pub(crate) mod metrics;
+pub(crate) mod rate_limit;
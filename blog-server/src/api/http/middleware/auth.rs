@@ -1,4 +1,6 @@
 use crate::api::http::errors::create_error_response;
+use crate::api::{AuthenticationError, authenticate};
+use crate::application::contracts::{ApiTokenRepository, UnitOfWork};
 use crate::infrastructure::JwtService;
 use actix_web::body::BoxBody;
 use actix_web::dev::{ServiceRequest, ServiceResponse};
@@ -19,36 +21,61 @@ pub(crate) async fn auth_middleware(
                     tracing::error!("Error parsing authorization header: {}", err);
                     Ok(req.into_response(create_unauthorized_response()))
                 }
-                Ok(header_value) => {
-                    if header_value.starts_with("Bearer ") {
-                        let token = header_value.split(" ").collect::<Vec<&str>>()[1];
-                        let jwt_service = req.app_data::<web::Data<Arc<JwtService>>>();
-                        match jwt_service {
-                            None => {
-                                tracing::error!("JwtService not found in request data");
-                                Ok(req.into_response(create_internal_server_error_response()))
-                            }
-                            Some(jwt_service) => {
-                                let claims = jwt_service.decode_jwt(token);
-                                match claims {
-                                    Err(_) => Ok(req.into_response(create_unauthorized_response())),
-                                    Ok(claims) => {
-                                        req.extensions_mut().insert(claims.sub());
-                                        next.call(req).await
-                                    }
-                                }
-                            }
-                        }
-                    } else {
-                        Ok(req.into_response(create_unauthorized_response()))
+                Ok(header_value) => match header_value.strip_prefix("Bearer ") {
+                    Some(token) => {
+                        let token = token.to_string();
+                        authenticate_request(req, next, &token).await
                     }
-                }
+                    None => Ok(req.into_response(create_unauthorized_response())),
+                },
             }
         }
         None => Ok(req.into_response(create_unauthorized_response())),
     }
 }
 
+async fn authenticate_request(
+    req: ServiceRequest,
+    next: Next<BoxBody>,
+    token: &str,
+) -> Result<ServiceResponse<BoxBody>, Error> {
+    let Some(jwt_service) = req.app_data::<web::Data<Arc<JwtService>>>() else {
+        tracing::error!("JwtService not found in request data");
+        return Ok(req.into_response(create_internal_server_error_response()));
+    };
+    let Some(token_repo) = req.app_data::<web::Data<Arc<dyn ApiTokenRepository>>>() else {
+        tracing::error!("ApiTokenRepository not found in request data");
+        return Ok(req.into_response(create_internal_server_error_response()));
+    };
+    let Some(unit_of_work) = req.app_data::<web::Data<Arc<dyn UnitOfWork>>>() else {
+        tracing::error!("UnitOfWork not found in request data");
+        return Ok(req.into_response(create_internal_server_error_response()));
+    };
+    match authenticate(
+        token,
+        jwt_service.get_ref(),
+        token_repo.get_ref(),
+        unit_of_work.get_ref(),
+    )
+    .await
+    {
+        Ok(principal) => {
+            req.extensions_mut().insert(principal);
+            next.call(req).await
+        }
+        Err(AuthenticationError::InvalidToken) => {
+            Ok(req.into_response(create_unauthorized_response()))
+        }
+        Err(AuthenticationError::Unavailable) => {
+            Ok(req.into_response(create_service_unavailable_response()))
+        }
+        Err(AuthenticationError::Unexpected(err)) => {
+            tracing::error!("Error authenticating request: {}", err);
+            Ok(req.into_response(create_internal_server_error_response()))
+        }
+    }
+}
+
 fn create_unauthorized_response() -> actix_web::HttpResponse {
     create_error_response(StatusCode::UNAUTHORIZED, "unauthorized".to_string())
 }
@@ -59,3 +86,10 @@ fn create_internal_server_error_response() -> actix_web::HttpResponse {
         "internal server error".to_string(),
     )
 }
+
+fn create_service_unavailable_response() -> actix_web::HttpResponse {
+    create_error_response(
+        StatusCode::SERVICE_UNAVAILABLE,
+        "service temporarily unavailable".to_string(),
+    )
+}
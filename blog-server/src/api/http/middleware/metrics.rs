@@ -0,0 +1,59 @@
+use crate::infrastructure::{Metrics, UNMATCHED_ROUTE};
+use actix_web::body::BoxBody;
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::Method;
+use actix_web::middleware::Next;
+use actix_web::{Error, web};
+use std::sync::Arc;
+use std::time::Instant;
+
+pub(crate) async fn metrics_middleware(
+    req: ServiceRequest,
+    next: Next<BoxBody>,
+) -> Result<ServiceResponse<BoxBody>, Error> {
+    let Some(metrics) = req.app_data::<web::Data<Arc<Metrics>>>().cloned() else {
+        tracing::error!("Metrics not found in request data");
+        return next.call(req).await;
+    };
+
+    let method = method_label(req.method());
+    let route = req
+        .match_pattern()
+        .unwrap_or_else(|| UNMATCHED_ROUTE.to_string());
+    let started_at = Instant::now();
+    let result = next.call(req).await;
+    let status = match &result {
+        Ok(response) => response.status(),
+        Err(err) => err.as_response_error().status_code(),
+    };
+    metrics.observe_http_request(method, &route, status.as_u16(), started_at.elapsed());
+    result
+}
+
+/// Folds non-standard methods into a single label value to keep cardinality bounded.
+fn method_label(method: &Method) -> &'static str {
+    match *method {
+        Method::GET => "GET",
+        Method::POST => "POST",
+        Method::PUT => "PUT",
+        Method::DELETE => "DELETE",
+        Method::PATCH => "PATCH",
+        Method::HEAD => "HEAD",
+        Method::OPTIONS => "OPTIONS",
+        _ => "OTHER",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn method_label_folds_extension_methods() {
+        assert_eq!(method_label(&Method::PUT), "PUT");
+        assert_eq!(
+            method_label(&Method::from_bytes(b"PURGE").unwrap()),
+            "OTHER"
+        );
+    }
+}
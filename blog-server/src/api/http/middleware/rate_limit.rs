@@ -0,0 +1,144 @@
+use crate::api::http::errors::create_error_response;
+use crate::api::{FORWARD_SECRET_HEADER, ForwardSecret, forwarded_client_ip, rate_limit_subject};
+use crate::infrastructure::{
+    JwtService, RateLimitDecision, RateLimitOperation, RateLimitSubject, RateLimiter,
+};
+use actix_web::body::BoxBody;
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::header::{
+    FORWARDED, HeaderMap, HeaderName, HeaderValue, RETRY_AFTER, X_FORWARDED_FOR,
+};
+use actix_web::http::{Method, StatusCode};
+use actix_web::middleware::Next;
+use actix_web::{Error, web};
+use std::net::IpAddr;
+use std::sync::Arc;
+
+const RATE_LIMIT_LIMIT: &str = "x-ratelimit-limit";
+const RATE_LIMIT_REMAINING: &str = "x-ratelimit-remaining";
+const RATE_LIMIT_RESET: &str = "x-ratelimit-reset";
+/// Probe endpoints are never rate limited, so that the orchestrator is not throttled.
+const HEALTH_PATH_PREFIX: &str = "/health/";
+/// The metrics endpoint is never rate limited, so that scrapes are not throttled.
+const METRICS_PATH: &str = "/metrics";
+
+/// Marks connections to the internal listener of single-port mode. Their `X-Forwarded-For`
+/// header holds the client address when the request carries the [`ForwardSecret`].
+pub(crate) struct ForwardedConnection {
+    secret: ForwardSecret,
+}
+
+impl ForwardedConnection {
+    pub(crate) fn new(secret: ForwardSecret) -> Self {
+        Self { secret }
+    }
+}
+
+pub(crate) async fn rate_limit_middleware(
+    req: ServiceRequest,
+    next: Next<BoxBody>,
+) -> Result<ServiceResponse<BoxBody>, Error> {
+    if req.method() == Method::OPTIONS
+        || req.path().starts_with(HEALTH_PATH_PREFIX)
+        || req.path() == METRICS_PATH
+    {
+        return next.call(req).await;
+    }
+
+    let Some(rate_limiter) = req.app_data::<web::Data<Arc<RateLimiter>>>() else {
+        tracing::error!("RateLimiter not found in request data");
+        return next.call(req).await;
+    };
+    if !rate_limiter.is_enabled() {
+        return next.call(req).await;
+    }
+    let Some(jwt_service) = req.app_data::<web::Data<Arc<JwtService>>>() else {
+        tracing::error!("JwtService not found in request data");
+        return next.call(req).await;
+    };
+
+    let subject = resolve_subject(&req, jwt_service);
+    let operation = match *req.method() {
+        Method::GET | Method::HEAD => RateLimitOperation::Read,
+        _ => RateLimitOperation::Write,
+    };
+    let decision = rate_limiter.check(subject, operation);
+
+    if !decision.is_allowed() {
+        tracing::warn!("Rate limit exceeded for {:?}", subject);
+        let mut response = create_error_response(
+            StatusCode::TOO_MANY_REQUESTS,
+            "too many requests".to_string(),
+        );
+        add_rate_limit_headers(response.headers_mut(), &decision);
+        response.headers_mut().insert(
+            RETRY_AFTER,
+            HeaderValue::from(decision.reset_after_secs().max(1)),
+        );
+        return Ok(req.into_response(response));
+    }
+
+    let mut response = next.call(req).await?;
+    add_rate_limit_headers(response.headers_mut(), &decision);
+    Ok(response)
+}
+
+/// Limits callers with a valid JWT per user and all others per client address. Personal
+/// access tokens would need a database lookup, so they are limited per client address, and
+/// authentication is left to `auth_middleware`.
+fn resolve_subject(req: &ServiceRequest, jwt_service: &JwtService) -> RateLimitSubject {
+    let token = req
+        .headers()
+        .get("Authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    rate_limit_subject(token, client_ip(req), jwt_service)
+}
+
+fn client_ip(req: &ServiceRequest) -> Option<IpAddr> {
+    // Other local processes can connect to the internal listener too; they are limited by
+    // their loopback address.
+    if let Some(connection) = req.conn_data::<ForwardedConnection>()
+        && req
+            .headers()
+            .get(FORWARD_SECRET_HEADER)
+            .is_some_and(|value| connection.secret.matches(value.as_bytes()))
+    {
+        return req
+            .headers()
+            .get(X_FORWARDED_FOR)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse().ok());
+    }
+    match req.peer_addr() {
+        Some(addr) => Some(addr.ip()),
+        // Only Unix socket connections have no peer address. Just local processes can reach
+        // the socket, so the headers of the reverse proxy in front of it are trusted.
+        None => forwarded_client_ip(
+            last_header(req.headers(), &FORWARDED),
+            last_header(req.headers(), &X_FORWARDED_FOR),
+        ),
+    }
+}
+
+fn last_header<'a>(headers: &'a HeaderMap, name: &HeaderName) -> Option<&'a str> {
+    headers
+        .get_all(name)
+        .last()
+        .and_then(|value| value.to_str().ok())
+}
+
+fn add_rate_limit_headers(headers: &mut HeaderMap, decision: &RateLimitDecision) {
+    headers.insert(
+        HeaderName::from_static(RATE_LIMIT_LIMIT),
+        HeaderValue::from(decision.limit()),
+    );
+    headers.insert(
+        HeaderName::from_static(RATE_LIMIT_REMAINING),
+        HeaderValue::from(decision.remaining()),
+    );
+    headers.insert(
+        HeaderName::from_static(RATE_LIMIT_RESET),
+        HeaderValue::from(decision.reset_after_secs()),
+    );
+}
@@ -1,3 +1,4 @@
+pub(crate) mod api_tokens;
 pub(crate) mod auth;
 pub(crate) mod blog;
 pub(crate) mod contracts;
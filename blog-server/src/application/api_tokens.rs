@@ -0,0 +1,11 @@
+mod authenticate_api_token_handler;
+mod create_api_token_handler;
+mod list_api_tokens_handler;
+mod revoke_api_token_handler;
+mod token_secret;
+
+pub(crate) use authenticate_api_token_handler::*;
+pub(crate) use create_api_token_handler::*;
+pub(crate) use list_api_tokens_handler::*;
+pub(crate) use revoke_api_token_handler::*;
+pub(crate) use token_secret::is_api_token;
@@ -0,0 +1,73 @@
+use crate::application::api_tokens::token_secret::hash_api_token;
+use crate::application::contracts::{ApiTokenRepository, UnitOfWork, storage_error};
+use crate::domain::entities::ApiToken;
+use std::sync::Arc;
+
+/// Resolves a personal access token. `last_used_at` is stored at most once per minute, under a
+/// row lock so that it cannot race with a revocation.
+#[tracing::instrument(name = "Handle authenticate api token command", skip_all)]
+pub(crate) async fn authenticate_api_token_handler(
+    token: &str,
+    token_repo: &Arc<dyn ApiTokenRepository>,
+    unit_of_work: &Arc<dyn UnitOfWork>,
+) -> Result<ApiToken, AuthenticateApiTokenError> {
+    let api_token = token_repo
+        .get_by_hash(&hash_api_token(token))
+        .await
+        .map_err(storage_error(
+            AuthenticateApiTokenError::Unavailable,
+            AuthenticateApiTokenError::Unexpected,
+        ))?
+        .ok_or(AuthenticateApiTokenError::InvalidToken)?;
+
+    if !api_token.is_active() {
+        return Err(AuthenticateApiTokenError::InvalidToken);
+    }
+
+    if !api_token.is_use_due() {
+        return Ok(api_token);
+    }
+
+    let mut transaction = unit_of_work.begin().await.map_err(storage_error(
+        AuthenticateApiTokenError::Unavailable,
+        AuthenticateApiTokenError::Unexpected,
+    ))?;
+    let mut api_token = transaction
+        .api_tokens()
+        .get_api_token_for_update(api_token.id())
+        .await
+        .map_err(storage_error(
+            AuthenticateApiTokenError::Unavailable,
+            AuthenticateApiTokenError::Unexpected,
+        ))?
+        .ok_or(AuthenticateApiTokenError::InvalidToken)?;
+    if !api_token.is_active() {
+        return Err(AuthenticateApiTokenError::InvalidToken);
+    }
+    if api_token.mark_used() {
+        transaction
+            .api_tokens()
+            .update_api_token(&api_token)
+            .await
+            .map_err(storage_error(
+                AuthenticateApiTokenError::Unavailable,
+                AuthenticateApiTokenError::Unexpected,
+            ))?;
+        transaction.commit().await.map_err(storage_error(
+            AuthenticateApiTokenError::Unavailable,
+            AuthenticateApiTokenError::Unexpected,
+        ))?;
+    }
+
+    Ok(api_token)
+}
+
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum AuthenticateApiTokenError {
+    #[error("invalid token")]
+    InvalidToken,
+    #[error("storage is temporarily unavailable")]
+    Unavailable,
+    #[error("unexpected error: {0}")]
+    Unexpected(String),
+}
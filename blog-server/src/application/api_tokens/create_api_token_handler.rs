@@ -0,0 +1,90 @@
+use crate::application::api_tokens::token_secret::{generate_api_token, hash_api_token};
+use crate::application::contracts::{ApiTokenRepository, storage_error};
+use crate::domain::entities::ApiToken;
+use crate::domain::value_objects::{
+    ApiTokenName, ApiTokenNameError, DateTime, Identification, Scope, ScopeError,
+};
+use secrecy::SecretString;
+use std::sync::Arc;
+
+const MAX_EXPIRES_IN_DAYS: u32 = 365 * 5;
+
+#[tracing::instrument(name = "Handle create api token command", skip(token_repo))]
+pub(crate) async fn create_api_token_handler(
+    user_id: Identification,
+    granted_scopes: &[Scope],
+    cmd: CreateApiTokenCommand,
+    token_repo: &Arc<dyn ApiTokenRepository>,
+) -> Result<(ApiToken, SecretString), CreateApiTokenError> {
+    let name = ApiTokenName::try_from(cmd.name)?;
+    if cmd.scopes.is_empty() {
+        return Err(CreateApiTokenError::InvalidScope(ScopeError::Empty));
+    }
+    let mut scopes = Vec::with_capacity(cmd.scopes.len());
+    for scope in &cmd.scopes {
+        let scope = Scope::try_from(scope.as_str())?;
+        // A token can never be granted more than the credentials used to create it.
+        if !granted_scopes.contains(&scope) {
+            return Err(CreateApiTokenError::ScopeNotGranted(
+                scope.as_ref().to_string(),
+            ));
+        }
+        if !scopes.contains(&scope) {
+            scopes.push(scope);
+        }
+    }
+    let expires_at = match cmd.expires_in_days {
+        None => None,
+        Some(days) if days == 0 || days > MAX_EXPIRES_IN_DAYS => {
+            return Err(CreateApiTokenError::InvalidExpiration(format!(
+                "expiration must be between 1 and {} days",
+                MAX_EXPIRES_IN_DAYS
+            )));
+        }
+        Some(days) => Some(DateTime::from(
+            chrono::Utc::now() + chrono::Duration::days(i64::from(days)),
+        )),
+    };
+
+    let token = generate_api_token();
+    let api_token = ApiToken::new(user_id, name, hash_api_token(&token), scopes, expires_at);
+    token_repo.create(&api_token).await.map_err(storage_error(
+        CreateApiTokenError::Unavailable,
+        CreateApiTokenError::Unexpected,
+    ))?;
+
+    Ok((api_token, SecretString::from(token)))
+}
+
+#[derive(serde::Deserialize, Debug)]
+pub(crate) struct CreateApiTokenCommand {
+    name: String,
+    scopes: Vec<String>,
+    expires_in_days: Option<u32>,
+}
+
+impl CreateApiTokenCommand {
+    pub(crate) fn new(name: String, scopes: Vec<String>, expires_in_days: Option<u32>) -> Self {
+        Self {
+            name,
+            scopes,
+            expires_in_days,
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum CreateApiTokenError {
+    #[error("invalid name: {0}")]
+    InvalidName(#[from] ApiTokenNameError),
+    #[error("invalid scope: {0}")]
+    InvalidScope(#[from] ScopeError),
+    #[error("scope is not granted to the current credentials: {0}")]
+    ScopeNotGranted(String),
+    #[error("invalid expiration: {0}")]
+    InvalidExpiration(String),
+    #[error("storage is temporarily unavailable")]
+    Unavailable,
+    #[error("unexpected error: {0}")]
+    Unexpected(String),
+}
@@ -0,0 +1,23 @@
+use crate::application::contracts::{ApiTokenRepository, storage_error};
+use crate::domain::entities::ApiToken;
+use crate::domain::value_objects::Identification;
+use std::sync::Arc;
+
+#[tracing::instrument(name = "Handle list api tokens query", skip(token_repo))]
+pub(crate) async fn list_api_tokens_handler(
+    user_id: Identification,
+    token_repo: &Arc<dyn ApiTokenRepository>,
+) -> Result<Vec<ApiToken>, ListApiTokensError> {
+    token_repo.list(&user_id).await.map_err(storage_error(
+        ListApiTokensError::Unavailable,
+        ListApiTokensError::Unexpected,
+    ))
+}
+
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum ListApiTokensError {
+    #[error("storage is temporarily unavailable")]
+    Unavailable,
+    #[error("unexpected error: {0}")]
+    Unexpected(String),
+}
@@ -0,0 +1,55 @@
+use crate::application::contracts::{UnitOfWork, storage_error};
+use crate::domain::value_objects::Identification;
+use std::sync::Arc;
+
+#[tracing::instrument(name = "Handle revoke api token command", skip(unit_of_work))]
+pub(crate) async fn revoke_api_token_handler(
+    token_id: Identification,
+    user_id: Identification,
+    unit_of_work: &Arc<dyn UnitOfWork>,
+) -> Result<(), RevokeApiTokenError> {
+    let mut transaction = unit_of_work.begin().await.map_err(storage_error(
+        RevokeApiTokenError::Unavailable,
+        RevokeApiTokenError::Unexpected,
+    ))?;
+    let mut token = transaction
+        .api_tokens()
+        .get_api_token_for_update(&token_id)
+        .await
+        .map_err(storage_error(
+            RevokeApiTokenError::Unavailable,
+            RevokeApiTokenError::Unexpected,
+        ))?
+        .ok_or(RevokeApiTokenError::NotFound)?;
+
+    // Tokens of other users are reported as missing so that their ids are not disclosed.
+    if *token.user_id() != user_id {
+        return Err(RevokeApiTokenError::NotFound);
+    }
+
+    token.revoke();
+    transaction
+        .api_tokens()
+        .update_api_token(&token)
+        .await
+        .map_err(storage_error(
+            RevokeApiTokenError::Unavailable,
+            RevokeApiTokenError::Unexpected,
+        ))?;
+    transaction.commit().await.map_err(storage_error(
+        RevokeApiTokenError::Unavailable,
+        RevokeApiTokenError::Unexpected,
+    ))?;
+
+    Ok(())
+}
+
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum RevokeApiTokenError {
+    #[error("token not found")]
+    NotFound,
+    #[error("storage is temporarily unavailable")]
+    Unavailable,
+    #[error("unexpected error: {0}")]
+    Unexpected(String),
+}
@@ -0,0 +1,41 @@
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use sha2::{Digest, Sha256};
+
+const API_TOKEN_PREFIX: &str = "blog_pat_";
+const API_TOKEN_BYTES: usize = 32;
+
+/// Returns `true` if the bearer credential looks like a personal access token rather than a JWT.
+pub(crate) fn is_api_token(token: &str) -> bool {
+    token.starts_with(API_TOKEN_PREFIX)
+}
+
+pub(super) fn generate_api_token() -> String {
+    let mut bytes = [0u8; API_TOKEN_BYTES];
+    OsRng.fill_bytes(&mut bytes);
+    let secret = bytes
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect::<String>();
+    format!("{}{}", API_TOKEN_PREFIX, secret)
+}
+
+pub(super) fn hash_api_token(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generate_unique_prefixed_tokens() {
+        let first = generate_api_token();
+        let second = generate_api_token();
+
+        assert!(is_api_token(&first));
+        assert_eq!(first.len(), API_TOKEN_PREFIX.len() + API_TOKEN_BYTES * 2);
+        assert_ne!(first, second);
+        assert_ne!(hash_api_token(&first), hash_api_token(&second));
+        assert_eq!(hash_api_token(&first), hash_api_token(&first));
+    }
+}
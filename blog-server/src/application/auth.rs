@@ -1,6 +1,15 @@
+mod confirm_two_factor_handler;
+mod enroll_two_factor_handler;
 mod password;
 mod register_user_handler;
+mod sign_in_with_oidc_handler;
+mod totp;
+mod verify_two_factor_handler;
 mod verify_user_handler;
 
+pub(crate) use confirm_two_factor_handler::*;
+pub(crate) use enroll_two_factor_handler::*;
 pub(crate) use register_user_handler::*;
+pub(crate) use sign_in_with_oidc_handler::*;
+pub(crate) use verify_two_factor_handler::*;
 pub(crate) use verify_user_handler::*;
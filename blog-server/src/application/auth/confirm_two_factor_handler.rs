@@ -0,0 +1,83 @@
+use crate::application::auth::totp::verify_totp_code;
+use crate::application::contracts::{UnitOfWork, storage_error};
+use crate::domain::value_objects::Identification;
+use secrecy::{ExposeSecret, SecretString};
+use std::sync::Arc;
+
+#[tracing::instrument(name = "Handle confirm two-factor command", skip(unit_of_work))]
+pub(crate) async fn confirm_two_factor_handler(
+    user_id: Identification,
+    cmd: ConfirmTwoFactorCommand,
+    unit_of_work: &Arc<dyn UnitOfWork>,
+) -> Result<(), ConfirmTwoFactorError> {
+    let mut transaction = unit_of_work.begin().await.map_err(storage_error(
+        ConfirmTwoFactorError::Unavailable,
+        ConfirmTwoFactorError::Unexpected,
+    ))?;
+    let mut user = transaction
+        .users()
+        .get_user_for_update(&user_id)
+        .await
+        .map_err(storage_error(
+            ConfirmTwoFactorError::Unavailable,
+            ConfirmTwoFactorError::Unexpected,
+        ))?
+        .ok_or(ConfirmTwoFactorError::UserNotFound)?;
+
+    if user.is_totp_enabled() {
+        return Err(ConfirmTwoFactorError::AlreadyEnabled);
+    }
+
+    let secret = user
+        .totp_secret()
+        .ok_or(ConfirmTwoFactorError::NotEnrolled)?;
+    let step = verify_totp_code(secret, cmd.code.expose_secret())
+        .map_err(|err| ConfirmTwoFactorError::Unexpected(err.to_string()))?
+        .ok_or(ConfirmTwoFactorError::InvalidCode)?;
+
+    // The confirmation code must not also complete the next login.
+    user.accept_totp_step(step);
+    user.enable_totp();
+    transaction
+        .two_factor()
+        .update_two_factor(&user)
+        .await
+        .map_err(storage_error(
+            ConfirmTwoFactorError::Unavailable,
+            ConfirmTwoFactorError::Unexpected,
+        ))?;
+    transaction.commit().await.map_err(storage_error(
+        ConfirmTwoFactorError::Unavailable,
+        ConfirmTwoFactorError::Unexpected,
+    ))?;
+
+    Ok(())
+}
+
+/// The code is a secret, so `Debug` redacts it.
+#[derive(serde::Deserialize, Debug)]
+pub(crate) struct ConfirmTwoFactorCommand {
+    code: SecretString,
+}
+
+impl ConfirmTwoFactorCommand {
+    pub(crate) fn new(code: SecretString) -> Self {
+        Self { code }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum ConfirmTwoFactorError {
+    #[error("user not found")]
+    UserNotFound,
+    #[error("two-factor authentication is not enrolled")]
+    NotEnrolled,
+    #[error("two-factor authentication is already enabled")]
+    AlreadyEnabled,
+    #[error("invalid two-factor code")]
+    InvalidCode,
+    #[error("storage is temporarily unavailable")]
+    Unavailable,
+    #[error("unexpected error: {0}")]
+    Unexpected(String),
+}
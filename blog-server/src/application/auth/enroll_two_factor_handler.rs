@@ -0,0 +1,95 @@
+use crate::application::auth::totp::{
+    create_otpauth_uri, generate_recovery_codes, generate_totp_secret, hash_recovery_code,
+};
+use crate::application::contracts::{UnitOfWork, storage_error};
+use crate::domain::value_objects::Identification;
+use std::sync::Arc;
+
+#[tracing::instrument(name = "Handle enroll two-factor command", skip(unit_of_work))]
+pub(crate) async fn enroll_two_factor_handler(
+    user_id: Identification,
+    unit_of_work: &Arc<dyn UnitOfWork>,
+) -> Result<TwoFactorEnrollment, EnrollTwoFactorError> {
+    let mut transaction = unit_of_work.begin().await.map_err(storage_error(
+        EnrollTwoFactorError::Unavailable,
+        EnrollTwoFactorError::Unexpected,
+    ))?;
+    let mut user = transaction
+        .users()
+        .get_user_for_update(&user_id)
+        .await
+        .map_err(storage_error(
+            EnrollTwoFactorError::Unavailable,
+            EnrollTwoFactorError::Unexpected,
+        ))?
+        .ok_or(EnrollTwoFactorError::UserNotFound)?;
+
+    if user.is_totp_enabled() {
+        return Err(EnrollTwoFactorError::AlreadyEnabled);
+    }
+
+    let secret = generate_totp_secret();
+    let otpauth_uri = create_otpauth_uri(&secret, user.username())
+        .map_err(|err| EnrollTwoFactorError::Unexpected(err.to_string()))?;
+    let recovery_codes = generate_recovery_codes();
+    let code_hashes = recovery_codes
+        .iter()
+        .map(|code| hash_recovery_code(code))
+        .collect::<anyhow::Result<Vec<String>>>()
+        .map_err(|err| EnrollTwoFactorError::Unexpected(err.to_string()))?;
+
+    user.enroll_totp(secret);
+    transaction
+        .two_factor()
+        .update_two_factor(&user)
+        .await
+        .map_err(storage_error(
+            EnrollTwoFactorError::Unavailable,
+            EnrollTwoFactorError::Unexpected,
+        ))?;
+    transaction
+        .two_factor()
+        .replace_recovery_codes(user.id(), &code_hashes)
+        .await
+        .map_err(storage_error(
+            EnrollTwoFactorError::Unavailable,
+            EnrollTwoFactorError::Unexpected,
+        ))?;
+    transaction.commit().await.map_err(storage_error(
+        EnrollTwoFactorError::Unavailable,
+        EnrollTwoFactorError::Unexpected,
+    ))?;
+
+    Ok(TwoFactorEnrollment {
+        otpauth_uri,
+        recovery_codes,
+    })
+}
+
+#[derive(Debug)]
+pub(crate) struct TwoFactorEnrollment {
+    otpauth_uri: String,
+    recovery_codes: Vec<String>,
+}
+
+impl TwoFactorEnrollment {
+    pub(crate) fn otpauth_uri(&self) -> &str {
+        &self.otpauth_uri
+    }
+
+    pub(crate) fn recovery_codes(&self) -> &[String] {
+        &self.recovery_codes
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum EnrollTwoFactorError {
+    #[error("user not found")]
+    UserNotFound,
+    #[error("two-factor authentication is already enabled")]
+    AlreadyEnabled,
+    #[error("storage is temporarily unavailable")]
+    Unavailable,
+    #[error("unexpected error: {0}")]
+    Unexpected(String),
+}
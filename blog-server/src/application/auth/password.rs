@@ -1,5 +1,6 @@
 use crate::domain::value_objects::{Password, PasswordHash};
 use argon2::password_hash::SaltString;
+use argon2::password_hash::rand_core::{OsRng, RngCore};
 use argon2::{Algorithm, Argon2, Params, PasswordHasher, PasswordVerifier, Version};
 use secrecy::SecretString;
 
@@ -15,6 +16,18 @@ pub(super) fn calculate_password_hash(
     Ok(SecretString::from(password_hash).into())
 }
 
+/// Hashes random bytes, leaving accounts created through single sign-on without a usable password.
+pub(super) fn calculate_random_password_hash(salt: &SaltString) -> anyhow::Result<PasswordHash> {
+    let mut password = [0u8; 32];
+    OsRng.fill_bytes(&mut password);
+    let argon2 = create_argon2()?;
+    let password_hash = argon2
+        .hash_password(&password, salt)
+        .map_err(|e| anyhow::anyhow!(e.to_string()).context("failed hash password"))?
+        .to_string();
+    Ok(SecretString::from(password_hash).into())
+}
+
 pub(super) fn verify_password(hash: &PasswordHash, password: &Password) -> anyhow::Result<bool> {
     let argon2 = create_argon2()?;
     let hash: argon2::password_hash::PasswordHash<'_> = hash.try_into()?;
@@ -26,7 +39,7 @@ pub(super) fn verify_password(hash: &PasswordHash, password: &Password) -> anyho
     }
 }
 
-fn create_argon2() -> anyhow::Result<Argon2<'static>> {
+pub(super) fn create_argon2() -> anyhow::Result<Argon2<'static>> {
     let argon2 = Argon2::new(
         Algorithm::Argon2id,
         Version::V0x13,
@@ -40,7 +53,6 @@ fn create_argon2() -> anyhow::Result<Argon2<'static>> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use argon2::password_hash::rand_core::OsRng;
 
     #[test]
     fn test_verify_password() {
@@ -1,8 +1,8 @@
 use crate::application::auth::password::calculate_password_hash;
-use crate::application::contracts::UserRepository;
+use crate::application::contracts::{UniqueViolation, UnitOfWork, storage_error};
 use crate::domain::entities::User;
 use crate::domain::value_objects::{
-    Email, EmailError, Password, PasswordError, UserName, UserNameError,
+    Email, EmailError, FieldViolation, Password, UserName, describe_violations,
 };
 use argon2::password_hash::SaltString;
 use argon2::password_hash::rand_core::OsRng;
@@ -10,38 +10,70 @@ use secrecy::SecretString;
 use std::sync::Arc;
 use tokio::task::JoinError;
 
-#[tracing::instrument(name = "Handle register user command", skip(users_repo))]
+#[tracing::instrument(name = "Handle register user command", skip(unit_of_work))]
 pub(crate) async fn register_user_handler(
     cmd: RegisterUserCommand,
-    users_repo: &Arc<dyn UserRepository>,
+    unit_of_work: &Arc<dyn UnitOfWork>,
 ) -> Result<User, RegisterUserError> {
-    let user_name = UserName::try_from(cmd.username)?;
-    let email = Email::try_from(cmd.email)?;
-    let password = Password::try_from(cmd.password)?;
+    // Every field is validated, so that the client learns about all invalid fields at once.
+    let mut violations = Vec::new();
+    let user_name =
+        UserName::try_from(cmd.username).map_err(|err| violations.push(FieldViolation::from(&err)));
+    let email = match Email::try_from(cmd.email) {
+        Err(EmailError::Regex(err)) => return Err(RegisterUserError::Unexpected(err.to_string())),
+        email => email.map_err(|err| violations.push(FieldViolation::from(&err))),
+    };
+    let password =
+        Password::try_from(cmd.password).map_err(|err| violations.push(FieldViolation::from(&err)));
+    let (Ok(user_name), Ok(email), Ok(password)) = (user_name, email, password) else {
+        return Err(RegisterUserError::InvalidUser(violations));
+    };
     let salt = SaltString::generate(&mut OsRng);
     let password_hash =
         tokio::task::spawn_blocking(move || calculate_password_hash(&password, &salt))
             .await?
             .map_err(|err| RegisterUserError::Unexpected(err.to_string()))?;
 
-    let is_username_or_email_exists = users_repo
-        .exist(&user_name, &email)
+    let mut transaction = unit_of_work.begin().await.map_err(storage_error(
+        RegisterUserError::Unavailable,
+        RegisterUserError::Unexpected,
+    ))?;
+    let is_username_or_email_exists = transaction
+        .users()
+        .user_exists(&user_name, &email)
         .await
-        .map_err(|err| RegisterUserError::Unexpected(err.to_string()))?;
+        .map_err(storage_error(
+            RegisterUserError::Unavailable,
+            RegisterUserError::Unexpected,
+        ))?;
 
     if is_username_or_email_exists {
         return Err(RegisterUserError::UsernameOrEmailExist);
     }
 
+    // A concurrent registration can still take the username or email before this one commits.
     let user = User::new(user_name, email, password_hash);
-    users_repo
-        .create(&user)
+    transaction
+        .users()
+        .create_user(&user)
         .await
-        .map_err(|err| RegisterUserError::Unexpected(err.to_string()))?;
+        .map_err(register_error)?;
+    transaction.commit().await.map_err(register_error)?;
 
     Ok(user)
 }
 
+fn register_error(err: anyhow::Error) -> RegisterUserError {
+    if err.is::<UniqueViolation>() {
+        RegisterUserError::UsernameOrEmailExist
+    } else {
+        storage_error(
+            RegisterUserError::Unavailable,
+            RegisterUserError::Unexpected,
+        )(err)
+    }
+}
+
 #[derive(serde::Deserialize, Debug)]
 pub(crate) struct RegisterUserCommand {
     username: String,
@@ -61,37 +93,49 @@ impl RegisterUserCommand {
 
 #[derive(Debug, thiserror::Error)]
 pub(crate) enum RegisterUserError {
-    #[error("invalid user: {0}")]
-    InvalidUser(String),
+    #[error("invalid user: {}", describe_violations(.0))]
+    InvalidUser(Vec<FieldViolation>),
     #[error("username or email already exists")]
     UsernameOrEmailExist,
+    #[error("storage is temporarily unavailable")]
+    Unavailable,
     #[error("unexpected error: {0}")]
     Unexpected(String),
 }
 
-impl From<UserNameError> for RegisterUserError {
-    fn from(err: UserNameError) -> Self {
-        RegisterUserError::InvalidUser(err.to_string())
+impl From<JoinError> for RegisterUserError {
+    fn from(value: JoinError) -> Self {
+        RegisterUserError::Unexpected(value.to_string())
     }
 }
 
-impl From<EmailError> for RegisterUserError {
-    fn from(err: EmailError) -> Self {
-        match err {
-            EmailError::Regex(err) => RegisterUserError::Unexpected(err.to_string()),
-            _ => RegisterUserError::InvalidUser(err.to_string()),
-        }
-    }
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::{DatabasePools, PostgresUnitOfWork, TotpSecretCipher};
+    use sqlx::postgres::PgPoolOptions;
 
-impl From<PasswordError> for RegisterUserError {
-    fn from(value: PasswordError) -> Self {
-        RegisterUserError::InvalidUser(value.to_string())
-    }
-}
+    #[tokio::test]
+    async fn reports_every_invalid_field() {
+        let pg_pool = PgPoolOptions::new()
+            .connect_lazy("postgres://localhost/blog")
+            .unwrap();
+        let unit_of_work: Arc<dyn UnitOfWork> = Arc::new(PostgresUnitOfWork::new(
+            Arc::new(DatabasePools::new(Arc::new(pg_pool), Vec::new())),
+            Arc::new(TotpSecretCipher::new(&SecretString::from("key")).unwrap()),
+        ));
+        let cmd = RegisterUserCommand::new(
+            "jo".to_string(),
+            SecretString::from("x"),
+            "not an email".to_string(),
+        );
 
-impl From<JoinError> for RegisterUserError {
-    fn from(value: JoinError) -> Self {
-        RegisterUserError::Unexpected(value.to_string())
+        let result = register_user_handler(cmd, &unit_of_work).await;
+
+        let Err(RegisterUserError::InvalidUser(violations)) = result else {
+            panic!("expected invalid user, got {:?}", result);
+        };
+        let fields: Vec<_> = violations.iter().map(FieldViolation::field).collect();
+        assert_eq!(fields, ["username", "email", "password"]);
     }
 }
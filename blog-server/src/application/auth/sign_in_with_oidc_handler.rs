@@ -0,0 +1,240 @@
+use crate::application::auth::password::calculate_random_password_hash;
+use crate::application::contracts::{
+    IdentityClaims, IdentityProvider, IdentityProviderError, Transaction, UniqueViolation,
+    UnitOfWork, storage_error,
+};
+use crate::domain::entities::User;
+use crate::domain::value_objects::{Email, USERNAME_MAX_LENGTH, USERNAME_MIN_LENGTH, UserName};
+use argon2::password_hash::SaltString;
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use std::sync::Arc;
+use tokio::task::JoinError;
+
+const USERNAME_ATTEMPTS: usize = 5;
+
+#[tracing::instrument(
+    name = "Handle sign in with OIDC command",
+    skip(identity_provider, unit_of_work)
+)]
+pub(crate) async fn sign_in_with_oidc_handler(
+    cmd: SignInWithOidcCommand,
+    identity_provider: &Arc<dyn IdentityProvider>,
+    unit_of_work: &Arc<dyn UnitOfWork>,
+) -> Result<OidcSignIn, SignInWithOidcError> {
+    let claims = identity_provider
+        .exchange_code(&cmd.code, &cmd.state)
+        .await?;
+
+    // Concurrent first sign-ins with the same identity wait here, and all but the first find
+    // the account the first one created.
+    let mut transaction = unit_of_work.begin().await.map_err(sign_in_error)?;
+    transaction
+        .external_identities()
+        .lock_external_identity(&claims.identity)
+        .await
+        .map_err(sign_in_error)?;
+    let linked_user = transaction
+        .external_identities()
+        .get_user_by_external_identity(&claims.identity)
+        .await
+        .map_err(sign_in_error)?;
+    if let Some(user_id) = &claims.link_user_id {
+        return match linked_user {
+            Some(user) if user.id() == user_id => Ok(OidcSignIn::Linked),
+            Some(_) => Err(SignInWithOidcError::IdentityInUse),
+            None => {
+                transaction
+                    .external_identities()
+                    .link_external_identity(user_id, &claims.identity)
+                    .await
+                    .map_err(sign_in_error)?;
+                transaction.commit().await.map_err(sign_in_error)?;
+                tracing::info!("External identity linked to the signed-in user");
+                Ok(OidcSignIn::Linked)
+            }
+        };
+    }
+    if let Some(user) = linked_user {
+        return Ok(OidcSignIn::SignedIn(user));
+    }
+
+    let email = claims
+        .email
+        .clone()
+        .ok_or(SignInWithOidcError::MissingEmail)
+        .and_then(|email| {
+            Email::try_from(email).map_err(|err| SignInWithOidcError::InvalidEmail(err.to_string()))
+        })?;
+
+    // Signing in with the provider must not bypass the password and second factor of an
+    // existing account, so its owner has to link the identity from a logged-in session.
+    if transaction
+        .users()
+        .email_exists(&email)
+        .await
+        .map_err(sign_in_error)?
+    {
+        return Err(SignInWithOidcError::AccountExists);
+    }
+    // The new account claims the email, so the provider has to vouch for it.
+    if !claims.email_verified {
+        return Err(SignInWithOidcError::EmailNotVerified);
+    }
+
+    let username = find_free_username(&claims, &email, transaction.as_mut()).await?;
+    let salt = SaltString::generate(&mut OsRng);
+    let password_hash = tokio::task::spawn_blocking(move || calculate_random_password_hash(&salt))
+        .await?
+        .map_err(|err| SignInWithOidcError::Unexpected(err.to_string()))?;
+    let user = User::new(username, email, password_hash);
+    transaction
+        .users()
+        .create_user(&user)
+        .await
+        .map_err(sign_in_error)?;
+    transaction
+        .external_identities()
+        .link_external_identity(user.id(), &claims.identity)
+        .await
+        .map_err(sign_in_error)?;
+    transaction.commit().await.map_err(sign_in_error)?;
+    tracing::info!("User created from external identity");
+
+    Ok(OidcSignIn::SignedIn(user))
+}
+
+/// The identity is locked, so a unique violation means that a concurrent registration took the
+/// email or username, or that the identity got linked to another account.
+fn sign_in_error(err: anyhow::Error) -> SignInWithOidcError {
+    if err.is::<UniqueViolation>() {
+        SignInWithOidcError::Conflict
+    } else {
+        storage_error(
+            SignInWithOidcError::Unavailable,
+            SignInWithOidcError::Unexpected,
+        )(err)
+    }
+}
+
+async fn find_free_username(
+    claims: &IdentityClaims,
+    email: &Email,
+    transaction: &mut dyn Transaction,
+) -> Result<UserName, SignInWithOidcError> {
+    let base = username_base(claims, email);
+    for attempt in 0..USERNAME_ATTEMPTS {
+        let candidate = if attempt == 0 && base.len() >= USERNAME_MIN_LENGTH {
+            base.clone()
+        } else {
+            let suffix = format!("_{:04x}", OsRng.next_u32() & 0xffff);
+            let mut candidate = base.clone();
+            candidate.truncate(USERNAME_MAX_LENGTH - suffix.len());
+            candidate + &suffix
+        };
+        let username = UserName::try_from(candidate)
+            .map_err(|err| SignInWithOidcError::Unexpected(err.to_string()))?;
+        // No account has the email, so this only tells whether the username is taken.
+        let is_taken = transaction
+            .users()
+            .user_exists(&username, email)
+            .await
+            .map_err(sign_in_error)?;
+        if !is_taken {
+            return Ok(username);
+        }
+    }
+
+    Err(SignInWithOidcError::Unexpected(
+        "failed to pick a free username".to_string(),
+    ))
+}
+
+fn username_base(claims: &IdentityClaims, email: &Email) -> String {
+    let source = claims
+        .preferred_username
+        .as_deref()
+        .unwrap_or_else(|| email.as_ref().split('@').next().unwrap_or_default());
+    let mut base = source
+        .chars()
+        .filter(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '_' | '-' | '.'))
+        .collect::<String>();
+    base.truncate(USERNAME_MAX_LENGTH);
+    if base.is_empty() {
+        base.push_str("user");
+    }
+    base
+}
+
+#[derive(Debug)]
+pub(crate) struct SignInWithOidcCommand {
+    code: String,
+    state: String,
+}
+
+impl SignInWithOidcCommand {
+    pub(crate) fn new(code: String, state: String) -> Self {
+        Self { code, state }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) enum OidcSignIn {
+    SignedIn(User),
+    /// The identity was linked to the user that started the sign-in.
+    Linked,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum SignInWithOidcError {
+    #[error("{0}")]
+    Provider(#[from] IdentityProviderError),
+    #[error("identity provider did not return an email")]
+    MissingEmail,
+    #[error("invalid email: {0}")]
+    InvalidEmail(String),
+    #[error("email is not verified by the identity provider")]
+    EmailNotVerified,
+    #[error("an account with this email already exists")]
+    AccountExists,
+    #[error("identity is linked to another account")]
+    IdentityInUse,
+    #[error("account details were taken by a concurrent sign up")]
+    Conflict,
+    #[error("storage is temporarily unavailable")]
+    Unavailable,
+    #[error("unexpected error: {0}")]
+    Unexpected(String),
+}
+
+impl From<JoinError> for SignInWithOidcError {
+    fn from(value: JoinError) -> Self {
+        SignInWithOidcError::Unexpected(value.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::value_objects::ExternalIdentity;
+
+    fn claims(preferred_username: Option<&str>) -> IdentityClaims {
+        IdentityClaims {
+            identity: ExternalIdentity::new("https://idp".to_string(), "42".to_string()),
+            email: Some("jane.doe@example.com".to_string()),
+            email_verified: true,
+            preferred_username: preferred_username.map(str::to_string),
+            link_user_id: None,
+        }
+    }
+
+    #[test]
+    fn username_base_prefers_preferred_username() {
+        let email = Email::try_from("jane.doe@example.com".to_string()).unwrap();
+        assert_eq!(username_base(&claims(Some("jane doe!")), &email), "janedoe");
+        assert_eq!(username_base(&claims(None), &email), "jane.doe");
+        assert_eq!(
+            username_base(&claims(Some("a_very_long_preferred_username")), &email),
+            "a_very_long_preferre"
+        );
+    }
+}
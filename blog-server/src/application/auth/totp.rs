@@ -0,0 +1,140 @@
+use crate::application::auth::password::create_argon2;
+use crate::domain::value_objects::{TotpSecret, UserName};
+use argon2::password_hash::SaltString;
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use argon2::{PasswordHasher, PasswordVerifier};
+use secrecy::{ExposeSecret, SecretString};
+use std::time::{SystemTime, UNIX_EPOCH};
+use totp_rs::{Algorithm, Secret, TOTP};
+
+const TOTP_ISSUER: &str = "Blog";
+const TOTP_DIGITS: usize = 6;
+const TOTP_SKEW: u64 = 1;
+const TOTP_STEP: u64 = 30;
+const RECOVERY_CODES_COUNT: usize = 10;
+
+pub(super) fn generate_totp_secret() -> TotpSecret {
+    let secret = Secret::generate_secret().to_encoded().to_string();
+    TotpSecret::from(SecretString::from(secret))
+}
+
+pub(super) fn create_otpauth_uri(
+    secret: &TotpSecret,
+    username: &UserName,
+) -> anyhow::Result<String> {
+    Ok(create_totp(secret, username.as_ref().to_string())?.get_url())
+}
+
+/// Returns the time step of the matching code, which callers store to refuse the code the next
+/// time it is presented.
+pub(super) fn verify_totp_code(secret: &TotpSecret, code: &str) -> anyhow::Result<Option<u64>> {
+    let mut totp = create_totp(secret, String::new())?;
+    totp.skew = 0;
+    let current_step = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() / TOTP_STEP;
+    let code = code.trim();
+    Ok(
+        (current_step.saturating_sub(TOTP_SKEW)..=current_step + TOTP_SKEW)
+            .find(|step| totp.check(code, step * TOTP_STEP)),
+    )
+}
+
+pub(super) fn generate_recovery_codes() -> Vec<String> {
+    (0..RECOVERY_CODES_COUNT)
+        .map(|_| {
+            let value = OsRng.next_u64();
+            format!("{:05x}-{:05x}", (value >> 20) & 0xfffff, value & 0xfffff)
+        })
+        .collect()
+}
+
+pub(super) fn hash_recovery_code(code: &str) -> anyhow::Result<String> {
+    let salt = SaltString::generate(&mut OsRng);
+    let hash = create_argon2()?
+        .hash_password(normalize_recovery_code(code).as_bytes(), &salt)
+        .map_err(|e| anyhow::anyhow!(e.to_string()).context("failed to hash recovery code"))?
+        .to_string();
+    Ok(hash)
+}
+
+pub(super) fn verify_recovery_code(code_hash: &str, code: &str) -> anyhow::Result<bool> {
+    let code = normalize_recovery_code(code);
+    let hash = argon2::password_hash::PasswordHash::new(code_hash).map_err(|e| {
+        anyhow::anyhow!(e.to_string()).context("failed to parse recovery code hash")
+    })?;
+    match create_argon2()?.verify_password(code.as_bytes(), &hash) {
+        Ok(()) => Ok(true),
+        Err(argon2::password_hash::Error::Password) => Ok(false),
+        Err(e) => Err(anyhow::anyhow!(e.to_string()).context("failed to verify recovery code")),
+    }
+}
+
+fn normalize_recovery_code(code: &str) -> String {
+    code.trim().to_lowercase()
+}
+
+fn create_totp(secret: &TotpSecret, account_name: String) -> anyhow::Result<TOTP> {
+    let secret = Secret::Encoded(secret.as_ref().expose_secret().to_string())
+        .to_bytes()
+        .map_err(|e| anyhow::anyhow!(e.to_string()).context("failed to decode totp secret"))?;
+    TOTP::new(
+        Algorithm::SHA1,
+        TOTP_DIGITS,
+        TOTP_SKEW as u8,
+        TOTP_STEP,
+        secret,
+        Some(TOTP_ISSUER.to_string()),
+        account_name,
+    )
+    .map_err(|e| anyhow::anyhow!(e.to_string()).context("failed to create totp"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify_totp_code() {
+        let secret = generate_totp_secret();
+        let code = create_totp(&secret, String::new())
+            .unwrap()
+            .generate_current()
+            .unwrap();
+
+        let current_step = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+            / TOTP_STEP;
+
+        let step = verify_totp_code(&secret, &code).unwrap().unwrap();
+        assert!(step.abs_diff(current_step) <= TOTP_SKEW);
+        assert_eq!(verify_totp_code(&secret, "not-a-code").unwrap(), None);
+    }
+
+    #[test]
+    fn test_create_otpauth_uri() {
+        let secret = generate_totp_secret();
+        let username = UserName::try_from("username".to_string()).unwrap();
+        let uri = create_otpauth_uri(&secret, &username).unwrap();
+
+        assert!(uri.starts_with("otpauth://totp/Blog:username?"));
+        assert!(uri.contains(secret.as_ref().expose_secret()));
+    }
+
+    #[test]
+    fn test_hash_recovery_code() {
+        let codes = generate_recovery_codes();
+        assert_eq!(codes.len(), RECOVERY_CODES_COUNT);
+        let hash = hash_recovery_code(&codes[0]).unwrap();
+
+        assert!(hash.starts_with("$argon2id$"));
+        assert_ne!(hash, hash_recovery_code(&codes[0]).unwrap());
+        assert!(verify_recovery_code(&hash, &format!(" {} ", codes[0].to_uppercase())).unwrap());
+        assert!(!verify_recovery_code(&hash, &codes[1]).unwrap());
+    }
+
+    #[test]
+    fn test_verify_recovery_code_rejects_other_hashes() {
+        assert!(verify_recovery_code("not an argon2 hash", "01234-abcde").is_err());
+    }
+}
@@ -0,0 +1,140 @@
+use crate::application::auth::totp::{verify_recovery_code, verify_totp_code};
+use crate::application::contracts::{UnitOfWork, storage_error};
+use crate::domain::entities::User;
+use crate::domain::value_objects::Identification;
+use secrecy::{ExposeSecret, SecretString};
+use std::sync::Arc;
+
+/// Runs in a transaction that locks the user, so that concurrent attempts cannot both accept
+/// the same TOTP code or recovery code.
+#[tracing::instrument(name = "Handle verify two-factor command", skip(unit_of_work))]
+pub(crate) async fn verify_two_factor_handler(
+    user_id: Identification,
+    cmd: VerifyTwoFactorCommand,
+    unit_of_work: &Arc<dyn UnitOfWork>,
+) -> Result<User, VerifyTwoFactorError> {
+    let mut transaction = unit_of_work.begin().await.map_err(storage_error(
+        VerifyTwoFactorError::Unavailable,
+        VerifyTwoFactorError::Unexpected,
+    ))?;
+    let mut user = transaction
+        .users()
+        .get_user_for_update(&user_id)
+        .await
+        .map_err(storage_error(
+            VerifyTwoFactorError::Unavailable,
+            VerifyTwoFactorError::Unexpected,
+        ))?
+        .ok_or(VerifyTwoFactorError::UserNotFound)?;
+
+    let secret = match user.totp_secret() {
+        Some(secret) if user.is_totp_enabled() => secret,
+        _ => return Err(VerifyTwoFactorError::NotEnabled),
+    };
+
+    let step = verify_totp_code(secret, cmd.code.expose_secret())
+        .map_err(|err| VerifyTwoFactorError::Unexpected(err.to_string()))?;
+    if let Some(step) = step {
+        if !user.accept_totp_step(step) {
+            tracing::warn!("TOTP code replayed");
+            return Err(VerifyTwoFactorError::InvalidCode);
+        }
+        transaction
+            .two_factor()
+            .update_two_factor(&user)
+            .await
+            .map_err(storage_error(
+                VerifyTwoFactorError::Unavailable,
+                VerifyTwoFactorError::Unexpected,
+            ))?;
+        transaction.commit().await.map_err(storage_error(
+            VerifyTwoFactorError::Unavailable,
+            VerifyTwoFactorError::Unexpected,
+        ))?;
+        return Ok(user);
+    }
+
+    let recovery_codes = transaction
+        .two_factor()
+        .get_unused_recovery_codes(user.id())
+        .await
+        .map_err(storage_error(
+            VerifyTwoFactorError::Unavailable,
+            VerifyTwoFactorError::Unexpected,
+        ))?;
+    for (code_id, code_hash) in recovery_codes {
+        let is_match = verify_recovery_code(&code_hash, cmd.code.expose_secret())
+            .map_err(|err| VerifyTwoFactorError::Unexpected(err.to_string()))?;
+        if !is_match {
+            continue;
+        }
+        transaction
+            .two_factor()
+            .use_recovery_code(&code_id)
+            .await
+            .map_err(storage_error(
+                VerifyTwoFactorError::Unavailable,
+                VerifyTwoFactorError::Unexpected,
+            ))?;
+        transaction.commit().await.map_err(storage_error(
+            VerifyTwoFactorError::Unavailable,
+            VerifyTwoFactorError::Unexpected,
+        ))?;
+        tracing::info!("Recovery code used");
+        return Ok(user);
+    }
+
+    Err(VerifyTwoFactorError::InvalidCode)
+}
+
+/// The challenge token and the code are secrets, so `Debug` redacts them.
+#[derive(serde::Deserialize, Debug)]
+pub(crate) struct VerifyTwoFactorCommand {
+    challenge_token: SecretString,
+    code: SecretString,
+}
+
+impl VerifyTwoFactorCommand {
+    pub(crate) fn new(challenge_token: SecretString, code: SecretString) -> Self {
+        Self {
+            challenge_token,
+            code,
+        }
+    }
+
+    pub(crate) fn challenge_token(&self) -> &str {
+        self.challenge_token.expose_secret()
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum VerifyTwoFactorError {
+    #[error("user not found")]
+    UserNotFound,
+    #[error("two-factor authentication is not enabled")]
+    NotEnabled,
+    #[error("invalid two-factor code")]
+    InvalidCode,
+    #[error("storage is temporarily unavailable")]
+    Unavailable,
+    #[error("unexpected error: {0}")]
+    Unexpected(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn debug_redacts_the_challenge_token_and_code() {
+        let cmd = VerifyTwoFactorCommand::new(
+            SecretString::from("challenge.token.value"),
+            SecretString::from("123456"),
+        );
+
+        let debug = format!("{:?}", cmd);
+
+        assert!(!debug.contains("challenge.token.value"));
+        assert!(!debug.contains("123456"));
+    }
+}
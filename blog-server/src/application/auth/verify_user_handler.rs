@@ -1,27 +1,33 @@
 use crate::application::auth::password::verify_password;
-use crate::application::contracts::UserRepository;
+use crate::application::contracts::{UserRepository, storage_error};
 use crate::domain::entities::User;
-use crate::domain::value_objects::{Password, PasswordError, UserName, UserNameError};
+use crate::domain::value_objects::{
+    FieldViolation, Password, PasswordError, Scope, ScopeError, UserName, UserNameError,
+};
 use secrecy::SecretString;
 use std::sync::Arc;
 
 pub(crate) async fn verify_user_handler(
     cmd: VerifyUserCommand,
     users_repo: &Arc<dyn UserRepository>,
-) -> Result<User, VerifyUserError> {
+) -> Result<(User, Vec<Scope>), VerifyUserError> {
     let user_name = UserName::try_from(cmd.username)?;
     let password = Password::try_from(cmd.password)?;
+    let scopes = Scope::parse_requested(cmd.scopes.as_deref())?;
     let user = users_repo
         .get(&user_name)
         .await
-        .map_err(|err| VerifyUserError::Unexpected(err.to_string()))?
+        .map_err(storage_error(
+            VerifyUserError::Unavailable,
+            VerifyUserError::Unexpected,
+        ))?
         .ok_or(VerifyUserError::UserNotFound)?;
 
     let is_verified = verify_password(user.password_hash(), &password)
         .map_err(|err| VerifyUserError::Unexpected(err.to_string()))?;
 
     if is_verified {
-        Ok(user)
+        Ok((user, scopes))
     } else {
         Err(VerifyUserError::InvalidUserNameOrPassword(
             "password is incorrect".to_string(),
@@ -33,11 +39,20 @@ pub(crate) async fn verify_user_handler(
 pub(crate) struct VerifyUserCommand {
     username: String,
     password: SecretString,
+    scopes: Option<Vec<String>>,
 }
 
 impl VerifyUserCommand {
-    pub(crate) fn new(username: String, password: SecretString) -> Self {
-        Self { username, password }
+    pub(crate) fn new(
+        username: String,
+        password: SecretString,
+        scopes: Option<Vec<String>>,
+    ) -> Self {
+        Self {
+            username,
+            password,
+            scopes,
+        }
     }
 }
 
@@ -45,20 +60,38 @@ impl VerifyUserCommand {
 pub(crate) enum VerifyUserError {
     #[error("invalid user username or password: {0}")]
     InvalidUserNameOrPassword(String),
+    #[error("invalid user username or password: {0}")]
+    InvalidCredentials(FieldViolation),
     #[error("user not found")]
     UserNotFound,
+    #[error("invalid scope: {0}")]
+    InvalidScope(#[from] ScopeError),
+    #[error("storage is temporarily unavailable")]
+    Unavailable,
     #[error("unexpected error: {0}")]
     Unexpected(String),
 }
 
+impl VerifyUserError {
+    /// Whether the login was rejected because of the supplied username or password.
+    pub(crate) fn is_rejected_login(&self) -> bool {
+        matches!(
+            self,
+            VerifyUserError::InvalidUserNameOrPassword(_)
+                | VerifyUserError::InvalidCredentials(_)
+                | VerifyUserError::UserNotFound
+        )
+    }
+}
+
 impl From<UserNameError> for VerifyUserError {
     fn from(value: UserNameError) -> Self {
-        VerifyUserError::InvalidUserNameOrPassword(value.to_string())
+        VerifyUserError::InvalidCredentials(FieldViolation::from(&value))
     }
 }
 
 impl From<PasswordError> for VerifyUserError {
     fn from(value: PasswordError) -> Self {
-        VerifyUserError::InvalidUserNameOrPassword(value.to_string())
+        VerifyUserError::InvalidCredentials(FieldViolation::from(&value))
     }
 }
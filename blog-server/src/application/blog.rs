@@ -1,9 +1,13 @@
+mod batch_get_posts_handler;
+mod bulk_create_posts_handler;
 mod create_post_handler;
 mod delete_post_handler;
 mod get_post_handler;
 mod get_post_list_handler;
 mod update_post_handler;
 
+pub(crate) use batch_get_posts_handler::*;
+pub(crate) use bulk_create_posts_handler::*;
 pub(crate) use create_post_handler::*;
 pub(crate) use delete_post_handler::*;
 pub(crate) use get_post_handler::*;
@@ -0,0 +1,52 @@
+use crate::application::blog::{GetPostError, MAX_BATCH_SIZE};
+use crate::application::contracts::{PostRepository, storage_error};
+use crate::domain::entities::Post;
+use crate::domain::value_objects::Identification;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Fetches many posts at once and reports the outcome of every id in request order.
+#[tracing::instrument(
+    name = "Handle batch get posts command",
+    skip(post_ids, post_repo),
+    fields(count = post_ids.len())
+)]
+pub(crate) async fn batch_get_posts_handler(
+    post_ids: Vec<Identification>,
+    post_repo: &Arc<dyn PostRepository>,
+) -> Result<Vec<Result<Post, GetPostError>>, BatchGetPostsError> {
+    if post_ids.is_empty() || post_ids.len() > MAX_BATCH_SIZE {
+        return Err(BatchGetPostsError::InvalidBatchSize);
+    }
+
+    let posts = post_repo
+        .get_many(&post_ids)
+        .await
+        .map_err(storage_error(
+            BatchGetPostsError::Unavailable,
+            BatchGetPostsError::Unexpected,
+        ))?
+        .into_iter()
+        .map(|post| (*post.id().as_ref(), post))
+        .collect::<HashMap<_, _>>();
+
+    Ok(post_ids
+        .iter()
+        .map(|id| {
+            posts
+                .get(id.as_ref())
+                .cloned()
+                .ok_or(GetPostError::NotFound)
+        })
+        .collect())
+}
+
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub(crate) enum BatchGetPostsError {
+    #[error("batch must contain from 1 to {MAX_BATCH_SIZE} post ids")]
+    InvalidBatchSize,
+    #[error("storage is temporarily unavailable")]
+    Unavailable,
+    #[error("unexpected error: {0}")]
+    Unexpected(String),
+}
@@ -0,0 +1,149 @@
+use crate::application::blog::CreatePostCommand;
+use crate::application::contracts::{PostEvent, PostEventBus, PostRepository, storage_error};
+use crate::domain::entities::Post;
+use crate::domain::value_objects::{Identification, TitleError};
+use std::sync::Arc;
+
+/// Maximum number of posts accepted by a single batch operation.
+pub(crate) const MAX_BATCH_SIZE: usize = 1000;
+
+/// Creates many posts at once and reports the outcome of every post in request order.
+///
+/// A transactional batch stores either all posts or none: when any post is invalid, the valid
+/// ones are reported as [`BulkCreatePostItemError::Aborted`].
+#[tracing::instrument(
+    name = "Handle bulk create posts command",
+    skip(command, post_repo, post_events),
+    fields(count = command.posts.len(), transactional = command.transactional)
+)]
+pub(crate) async fn bulk_create_posts_handler(
+    user_id: Identification,
+    command: BulkCreatePostsCommand,
+    post_repo: &Arc<dyn PostRepository>,
+    post_events: &Arc<dyn PostEventBus>,
+) -> Result<Vec<Result<Post, BulkCreatePostItemError>>, BulkCreatePostsError> {
+    if command.posts.is_empty() || command.posts.len() > MAX_BATCH_SIZE {
+        return Err(BulkCreatePostsError::InvalidBatchSize);
+    }
+
+    let posts = command
+        .posts
+        .into_iter()
+        .map(|post| post.into_post(user_id.clone()))
+        .collect::<Vec<_>>();
+
+    if command.transactional {
+        let posts = match all_valid(posts) {
+            Ok(posts) => posts,
+            Err(results) => return Ok(results),
+        };
+        post_repo.create_many(&posts).await.map_err(storage_error(
+            BulkCreatePostsError::Unavailable,
+            BulkCreatePostsError::Unexpected,
+        ))?;
+        for post in &posts {
+            post_events.publish(PostEvent::Created(post.clone()));
+        }
+        return Ok(posts.into_iter().map(Ok).collect());
+    }
+
+    let mut results = Vec::with_capacity(posts.len());
+    for post in posts {
+        let result = match post {
+            Ok(post) => match post_repo.create(&post).await {
+                Ok(()) => {
+                    post_events.publish(PostEvent::Created(post.clone()));
+                    Ok(post)
+                }
+                Err(err) => Err(storage_error(
+                    BulkCreatePostItemError::Unavailable,
+                    BulkCreatePostItemError::Unexpected,
+                )(err)),
+            },
+            Err(err) => Err(err.into()),
+        };
+        results.push(result);
+    }
+    Ok(results)
+}
+
+/// Unwraps the posts when all of them are valid, otherwise reports why each one was not created.
+fn all_valid(
+    posts: Vec<Result<Post, TitleError>>,
+) -> Result<Vec<Post>, Vec<Result<Post, BulkCreatePostItemError>>> {
+    if posts.iter().all(Result::is_ok) {
+        return Ok(posts.into_iter().flatten().collect());
+    }
+    Err(posts
+        .into_iter()
+        .map(|post| match post {
+            Ok(_) => Err(BulkCreatePostItemError::Aborted),
+            Err(err) => Err(err.into()),
+        })
+        .collect())
+}
+
+#[derive(serde::Deserialize, Debug)]
+pub(crate) struct BulkCreatePostsCommand {
+    posts: Vec<CreatePostCommand>,
+    #[serde(default)]
+    transactional: bool,
+}
+
+impl BulkCreatePostsCommand {
+    pub(crate) fn new(posts: Vec<CreatePostCommand>, transactional: bool) -> Self {
+        Self {
+            posts,
+            transactional,
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub(crate) enum BulkCreatePostsError {
+    #[error("batch must contain from 1 to {MAX_BATCH_SIZE} posts")]
+    InvalidBatchSize,
+    #[error("storage is temporarily unavailable")]
+    Unavailable,
+    #[error("unexpected error: {0}")]
+    Unexpected(String),
+}
+
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub(crate) enum BulkCreatePostItemError {
+    #[error("invalid title: {0}")]
+    InvalidTitle(#[from] TitleError),
+    #[error("not created because another post in the transaction is invalid")]
+    Aborted,
+    #[error("storage is temporarily unavailable")]
+    Unavailable,
+    #[error("unexpected error: {0}")]
+    Unexpected(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn post(title: &str) -> Result<Post, TitleError> {
+        CreatePostCommand::new(title.to_string(), "content".to_string())
+            .into_post(Identification::new())
+    }
+
+    #[test]
+    fn all_valid_returns_posts_when_every_post_is_valid() {
+        let posts = all_valid(vec![post("first"), post("second")]).unwrap();
+        assert_eq!(posts.len(), 2);
+    }
+
+    #[test]
+    fn all_valid_aborts_valid_posts_when_any_post_is_invalid() {
+        let results = all_valid(vec![post("first"), post(""), post("third")]).unwrap_err();
+        assert_eq!(results[0], Err(BulkCreatePostItemError::Aborted));
+        assert_eq!(
+            results[1],
+            Err(BulkCreatePostItemError::InvalidTitle(TitleError::Empty))
+        );
+        assert_eq!(results[2], Err(BulkCreatePostItemError::Aborted));
+    }
+}
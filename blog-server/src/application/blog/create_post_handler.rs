@@ -1,22 +1,22 @@
-use crate::application::contracts::PostRepository;
+use crate::application::contracts::{PostEvent, PostEventBus, PostRepository, storage_error};
 use crate::domain::entities::Post;
 use crate::domain::value_objects::{Content, Identification, Title, TitleError};
 use html_escape::encode_text;
 use std::sync::Arc;
 
-#[tracing::instrument(name = "Handle create post command", skip(post_repo))]
+#[tracing::instrument(name = "Handle create post command", skip(post_repo, post_events))]
 pub(crate) async fn create_post_handler(
     user_id: Identification,
     command: CreatePostCommand,
     post_repo: &Arc<dyn PostRepository>,
+    post_events: &Arc<dyn PostEventBus>,
 ) -> Result<Post, CreatePostError> {
-    let title = Title::try_from(encode_text(&command.title).to_string())?;
-    let content = Content::from(encode_text(&command.content).to_string());
-    let post = Post::new(title, content, user_id);
-    post_repo
-        .create(&post)
-        .await
-        .map_err(|err| CreatePostError::Unexpected(err.to_string()))?;
+    let post = command.into_post(user_id)?;
+    post_repo.create(&post).await.map_err(storage_error(
+        CreatePostError::Unavailable,
+        CreatePostError::Unexpected,
+    ))?;
+    post_events.publish(PostEvent::Created(post.clone()));
     Ok(post)
 }
 
@@ -30,12 +30,20 @@ impl CreatePostCommand {
     pub(crate) fn new(title: String, content: String) -> Self {
         Self { title, content }
     }
+
+    pub(super) fn into_post(self, author_id: Identification) -> Result<Post, TitleError> {
+        let title = Title::try_from(encode_text(&self.title).to_string())?;
+        let content = Content::from(encode_text(&self.content).to_string());
+        Ok(Post::new(title, content, author_id))
+    }
 }
 
 #[derive(Debug, thiserror::Error, PartialEq)]
 pub(crate) enum CreatePostError {
     #[error("invalid title: {0}")]
     InvalidTitle(#[from] TitleError),
+    #[error("storage is temporarily unavailable")]
+    Unavailable,
     #[error("unexpected error: {0}")]
     Unexpected(String),
 }
@@ -1,27 +1,45 @@
-use crate::application::contracts::PostRepository;
+use crate::application::contracts::{PostEvent, PostEventBus, UnitOfWork, storage_error};
 use crate::domain::value_objects::Identification;
 use std::sync::Arc;
 
-#[tracing::instrument(name = "Handle delete post command", skip(post_repo))]
+#[tracing::instrument(name = "Handle delete post command", skip(unit_of_work, post_events))]
 pub(crate) async fn delete_post_handler(
     post_id: Identification,
     user_id: Identification,
-    post_repo: &Arc<dyn PostRepository>,
+    unit_of_work: &Arc<dyn UnitOfWork>,
+    post_events: &Arc<dyn PostEventBus>,
 ) -> Result<(), DeletePostError> {
-    let post = post_repo
-        .get(&post_id)
+    let mut transaction = unit_of_work.begin().await.map_err(storage_error(
+        DeletePostError::Unavailable,
+        DeletePostError::Unexpected,
+    ))?;
+    let post = transaction
+        .posts()
+        .get_post_for_update(&post_id)
         .await
-        .map_err(|e| DeletePostError::Unexpected(e.to_string()))?
+        .map_err(storage_error(
+            DeletePostError::Unavailable,
+            DeletePostError::Unexpected,
+        ))?
         .ok_or(DeletePostError::NotFound)?;
 
     if *post.author_id() != user_id {
         return Err(DeletePostError::NotAllowed);
     }
 
-    post_repo
-        .delete(&post_id)
+    transaction
+        .posts()
+        .delete_post(&post_id)
         .await
-        .map_err(|e| DeletePostError::Unexpected(e.to_string()))?;
+        .map_err(storage_error(
+            DeletePostError::Unavailable,
+            DeletePostError::Unexpected,
+        ))?;
+    transaction.commit().await.map_err(storage_error(
+        DeletePostError::Unavailable,
+        DeletePostError::Unexpected,
+    ))?;
+    post_events.publish(PostEvent::Deleted(post));
 
     Ok(())
 }
@@ -32,6 +50,8 @@ pub(crate) enum DeletePostError {
     NotFound,
     #[error("not allowed to delete post")]
     NotAllowed,
+    #[error("storage is temporarily unavailable")]
+    Unavailable,
     #[error("unexpected error: {0}")]
     Unexpected(String),
 }
@@ -1,4 +1,4 @@
-use crate::application::contracts::PostRepository;
+use crate::application::contracts::{PostRepository, storage_error};
 use crate::domain::entities::Post;
 use crate::domain::value_objects::Identification;
 use std::sync::Arc;
@@ -11,7 +11,10 @@ pub(crate) async fn get_post_handler(
     let post = post_repo
         .get(&post_id)
         .await
-        .map_err(|e| GetPostError::Unexpected(e.to_string()))?
+        .map_err(storage_error(
+            GetPostError::Unavailable,
+            GetPostError::Unexpected,
+        ))?
         .ok_or(GetPostError::NotFound)?;
     Ok(post)
 }
@@ -20,6 +23,8 @@ pub(crate) async fn get_post_handler(
 pub(crate) enum GetPostError {
     #[error("post not found")]
     NotFound,
+    #[error("storage is temporarily unavailable")]
+    Unavailable,
     #[error("unexpected error: {0}")]
     Unexpected(String),
 }
@@ -1,4 +1,4 @@
-use crate::application::contracts::PostRepository;
+use crate::application::contracts::{PostRepository, storage_error};
 use crate::domain::entities::Post;
 use std::sync::Arc;
 
@@ -8,14 +8,16 @@ pub(crate) async fn get_post_list_handler(
     offset: usize,
     post_repo: &Arc<dyn PostRepository>,
 ) -> Result<(Vec<Post>, usize), GetPostListError> {
-    post_repo
-        .list(limit, offset)
-        .await
-        .map_err(|e| GetPostListError::Unexpected(e.to_string()))
+    post_repo.list(limit, offset).await.map_err(storage_error(
+        GetPostListError::Unavailable,
+        GetPostListError::Unexpected,
+    ))
 }
 
 #[derive(Debug, thiserror::Error, PartialEq)]
 pub(crate) enum GetPostListError {
+    #[error("storage is temporarily unavailable")]
+    Unavailable,
     #[error("unexpected error: {0}")]
     Unexpected(String),
 }
@@ -1,20 +1,29 @@
-use crate::application::contracts::PostRepository;
+use crate::application::contracts::{PostEvent, PostEventBus, UnitOfWork, storage_error};
 use crate::domain::entities::Post;
 use crate::domain::value_objects::{Content, Identification, Title, TitleError};
 use html_escape::encode_text;
 use std::sync::Arc;
 
-#[tracing::instrument(name = "Handle update post command", skip(post_repo))]
+#[tracing::instrument(name = "Handle update post command", skip(unit_of_work, post_events))]
 pub(crate) async fn update_post_handler(
     post_id: Identification,
     user_id: Identification,
     command: UpdatePostCommand,
-    post_repo: &Arc<dyn PostRepository>,
+    unit_of_work: &Arc<dyn UnitOfWork>,
+    post_events: &Arc<dyn PostEventBus>,
 ) -> Result<Post, UpdatePostError> {
-    let mut post = post_repo
-        .get(&post_id)
+    let mut transaction = unit_of_work.begin().await.map_err(storage_error(
+        UpdatePostError::Unavailable,
+        UpdatePostError::Unexpected,
+    ))?;
+    let mut post = transaction
+        .posts()
+        .get_post_for_update(&post_id)
         .await
-        .map_err(|e| UpdatePostError::Unexpected(e.to_string()))?
+        .map_err(storage_error(
+            UpdatePostError::Unavailable,
+            UpdatePostError::Unexpected,
+        ))?
         .ok_or(UpdatePostError::NotFound)?;
 
     if *post.author_id() != user_id {
@@ -24,10 +33,19 @@ pub(crate) async fn update_post_handler(
     let title = Title::try_from(encode_text(&command.title).to_string())?;
     let content = Content::from(encode_text(&command.content).to_string());
     post.update(title, content);
-    post_repo
-        .update(&post)
+    transaction
+        .posts()
+        .update_post(&post)
         .await
-        .map_err(|e| UpdatePostError::Unexpected(e.to_string()))?;
+        .map_err(storage_error(
+            UpdatePostError::Unavailable,
+            UpdatePostError::Unexpected,
+        ))?;
+    transaction.commit().await.map_err(storage_error(
+        UpdatePostError::Unavailable,
+        UpdatePostError::Unexpected,
+    ))?;
+    post_events.publish(PostEvent::Updated(post.clone()));
 
     Ok(post)
 }
@@ -52,6 +70,8 @@ pub(crate) enum UpdatePostError {
     NotAllowed,
     #[error("invalid title: {0}")]
     InvalidTitle(#[from] TitleError),
+    #[error("storage is temporarily unavailable")]
+    Unavailable,
     #[error("unexpected error: {0}")]
     Unexpected(String),
 }
@@ -1,5 +1,19 @@
+mod api_token_repository;
+mod health_check;
+mod identity_provider;
+mod post_event_bus;
 mod post_repository;
+mod storage_unavailable;
+mod unique_violation;
+mod unit_of_work;
 mod user_repository;
 
+pub use api_token_repository::*;
+pub use health_check::*;
+pub use identity_provider::*;
+pub use post_event_bus::*;
 pub use post_repository::*;
+pub use storage_unavailable::*;
+pub use unique_violation::*;
+pub use unit_of_work::*;
 pub use user_repository::*;
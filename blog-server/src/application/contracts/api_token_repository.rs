@@ -0,0 +1,10 @@
+use crate::domain::entities::ApiToken;
+use crate::domain::value_objects::Identification;
+use async_trait::async_trait;
+
+#[async_trait]
+pub trait ApiTokenRepository: Send + Sync {
+    async fn create(&self, token: &ApiToken) -> Result<(), anyhow::Error>;
+    async fn get_by_hash(&self, token_hash: &str) -> Result<Option<ApiToken>, anyhow::Error>;
+    async fn list(&self, user_id: &Identification) -> Result<Vec<ApiToken>, anyhow::Error>;
+}
@@ -0,0 +1,7 @@
+use async_trait::async_trait;
+
+/// Probes the external dependencies the server cannot answer requests without.
+#[async_trait]
+pub trait HealthCheck: Send + Sync {
+    async fn check(&self) -> Result<(), anyhow::Error>;
+}
@@ -0,0 +1,42 @@
+use crate::domain::value_objects::{ExternalIdentity, Identification};
+use async_trait::async_trait;
+
+#[async_trait]
+pub trait IdentityProvider: Send + Sync {
+    /// Starts a new sign-in and returns the provider URL the browser must be sent to.
+    ///
+    /// With `link_user_id` the external identity is linked to that user instead of signing in.
+    async fn authorization_url(
+        &self,
+        link_user_id: Option<Identification>,
+    ) -> Result<String, anyhow::Error>;
+    /// Completes a sign-in started by `authorization_url`.
+    async fn exchange_code(
+        &self,
+        code: &str,
+        state: &str,
+    ) -> Result<IdentityClaims, IdentityProviderError>;
+}
+
+/// Verified claims taken from the provider's ID token.
+#[derive(Debug)]
+pub struct IdentityClaims {
+    pub identity: ExternalIdentity,
+    pub email: Option<String>,
+    pub email_verified: bool,
+    pub preferred_username: Option<String>,
+    /// User that started the sign-in from a logged-in session to link this identity.
+    pub link_user_id: Option<Identification>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum IdentityProviderError {
+    #[error("unknown or expired sign-in state")]
+    InvalidState,
+    #[error("identity provider rejected the sign-in: {0}")]
+    Rejected(String),
+    #[error("invalid id token: {0}")]
+    InvalidIdToken(String),
+    #[error("unexpected error: {0}")]
+    Unexpected(String),
+}
@@ -0,0 +1,18 @@
+use crate::domain::entities::Post;
+use tokio::sync::broadcast;
+
+/// A change to a post, published after it has been persisted.
+#[derive(Debug, Clone)]
+pub enum PostEvent {
+    Created(Post),
+    Updated(Post),
+    Deleted(Post),
+}
+
+pub trait PostEventBus: Send + Sync {
+    /// Publishes an event to all current subscribers; events are dropped when nobody listens.
+    fn publish(&self, event: PostEvent);
+    fn subscribe(&self) -> broadcast::Receiver<PostEvent>;
+    /// Ends all subscriptions and drops later events, so that watchers disconnect on shutdown.
+    fn close(&self);
+}
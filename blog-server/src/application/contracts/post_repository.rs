@@ -5,8 +5,13 @@ use async_trait::async_trait;
 #[async_trait]
 pub trait PostRepository: Send + Sync {
     async fn create(&self, post: &Post) -> Result<(), anyhow::Error>;
-    async fn update(&self, post: &Post) -> Result<(), anyhow::Error>;
+    /// Creates all posts in a single transaction; either every post is stored or none.
+    async fn create_many(&self, posts: &[Post]) -> Result<(), anyhow::Error>;
+    /// May be served by a read replica and miss the latest writes.
     async fn get(&self, id: &Identification) -> Result<Option<Post>, anyhow::Error>;
-    async fn delete(&self, id: &Identification) -> Result<(), anyhow::Error>;
+    /// Returns the existing posts among `ids`, in no particular order. May be served by a read
+    /// replica.
+    async fn get_many(&self, ids: &[Identification]) -> Result<Vec<Post>, anyhow::Error>;
+    /// May be served by a read replica.
     async fn list(&self, limit: usize, offset: usize) -> Result<(Vec<Post>, usize), anyhow::Error>;
 }
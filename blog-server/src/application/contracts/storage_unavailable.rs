@@ -0,0 +1,34 @@
+/// Returned by repositories, wrapped in an `anyhow::Error`, when no database connection became
+/// available in time. Unlike other storage errors it is temporary and the request can be retried.
+#[derive(Debug, thiserror::Error)]
+#[error("storage is temporarily unavailable")]
+pub struct StorageUnavailable;
+
+/// Maps a repository error to `unavailable` when storage is temporarily unavailable and to
+/// `unexpected` otherwise, e.g. `.map_err(storage_error(Error::Unavailable, Error::Unexpected))`.
+pub(crate) fn storage_error<E>(
+    unavailable: E,
+    unexpected: impl FnOnce(String) -> E,
+) -> impl FnOnce(anyhow::Error) -> E {
+    move |err| {
+        if err.is::<StorageUnavailable>() {
+            unavailable
+        } else {
+            unexpected(err.to_string())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn storage_error_distinguishes_unavailable_storage() {
+        let unavailable = storage_error(None, Some)(anyhow::Error::new(StorageUnavailable));
+        let unexpected = storage_error(None, Some)(anyhow::anyhow!("syntax error"));
+
+        assert_eq!(unavailable, None);
+        assert_eq!(unexpected, Some("syntax error".to_string()));
+    }
+}
@@ -0,0 +1,5 @@
+/// Returned by repositories, wrapped in an `anyhow::Error`, when a write would store a value
+/// that must be unique a second time, e.g. a username that was registered concurrently.
+#[derive(Debug, thiserror::Error)]
+#[error("unique constraint violated: {0}")]
+pub struct UniqueViolation(pub String);
@@ -0,0 +1,98 @@
+use crate::domain::entities::{ApiToken, Post, User};
+use crate::domain::value_objects::{Email, ExternalIdentity, Identification, UserName};
+use async_trait::async_trait;
+
+/// Starts transactions that group several repository calls, so that a decision taken on a read
+/// still holds when the following writes are committed.
+#[async_trait]
+pub trait UnitOfWork: Send + Sync {
+    async fn begin(&self) -> Result<Box<dyn Transaction>, anyhow::Error>;
+}
+
+/// A single transaction on the primary database, with one repository per aggregate. Dropping it
+/// without calling [`Transaction::commit`] rolls back every write made through them.
+///
+/// The `*_for_update` lookups lock the row until the transaction ends, so concurrent
+/// transactions that modify the same row run one after another.
+#[async_trait]
+pub trait Transaction: Send {
+    fn users(&mut self) -> &mut dyn UserTransactionRepository;
+    fn two_factor(&mut self) -> &mut dyn TwoFactorTransactionRepository;
+    fn external_identities(&mut self) -> &mut dyn ExternalIdentityTransactionRepository;
+    fn posts(&mut self) -> &mut dyn PostTransactionRepository;
+    fn api_tokens(&mut self) -> &mut dyn ApiTokenTransactionRepository;
+    async fn commit(self: Box<Self>) -> Result<(), anyhow::Error>;
+}
+
+#[async_trait]
+pub trait UserTransactionRepository: Send {
+    async fn user_exists(
+        &mut self,
+        username: &UserName,
+        email: &Email,
+    ) -> Result<bool, anyhow::Error>;
+    /// Fails with [`UniqueViolation`](crate::application::contracts::UniqueViolation) when the
+    /// username or email has been taken in the meantime.
+    async fn create_user(&mut self, user: &User) -> Result<(), anyhow::Error>;
+    async fn email_exists(&mut self, email: &Email) -> Result<bool, anyhow::Error>;
+    async fn get_user_for_update(
+        &mut self,
+        id: &Identification,
+    ) -> Result<Option<User>, anyhow::Error>;
+}
+
+#[async_trait]
+pub trait TwoFactorTransactionRepository: Send {
+    async fn update_two_factor(&mut self, user: &User) -> Result<(), anyhow::Error>;
+    async fn replace_recovery_codes(
+        &mut self,
+        user_id: &Identification,
+        code_hashes: &[String],
+    ) -> Result<(), anyhow::Error>;
+    /// The ids and hashes of the recovery codes the user has not used yet.
+    async fn get_unused_recovery_codes(
+        &mut self,
+        user_id: &Identification,
+    ) -> Result<Vec<(Identification, String)>, anyhow::Error>;
+    async fn use_recovery_code(&mut self, id: &Identification) -> Result<(), anyhow::Error>;
+}
+
+#[async_trait]
+pub trait ExternalIdentityTransactionRepository: Send {
+    /// Locks the external identity until the transaction ends, whether it is stored or not.
+    async fn lock_external_identity(
+        &mut self,
+        identity: &ExternalIdentity,
+    ) -> Result<(), anyhow::Error>;
+    async fn get_user_by_external_identity(
+        &mut self,
+        identity: &ExternalIdentity,
+    ) -> Result<Option<User>, anyhow::Error>;
+    /// Fails with [`UniqueViolation`](crate::application::contracts::UniqueViolation) when the
+    /// identity is already linked.
+    async fn link_external_identity(
+        &mut self,
+        user_id: &Identification,
+        identity: &ExternalIdentity,
+    ) -> Result<(), anyhow::Error>;
+}
+
+#[async_trait]
+pub trait PostTransactionRepository: Send {
+    async fn get_post_for_update(
+        &mut self,
+        id: &Identification,
+    ) -> Result<Option<Post>, anyhow::Error>;
+    async fn update_post(&mut self, post: &Post) -> Result<(), anyhow::Error>;
+    async fn delete_post(&mut self, id: &Identification) -> Result<(), anyhow::Error>;
+}
+
+#[async_trait]
+pub trait ApiTokenTransactionRepository: Send {
+    async fn get_api_token_for_update(
+        &mut self,
+        id: &Identification,
+    ) -> Result<Option<ApiToken>, anyhow::Error>;
+    /// Stores `last_used_at` and `revoked_at`.
+    async fn update_api_token(&mut self, token: &ApiToken) -> Result<(), anyhow::Error>;
+}
@@ -0,0 +1,6 @@
+#[derive(clap::Parser, Debug)]
+pub(super) struct Args {
+    /// Validate the configuration and exit
+    #[clap(long)]
+    pub check_config: bool,
+}
@@ -1,19 +1,27 @@
 use secrecy::{ExposeSecret, SecretString};
-use sqlx::postgres::{PgConnectOptions, PgSslMode};
-use std::net::{SocketAddr, SocketAddrV4};
+use sqlx::postgres::{PgConnectOptions, PgPoolOptions, PgSslMode};
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+mod validation;
 
 #[derive(serde::Deserialize, Clone)]
 pub(crate) struct Configuration {
     server: ServerConfiguration,
     database: DatabaseConfiguration,
     jwt: JwtConfiguration,
+    two_factor: TwoFactorConfiguration,
+    rate_limit: RateLimitConfiguration,
+    oidc: OidcConfiguration,
+    telemetry: TelemetryConfiguration,
+    cache: CacheConfiguration,
 }
 
 impl Configuration {
     pub(crate) fn read_configuration() -> anyhow::Result<Self> {
         let env = std::env::var("APP_ENVIRONMENT").unwrap_or_else(|_| "development".to_string());
-        let base_path = std::env::current_dir()?;
-        let config_dir = base_path.join("config");
+        let config_dir = Self::config_dir()?;
         let env_file = format!("{}.yaml", env);
         let configuration = config::Config::builder()
             .add_source(config::File::from(config_dir.join("base.yaml")))
@@ -21,13 +29,109 @@ impl Configuration {
             .add_source(
                 config::Environment::with_prefix("APP")
                     .prefix_separator("_")
-                    .separator("__"),
+                    .separator("__")
+                    .try_parsing(true)
+                    .list_separator(",")
+                    .with_list_parse_key("server.hosts"),
             )
             .build()?;
         let result = configuration.try_deserialize::<Configuration>()?;
+        result.validate()?;
         Ok(result)
     }
 
+    /// Directory with the configuration files.
+    pub(crate) fn config_dir() -> std::io::Result<PathBuf> {
+        Ok(std::env::current_dir()?.join("config"))
+    }
+
+    /// Names the settings that differ in `other` but only take effect after a restart.
+    pub(crate) fn restart_required_changes(&self, other: &Self) -> Vec<&'static str> {
+        let (server, other_server) = (&self.server, &other.server);
+        let (database, other_database) = (&self.database, &other.database);
+        let (oidc, other_oidc) = (&self.oidc, &other.oidc);
+        [
+            ("server.hosts", server.hosts() != other_server.hosts()),
+            (
+                "server.http_port",
+                server.http_port != other_server.http_port,
+            ),
+            (
+                "server.grpc_port",
+                server.grpc_port != other_server.grpc_port,
+            ),
+            (
+                "server.http_unix_socket",
+                server.http_unix_socket != other_server.http_unix_socket,
+            ),
+            (
+                "server.grpc_unix_socket",
+                server.grpc_unix_socket != other_server.grpc_unix_socket,
+            ),
+            (
+                "server.single_port",
+                server.single_port != other_server.single_port,
+            ),
+            (
+                "server.shutdown_timeout_secs",
+                server.shutdown_timeout_secs != other_server.shutdown_timeout_secs,
+            ),
+            ("server.tls", server.tls != other_server.tls),
+            (
+                "database",
+                database.username != other_database.username
+                    || database.password.expose_secret() != other_database.password.expose_secret()
+                    || database.port != other_database.port
+                    || database.host != other_database.host
+                    || database.database_name != other_database.database_name
+                    || database.require_ssl != other_database.require_ssl
+                    || database.max_connections != other_database.max_connections
+                    || database.min_connections != other_database.min_connections
+                    || database.acquire_timeout_secs != other_database.acquire_timeout_secs
+                    || database.idle_timeout_secs != other_database.idle_timeout_secs
+                    || database.max_lifetime_secs != other_database.max_lifetime_secs
+                    || database.statement_timeout_ms != other_database.statement_timeout_ms
+                    || database.read_replicas != other_database.read_replicas,
+            ),
+            (
+                "jwt.secret",
+                self.jwt.secret.expose_secret() != other.jwt.secret.expose_secret(),
+            ),
+            (
+                "two_factor.encryption_key",
+                self.two_factor.encryption_key.expose_secret()
+                    != other.two_factor.encryption_key.expose_secret(),
+            ),
+            (
+                "oidc",
+                oidc.enabled != other_oidc.enabled
+                    || oidc.issuer_url != other_oidc.issuer_url
+                    || oidc.client_id != other_oidc.client_id
+                    || oidc.client_secret.as_ref().map(ExposeSecret::expose_secret)
+                        != other_oidc
+                            .client_secret
+                            .as_ref()
+                            .map(ExposeSecret::expose_secret)
+                    || oidc.redirect_url != other_oidc.redirect_url
+                    || oidc.post_login_redirect_url != other_oidc.post_login_redirect_url
+                    || oidc.scopes != other_oidc.scopes,
+            ),
+            ("telemetry", self.telemetry != other.telemetry),
+            ("cache", self.cache != other.cache),
+        ]
+        .into_iter()
+        .filter_map(|(name, changed)| changed.then_some(name))
+        .collect()
+    }
+
+    /// Takes over the settings that can change while the server runs: the log level, the CORS
+    /// white list and the rate limits.
+    pub(crate) fn take_reloadable(&mut self, other: &Self) {
+        self.server.log_level = other.server.log_level.clone();
+        self.server.white_list = other.server.white_list.clone();
+        self.rate_limit = other.rate_limit.clone();
+    }
+
     pub(crate) fn get_server_configuration(&self) -> &ServerConfiguration {
         &self.server
     }
@@ -38,27 +142,75 @@ impl Configuration {
     pub(crate) fn get_jwt_configuration(&self) -> &JwtConfiguration {
         &self.jwt
     }
+
+    pub(crate) fn get_two_factor_configuration(&self) -> &TwoFactorConfiguration {
+        &self.two_factor
+    }
+
+    pub(crate) fn get_rate_limit_configuration(&self) -> &RateLimitConfiguration {
+        &self.rate_limit
+    }
+
+    pub(crate) fn get_oidc_configuration(&self) -> &OidcConfiguration {
+        &self.oidc
+    }
+
+    pub(crate) fn get_telemetry_configuration(&self) -> &TelemetryConfiguration {
+        &self.telemetry
+    }
+
+    pub(crate) fn get_cache_configuration(&self) -> &CacheConfiguration {
+        &self.cache
+    }
 }
 
 #[derive(serde::Deserialize, Clone)]
 pub(crate) struct ServerConfiguration {
-    host: String,
+    hosts: Vec<String>,
+    /// Single address used before `hosts` was introduced; still honored, so that existing
+    /// configurations and `APP_SERVER__HOST` keep working.
+    host: Option<String>,
     http_port: u16,
     grpc_port: u16,
+    http_unix_socket: Option<String>,
+    grpc_unix_socket: Option<String>,
+    single_port: bool,
     log_level: String,
     white_list: Vec<String>,
+    shutdown_timeout_secs: u64,
+    tls: TlsConfiguration,
 }
 
 impl ServerConfiguration {
-    pub(crate) fn get_http_address(&self) -> (&str, u16) {
-        (self.host.as_str(), self.http_port)
+    pub(crate) fn get_http_addresses(&self) -> std::io::Result<Vec<SocketAddr>> {
+        socket_addresses(self.hosts(), self.http_port)
     }
 
-    pub(crate) fn get_grpc_address(&self) -> Result<SocketAddr, std::net::AddrParseError> {
-        Ok(SocketAddr::V4(SocketAddrV4::new(
-            self.host.parse()?,
-            self.grpc_port,
-        )))
+    pub(crate) fn get_grpc_addresses(&self) -> std::io::Result<Vec<SocketAddr>> {
+        socket_addresses(self.hosts(), self.grpc_port)
+    }
+
+    /// The old `host` key replaces `hosts` when it is set.
+    fn hosts(&self) -> &[String] {
+        match &self.host {
+            Some(host) => std::slice::from_ref(host),
+            None => &self.hosts,
+        }
+    }
+
+    /// Unix domain socket the HTTP server listens on in addition to the TCP addresses.
+    pub(crate) fn get_http_unix_socket(&self) -> Option<&Path> {
+        self.http_unix_socket.as_deref().map(Path::new)
+    }
+
+    /// Unix domain socket the gRPC server listens on in addition to the TCP addresses.
+    pub(crate) fn get_grpc_unix_socket(&self) -> Option<&Path> {
+        self.grpc_unix_socket.as_deref().map(Path::new)
+    }
+
+    /// Whether gRPC and the HTTP API are both served on the HTTP listeners.
+    pub(crate) fn is_single_port(&self) -> bool {
+        self.single_port
     }
 
     pub(crate) fn get_log_level(&self) -> &str {
@@ -68,6 +220,67 @@ impl ServerConfiguration {
     pub(crate) fn get_white_list(&self) -> &[String] {
         &self.white_list
     }
+
+    /// How long in-flight requests may take to finish once shutdown has started.
+    pub(crate) fn get_shutdown_timeout(&self) -> Duration {
+        Duration::from_secs(self.shutdown_timeout_secs)
+    }
+
+    pub(crate) fn get_tls_configuration(&self) -> &TlsConfiguration {
+        &self.tls
+    }
+}
+
+/// Resolves every host to the addresses to listen on. Hosts are IP addresses, optionally in
+/// brackets for IPv6, or names such as `localhost` that may resolve to several addresses.
+fn socket_addresses(hosts: &[String], port: u16) -> std::io::Result<Vec<SocketAddr>> {
+    let mut addresses = Vec::new();
+    for host in hosts {
+        let host = host.trim();
+        let host = host
+            .strip_prefix('[')
+            .and_then(|host| host.strip_suffix(']'))
+            .unwrap_or(host);
+        for address in (host, port).to_socket_addrs()? {
+            if !addresses.contains(&address) {
+                addresses.push(address);
+            }
+        }
+    }
+    Ok(addresses)
+}
+
+/// Interprets zero seconds as no limit.
+fn non_zero_duration(secs: u64) -> Option<Duration> {
+    (secs > 0).then(|| Duration::from_secs(secs))
+}
+
+#[derive(serde::Deserialize, Clone, PartialEq)]
+pub(crate) struct TlsConfiguration {
+    enabled: bool,
+    certificate_path: String,
+    private_key_path: String,
+    client_ca_path: Option<String>,
+}
+
+impl TlsConfiguration {
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// PEM file with the server certificate chain, leaf first.
+    pub(crate) fn get_certificate_path(&self) -> &str {
+        &self.certificate_path
+    }
+
+    pub(crate) fn get_private_key_path(&self) -> &str {
+        &self.private_key_path
+    }
+
+    /// PEM bundle of the CAs that sign gRPC client certificates; enables mutual TLS when set.
+    pub(crate) fn get_client_ca_path(&self) -> Option<&str> {
+        self.client_ca_path.as_deref()
+    }
 }
 
 #[derive(serde::Deserialize, Clone)]
@@ -78,6 +291,16 @@ pub(crate) struct DatabaseConfiguration {
     host: String,
     database_name: String,
     require_ssl: bool,
+    max_connections: u32,
+    min_connections: u32,
+    acquire_timeout_secs: u64,
+    /// Zero keeps idle connections open.
+    idle_timeout_secs: u64,
+    /// Zero keeps connections open regardless of age.
+    max_lifetime_secs: u64,
+    /// Zero lets statements run without a time limit.
+    statement_timeout_ms: u64,
+    read_replicas: Vec<ReadReplicaConfiguration>,
 }
 
 impl DatabaseConfiguration {
@@ -95,9 +318,37 @@ impl DatabaseConfiguration {
             .port(self.port)
             .database(&self.database_name)
             .ssl_mode(ssl_mode)
+            .options([("statement_timeout", self.statement_timeout_ms)])
+    }
+
+    /// Replicas share the credentials, database name and pool settings of the primary.
+    pub(crate) fn get_read_replica_connection_options(&self) -> Vec<PgConnectOptions> {
+        self.read_replicas
+            .iter()
+            .map(|replica| {
+                self.get_connection_options()
+                    .host(&replica.host)
+                    .port(replica.port)
+            })
+            .collect()
+    }
+
+    pub(crate) fn get_pool_options(&self) -> PgPoolOptions {
+        PgPoolOptions::new()
+            .max_connections(self.max_connections)
+            .min_connections(self.min_connections)
+            .acquire_timeout(Duration::from_secs(self.acquire_timeout_secs))
+            .idle_timeout(non_zero_duration(self.idle_timeout_secs))
+            .max_lifetime(non_zero_duration(self.max_lifetime_secs))
     }
 }
 
+#[derive(serde::Deserialize, Clone, PartialEq)]
+pub(crate) struct ReadReplicaConfiguration {
+    host: String,
+    port: u16,
+}
+
 #[derive(serde::Deserialize, Clone)]
 pub(crate) struct JwtConfiguration {
     secret: SecretString,
@@ -108,3 +359,258 @@ impl JwtConfiguration {
         &self.secret
     }
 }
+
+#[derive(serde::Deserialize, Clone)]
+pub(crate) struct TwoFactorConfiguration {
+    encryption_key: SecretString,
+}
+
+impl TwoFactorConfiguration {
+    /// Key the TOTP secrets are encrypted with in the database. Secrets encrypted with another
+    /// key can no longer be read, so the affected users have to enroll again.
+    pub(crate) fn get_encryption_key(&self) -> &SecretString {
+        &self.encryption_key
+    }
+}
+
+#[derive(serde::Deserialize, Clone, PartialEq)]
+pub(crate) struct RateLimitConfiguration {
+    enabled: bool,
+    anonymous: RateLimitBudgetConfiguration,
+    authenticated: RateLimitBudgetConfiguration,
+}
+
+impl RateLimitConfiguration {
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub(crate) fn get_anonymous(&self) -> &RateLimitBudgetConfiguration {
+        &self.anonymous
+    }
+
+    pub(crate) fn get_authenticated(&self) -> &RateLimitBudgetConfiguration {
+        &self.authenticated
+    }
+}
+
+#[derive(serde::Deserialize, Clone, PartialEq)]
+pub(crate) struct RateLimitBudgetConfiguration {
+    read: TokenBucketConfiguration,
+    write: TokenBucketConfiguration,
+}
+
+impl RateLimitBudgetConfiguration {
+    pub(crate) fn get_read(&self) -> &TokenBucketConfiguration {
+        &self.read
+    }
+
+    pub(crate) fn get_write(&self) -> &TokenBucketConfiguration {
+        &self.write
+    }
+}
+
+#[derive(serde::Deserialize, Clone, PartialEq)]
+pub(crate) struct TokenBucketConfiguration {
+    capacity: u32,
+    refill_per_minute: u32,
+}
+
+impl TokenBucketConfiguration {
+    pub(crate) fn get_capacity(&self) -> u32 {
+        self.capacity
+    }
+
+    pub(crate) fn get_refill_per_minute(&self) -> u32 {
+        self.refill_per_minute
+    }
+}
+
+#[derive(serde::Deserialize, Clone)]
+pub(crate) struct OidcConfiguration {
+    enabled: bool,
+    issuer_url: String,
+    client_id: String,
+    client_secret: Option<SecretString>,
+    redirect_url: String,
+    post_login_redirect_url: String,
+    scopes: Vec<String>,
+}
+
+impl OidcConfiguration {
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub(crate) fn get_issuer_url(&self) -> &str {
+        self.issuer_url.trim_end_matches('/')
+    }
+
+    pub(crate) fn get_client_id(&self) -> &str {
+        &self.client_id
+    }
+
+    pub(crate) fn get_client_secret(&self) -> Option<&SecretString> {
+        self.client_secret.as_ref()
+    }
+
+    pub(crate) fn get_redirect_url(&self) -> &str {
+        &self.redirect_url
+    }
+
+    /// Frontend page that receives the issued token, or an error code, in its URL fragment.
+    pub(crate) fn get_post_login_redirect_url(&self) -> &str {
+        &self.post_login_redirect_url
+    }
+
+    pub(crate) fn get_scopes(&self) -> &[String] {
+        &self.scopes
+    }
+}
+
+#[derive(serde::Deserialize, Clone, PartialEq)]
+pub(crate) struct TelemetryConfiguration {
+    enabled: bool,
+    otlp_endpoint: String,
+    service_name: String,
+}
+
+impl TelemetryConfiguration {
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// gRPC endpoint of the OTLP collector that spans are exported to.
+    pub(crate) fn get_otlp_endpoint(&self) -> &str {
+        &self.otlp_endpoint
+    }
+
+    pub(crate) fn get_service_name(&self) -> &str {
+        &self.service_name
+    }
+}
+
+#[derive(serde::Deserialize, Clone, PartialEq)]
+pub(crate) struct CacheConfiguration {
+    enabled: bool,
+    capacity: usize,
+    ttl_secs: u64,
+    max_age_secs: u64,
+}
+
+impl CacheConfiguration {
+    /// Whether post reads are cached in memory; HTTP caching headers are sent either way.
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Maximum number of cached posts, and separately of cached post list pages.
+    pub(crate) fn get_capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub(crate) fn get_ttl(&self) -> Duration {
+        Duration::from_secs(self.ttl_secs)
+    }
+
+    /// `max-age` of the `Cache-Control` header on post reads.
+    pub(crate) fn get_max_age(&self) -> Duration {
+        Duration::from_secs(self.max_age_secs)
+    }
+}
+
+#[cfg(test)]
+impl Configuration {
+    /// The base configuration with a valid JWT secret, overridden by `overrides` in YAML.
+    pub(crate) fn for_tests(overrides: &str) -> Self {
+        config::Config::builder()
+            .add_source(config::File::from_str(
+                include_str!("../../config/base.yaml"),
+                config::FileFormat::Yaml,
+            ))
+            .add_source(config::File::from_str(
+                "jwt:\n  secret: \"0123456789abcdef0123456789abcdef\"\ntwo_factor:\n  encryption_key: \"fedcba9876543210fedcba9876543210\"",
+                config::FileFormat::Yaml,
+            ))
+            .add_source(config::File::from_str(overrides, config::FileFormat::Yaml))
+            .build()
+            .unwrap()
+            .try_deserialize()
+            .unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hosts(hosts: &[&str]) -> Vec<String> {
+        hosts.iter().map(|host| host.to_string()).collect()
+    }
+
+    #[test]
+    fn socket_addresses_accepts_ipv4_and_ipv6_hosts() {
+        let addresses = socket_addresses(&hosts(&["127.0.0.1", "::1", "[::]"]), 3000).unwrap();
+
+        assert_eq!(
+            addresses,
+            vec![
+                "127.0.0.1:3000".parse::<SocketAddr>().unwrap(),
+                "[::1]:3000".parse().unwrap(),
+                "[::]:3000".parse().unwrap(),
+            ]
+        );
+    }
+
+    #[test]
+    fn socket_addresses_skips_duplicates() {
+        let addresses = socket_addresses(&hosts(&["127.0.0.1", " 127.0.0.1 "]), 50051).unwrap();
+
+        assert_eq!(addresses, vec!["127.0.0.1:50051".parse().unwrap()]);
+    }
+
+    #[test]
+    fn socket_addresses_rejects_invalid_host() {
+        assert!(socket_addresses(&hosts(&["not a host"]), 3000).is_err());
+    }
+
+    #[test]
+    fn old_host_key_replaces_hosts() {
+        let config = Configuration::for_tests("server:\n  host: \"127.0.0.2\"");
+
+        assert_eq!(
+            config
+                .get_server_configuration()
+                .get_http_addresses()
+                .unwrap(),
+            vec!["127.0.0.2:3000".parse::<SocketAddr>().unwrap()]
+        );
+    }
+
+    #[test]
+    fn restart_required_changes_ignores_reloadable_settings() {
+        let active = Configuration::for_tests("");
+        let other = Configuration::for_tests(
+            "server:\n  log_level: debug\n  white_list: []\n  http_port: 3001\nrate_limit:\n  enabled: false\njwt:\n  secret: \"another secret that is long enough!\"",
+        );
+
+        assert_eq!(
+            active.restart_required_changes(&other),
+            vec!["server.http_port", "jwt.secret"]
+        );
+    }
+
+    #[test]
+    fn take_reloadable_keeps_other_settings() {
+        let mut active = Configuration::for_tests("");
+        let other = Configuration::for_tests("server:\n  log_level: debug\n  http_port: 3001");
+
+        active.take_reloadable(&other);
+
+        assert_eq!(active.get_server_configuration().get_log_level(), "debug");
+        assert_eq!(
+            active.restart_required_changes(&other),
+            vec!["server.http_port"]
+        );
+    }
+}
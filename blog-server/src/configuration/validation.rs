@@ -0,0 +1,304 @@
+use super::{
+    CacheConfiguration, Configuration, DatabaseConfiguration, JwtConfiguration, OidcConfiguration,
+    RateLimitBudgetConfiguration, RateLimitConfiguration, ServerConfiguration,
+    TelemetryConfiguration, TlsConfiguration, TwoFactorConfiguration, socket_addresses,
+};
+use reqwest::Url;
+use secrecy::ExposeSecret;
+use std::path::Path;
+use tracing_subscriber::EnvFilter;
+
+/// Shortest accepted JWT signing secret; HS256 keys should be at least as long as the hash.
+const MIN_JWT_SECRET_BYTES: usize = 32;
+/// Shortest accepted TOTP secret encryption key, as long as the AES-256 key derived from it.
+const MIN_ENCRYPTION_KEY_BYTES: usize = 32;
+
+/// Every semantic problem found in a configuration that was read successfully.
+#[derive(Debug, thiserror::Error)]
+#[error("invalid configuration:\n{}", format_problems(.problems))]
+pub(crate) struct ConfigurationError {
+    problems: Vec<String>,
+}
+
+fn format_problems(problems: &[String]) -> String {
+    problems
+        .iter()
+        .map(|problem| format!("  - {}", problem))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+impl Configuration {
+    /// Checks the settings that deserialisation cannot, reporting all problems at once.
+    pub(crate) fn validate(&self) -> Result<(), ConfigurationError> {
+        let mut problems = Vec::new();
+        validate_server(&self.server, &mut problems);
+        validate_database(&self.database, &mut problems);
+        validate_jwt(&self.jwt, &mut problems);
+        validate_two_factor(&self.two_factor, &mut problems);
+        validate_rate_limit(&self.rate_limit, &mut problems);
+        validate_oidc(&self.oidc, &mut problems);
+        validate_telemetry(&self.telemetry, &mut problems);
+        validate_cache(&self.cache, &mut problems);
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigurationError { problems })
+        }
+    }
+}
+
+fn validate_server(config: &ServerConfiguration, problems: &mut Vec<String>) {
+    for host in config.hosts() {
+        if let Err(err) = socket_addresses(std::slice::from_ref(host), 0) {
+            problems.push(format!("server.hosts: cannot resolve {:?}: {}", host, err));
+        }
+    }
+    if config.hosts().is_empty() && config.http_unix_socket.is_none() {
+        problems.push("server.hosts: at least one host or http_unix_socket is required".into());
+    }
+    if !config.single_port {
+        if config.http_port == config.grpc_port {
+            problems.push(format!(
+                "server.grpc_port: {} is also the http_port; use single_port to share it",
+                config.grpc_port
+            ));
+        }
+        if config.hosts().is_empty() && config.grpc_unix_socket.is_none() {
+            problems.push("server.hosts: at least one host or grpc_unix_socket is required".into());
+        }
+        if config.http_unix_socket.is_some() && config.http_unix_socket == config.grpc_unix_socket {
+            problems.push(
+                "server.grpc_unix_socket: must differ from http_unix_socket; use single_port to \
+                 share it"
+                    .into(),
+            );
+        }
+    }
+    if let Err(err) = EnvFilter::try_new(&config.log_level) {
+        problems.push(format!("server.log_level: {}", err));
+    }
+    for origin in &config.white_list {
+        if let Err(problem) = validate_origin(origin) {
+            problems.push(format!("server.white_list: {:?} {}", origin, problem));
+        }
+    }
+    validate_tls(&config.tls, config.single_port, problems);
+}
+
+fn validate_tls(config: &TlsConfiguration, single_port: bool, problems: &mut Vec<String>) {
+    if !config.enabled {
+        return;
+    }
+    validate_file(
+        "server.tls.certificate_path",
+        &config.certificate_path,
+        problems,
+    );
+    validate_file(
+        "server.tls.private_key_path",
+        &config.private_key_path,
+        problems,
+    );
+    if let Some(client_ca_path) = &config.client_ca_path {
+        validate_file("server.tls.client_ca_path", client_ca_path, problems);
+        if single_port {
+            problems.push(
+                "server.tls.client_ca_path: client certificates cannot be required in \
+                 single-port mode"
+                    .into(),
+            );
+        }
+    }
+}
+
+fn validate_database(config: &DatabaseConfiguration, problems: &mut Vec<String>) {
+    if config.host.trim().is_empty() {
+        problems.push("database.host: must not be empty".into());
+    }
+    if config.username.trim().is_empty() {
+        problems.push("database.username: must not be empty".into());
+    }
+    if config.database_name.trim().is_empty() {
+        problems.push("database.database_name: must not be empty".into());
+    }
+    if config.max_connections == 0 {
+        problems.push("database.max_connections: must be positive".into());
+    }
+    if config.min_connections > config.max_connections {
+        problems.push(format!(
+            "database.min_connections: {} exceeds max_connections {}",
+            config.min_connections, config.max_connections
+        ));
+    }
+    if config.acquire_timeout_secs == 0 {
+        problems.push("database.acquire_timeout_secs: must be positive".into());
+    }
+    for replica in &config.read_replicas {
+        if replica.host.trim().is_empty() {
+            problems.push("database.read_replicas.host: must not be empty".into());
+        }
+    }
+}
+
+fn validate_jwt(config: &JwtConfiguration, problems: &mut Vec<String>) {
+    let length = config.secret.expose_secret().len();
+    if length < MIN_JWT_SECRET_BYTES {
+        problems.push(format!(
+            "jwt.secret: must be at least {} bytes long, got {}",
+            MIN_JWT_SECRET_BYTES, length
+        ));
+    }
+}
+
+fn validate_two_factor(config: &TwoFactorConfiguration, problems: &mut Vec<String>) {
+    let length = config.encryption_key.expose_secret().len();
+    if length < MIN_ENCRYPTION_KEY_BYTES {
+        problems.push(format!(
+            "two_factor.encryption_key: must be at least {} bytes long, got {}",
+            MIN_ENCRYPTION_KEY_BYTES, length
+        ));
+    }
+}
+
+fn validate_rate_limit(config: &RateLimitConfiguration, problems: &mut Vec<String>) {
+    if !config.enabled {
+        return;
+    }
+    validate_budget("rate_limit.anonymous", &config.anonymous, problems);
+    validate_budget("rate_limit.authenticated", &config.authenticated, problems);
+}
+
+fn validate_budget(key: &str, config: &RateLimitBudgetConfiguration, problems: &mut Vec<String>) {
+    for (operation, bucket) in [("read", &config.read), ("write", &config.write)] {
+        if bucket.capacity == 0 {
+            problems.push(format!(
+                "{}.{}.capacity: must be positive, or disable rate_limit",
+                key, operation
+            ));
+        }
+    }
+}
+
+fn validate_oidc(config: &OidcConfiguration, problems: &mut Vec<String>) {
+    if !config.enabled {
+        return;
+    }
+    validate_url("oidc.issuer_url", &config.issuer_url, problems);
+    validate_url("oidc.redirect_url", &config.redirect_url, problems);
+    validate_url(
+        "oidc.post_login_redirect_url",
+        &config.post_login_redirect_url,
+        problems,
+    );
+    if config.client_id.trim().is_empty() {
+        problems.push("oidc.client_id: must not be empty".into());
+    }
+    if !config.scopes.iter().any(|scope| scope == "openid") {
+        problems.push("oidc.scopes: must include \"openid\"".into());
+    }
+}
+
+fn validate_telemetry(config: &TelemetryConfiguration, problems: &mut Vec<String>) {
+    if !config.enabled {
+        return;
+    }
+    validate_url("telemetry.otlp_endpoint", &config.otlp_endpoint, problems);
+    if config.service_name.trim().is_empty() {
+        problems.push("telemetry.service_name: must not be empty".into());
+    }
+}
+
+fn validate_cache(config: &CacheConfiguration, problems: &mut Vec<String>) {
+    if !config.enabled {
+        return;
+    }
+    if config.capacity == 0 {
+        problems.push("cache.capacity: must be positive, or disable cache".into());
+    }
+    if config.ttl_secs == 0 {
+        problems.push("cache.ttl_secs: must be positive, or disable cache".into());
+    }
+}
+
+/// CORS origins are compared verbatim, so they must be in their serialised form, e.g.
+/// `https://example.com:8080` without a path or trailing slash.
+fn validate_origin(origin: &str) -> Result<(), &'static str> {
+    let url = Url::parse(origin).map_err(|_| "is not a URL")?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err("must use http or https");
+    }
+    if url.origin().ascii_serialization() != origin {
+        return Err("must be a bare origin such as https://example.com, without a path");
+    }
+    Ok(())
+}
+
+fn validate_url(key: &str, url: &str, problems: &mut Vec<String>) {
+    match Url::parse(url) {
+        Ok(url) if matches!(url.scheme(), "http" | "https") => {}
+        Ok(_) => problems.push(format!("{}: {:?} must use http or https", key, url)),
+        Err(err) => problems.push(format!("{}: {:?} is not a URL: {}", key, url, err)),
+    }
+}
+
+fn validate_file(key: &str, path: &str, problems: &mut Vec<String>) {
+    if !Path::new(path).is_file() {
+        problems.push(format!("{}: {} does not exist", key, path));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn problems(overrides: &str) -> Vec<String> {
+        match Configuration::for_tests(overrides).validate() {
+            Ok(()) => Vec::new(),
+            Err(err) => err.problems,
+        }
+    }
+
+    #[test]
+    fn validate_accepts_base_configuration() {
+        assert_eq!(problems(""), Vec::<String>::new());
+    }
+
+    #[test]
+    fn validate_reports_all_problems() {
+        let problems = problems(
+            "server:\n  grpc_port: 3000\n  white_list: [\"http://127.0.0.1:8080/\"]\njwt:\n  secret: short",
+        );
+
+        assert_eq!(problems.len(), 3, "{:?}", problems);
+        assert!(problems[0].starts_with("server.grpc_port:"));
+        assert!(problems[1].starts_with("server.white_list:"));
+        assert_eq!(
+            problems[2],
+            "jwt.secret: must be at least 32 bytes long, got 5"
+        );
+    }
+
+    #[test]
+    fn validate_requires_long_encryption_key() {
+        assert_eq!(
+            problems("two_factor:\n  encryption_key: short"),
+            vec!["two_factor.encryption_key: must be at least 32 bytes long, got 5"]
+        );
+    }
+
+    #[test]
+    fn validate_allows_shared_port_in_single_port_mode() {
+        assert!(problems("server:\n  grpc_port: 3000\n  single_port: true").is_empty());
+    }
+
+    #[test]
+    fn validate_origin_requires_bare_http_origin() {
+        assert!(validate_origin("http://127.0.0.1:8080").is_ok());
+        assert!(validate_origin("https://example.com").is_ok());
+        assert!(validate_origin("https://example.com/").is_err());
+        assert!(validate_origin("https://example.com/app").is_err());
+        assert!(validate_origin("ftp://example.com").is_err());
+        assert!(validate_origin("example.com").is_err());
+    }
+}
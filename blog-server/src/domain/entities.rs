@@ -1,5 +1,7 @@
+mod api_token;
 mod post;
 mod user;
 
+pub(crate) use api_token::*;
 pub(crate) use post::*;
 pub(crate) use user::*;
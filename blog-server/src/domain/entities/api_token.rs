@@ -0,0 +1,176 @@
+use crate::domain::value_objects::{ApiTokenName, DateTime, Identification, Scope};
+
+/// `last_used_at` is only recorded to this precision, so that a token used for every request
+/// does not cause a database write each time.
+const LAST_USED_RESOLUTION_SECS: i64 = 60;
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct ApiToken {
+    id: Identification,
+    user_id: Identification,
+    name: ApiTokenName,
+    token_hash: String,
+    scopes: Vec<Scope>,
+    created_at: DateTime,
+    expires_at: Option<DateTime>,
+    last_used_at: Option<DateTime>,
+    revoked_at: Option<DateTime>,
+}
+
+impl ApiToken {
+    pub(crate) fn new(
+        user_id: Identification,
+        name: ApiTokenName,
+        token_hash: String,
+        scopes: Vec<Scope>,
+        expires_at: Option<DateTime>,
+    ) -> Self {
+        Self {
+            id: Identification::new(),
+            user_id,
+            name,
+            token_hash,
+            scopes,
+            created_at: DateTime::now(),
+            expires_at,
+            last_used_at: None,
+            revoked_at: None,
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn restore(
+        id: Identification,
+        user_id: Identification,
+        name: ApiTokenName,
+        token_hash: String,
+        scopes: Vec<Scope>,
+        created_at: DateTime,
+        expires_at: Option<DateTime>,
+        last_used_at: Option<DateTime>,
+        revoked_at: Option<DateTime>,
+    ) -> Self {
+        Self {
+            id,
+            user_id,
+            name,
+            token_hash,
+            scopes,
+            created_at,
+            expires_at,
+            last_used_at,
+            revoked_at,
+        }
+    }
+
+    pub(crate) fn is_active(&self) -> bool {
+        let now = DateTime::now();
+        self.revoked_at.is_none()
+            && self
+                .expires_at
+                .as_ref()
+                .is_none_or(|expires_at| expires_at.as_ref() > now.as_ref())
+    }
+
+    /// Returns whether a use now would change `last_used_at`.
+    pub(crate) fn is_use_due(&self) -> bool {
+        let now = DateTime::now();
+        self.last_used_at.as_ref().is_none_or(|last_used_at| {
+            (*now.as_ref() - *last_used_at.as_ref()).num_seconds() >= LAST_USED_RESOLUTION_SECS
+        })
+    }
+
+    /// Returns whether `last_used_at` changed and has to be stored.
+    pub(crate) fn mark_used(&mut self) -> bool {
+        if !self.is_use_due() {
+            return false;
+        }
+        self.last_used_at = Some(DateTime::now());
+        true
+    }
+
+    pub(crate) fn revoke(&mut self) {
+        if self.revoked_at.is_none() {
+            self.revoked_at = Some(DateTime::now());
+        }
+    }
+
+    pub(crate) fn id(&self) -> &Identification {
+        &self.id
+    }
+
+    pub(crate) fn user_id(&self) -> &Identification {
+        &self.user_id
+    }
+
+    pub(crate) fn name(&self) -> &ApiTokenName {
+        &self.name
+    }
+
+    pub(crate) fn token_hash(&self) -> &str {
+        &self.token_hash
+    }
+
+    pub(crate) fn scopes(&self) -> &[Scope] {
+        &self.scopes
+    }
+
+    pub(crate) fn created_at(&self) -> &DateTime {
+        &self.created_at
+    }
+
+    pub(crate) fn expires_at(&self) -> Option<&DateTime> {
+        self.expires_at.as_ref()
+    }
+
+    pub(crate) fn last_used_at(&self) -> Option<&DateTime> {
+        self.last_used_at.as_ref()
+    }
+
+    pub(crate) fn revoked_at(&self) -> Option<&DateTime> {
+        self.revoked_at.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_token(expires_at: Option<DateTime>) -> ApiToken {
+        ApiToken::new(
+            Identification::new(),
+            ApiTokenName::try_from("ci".to_string()).unwrap(),
+            "hash".to_string(),
+            vec![Scope::PostsWrite],
+            expires_at,
+        )
+    }
+
+    #[test]
+    fn revoked_token_is_not_active() {
+        let mut token = create_token(None);
+        assert!(token.is_active());
+        token.revoke();
+        assert!(!token.is_active());
+    }
+
+    #[test]
+    fn mark_used_skips_recent_uses() {
+        let mut token = create_token(None);
+        assert!(token.is_use_due());
+        assert!(token.mark_used());
+        assert!(!token.is_use_due());
+        assert!(!token.mark_used());
+
+        let hour_ago = chrono::Utc::now() - chrono::Duration::hours(1);
+        token.last_used_at = Some(DateTime::from(hour_ago));
+        assert!(token.mark_used());
+    }
+
+    #[test]
+    fn expired_token_is_not_active() {
+        let expired_at = chrono::Utc::now() - chrono::Duration::minutes(1);
+        let token = create_token(Some(DateTime::from(expired_at)));
+        assert!(!token.is_active());
+    }
+}
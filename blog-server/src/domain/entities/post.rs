@@ -1,6 +1,6 @@
 use crate::domain::value_objects::{Content, DateTime, Identification, Title};
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub(crate) struct Post {
     id: Identification,
     title: Title,
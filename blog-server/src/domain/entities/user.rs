@@ -1,4 +1,6 @@
-use crate::domain::value_objects::{DateTime, Email, Identification, PasswordHash, UserName};
+use crate::domain::value_objects::{
+    DateTime, Email, Identification, PasswordHash, TotpSecret, UserName,
+};
 
 #[derive(Debug, PartialEq)]
 pub(crate) struct User {
@@ -7,6 +9,9 @@ pub(crate) struct User {
     email: Email,
     password_hash: PasswordHash,
     created_at: DateTime,
+    totp_secret: Option<TotpSecret>,
+    totp_enabled: bool,
+    totp_last_step: Option<u64>,
 }
 
 impl User {
@@ -17,15 +22,22 @@ impl User {
             email,
             password_hash,
             created_at: DateTime::now(),
+            totp_secret: None,
+            totp_enabled: false,
+            totp_last_step: None,
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn restore(
         id: Identification,
         username: UserName,
         email: Email,
         password_hash: PasswordHash,
         created_at: DateTime,
+        totp_secret: Option<TotpSecret>,
+        totp_enabled: bool,
+        totp_last_step: Option<u64>,
     ) -> Self {
         Self {
             id,
@@ -33,9 +45,35 @@ impl User {
             email,
             password_hash,
             created_at,
+            totp_secret,
+            totp_enabled,
+            totp_last_step,
         }
     }
 
+    pub(crate) fn enroll_totp(&mut self, secret: TotpSecret) {
+        self.totp_secret = Some(secret);
+        self.totp_enabled = false;
+        self.totp_last_step = None;
+    }
+
+    /// Records the time step of an accepted TOTP code. Fails for a step that is not later than
+    /// the last accepted one, so that a code cannot be used twice.
+    pub(crate) fn accept_totp_step(&mut self, step: u64) -> bool {
+        if self
+            .totp_last_step
+            .is_some_and(|last_step| step <= last_step)
+        {
+            return false;
+        }
+        self.totp_last_step = Some(step);
+        true
+    }
+
+    pub(crate) fn enable_totp(&mut self) {
+        self.totp_enabled = self.totp_secret.is_some();
+    }
+
     pub(crate) fn id(&self) -> &Identification {
         &self.id
     }
@@ -55,4 +93,38 @@ impl User {
     pub(crate) fn created_at(&self) -> &DateTime {
         &self.created_at
     }
+
+    pub(crate) fn totp_secret(&self) -> Option<&TotpSecret> {
+        self.totp_secret.as_ref()
+    }
+
+    pub(crate) fn is_totp_enabled(&self) -> bool {
+        self.totp_enabled
+    }
+
+    pub(crate) fn totp_last_step(&self) -> Option<u64> {
+        self.totp_last_step
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use secrecy::SecretString;
+
+    #[test]
+    fn accept_totp_step_refuses_used_steps() {
+        let mut user = User::new(
+            UserName::try_from("username".to_string()).unwrap(),
+            Email::try_from("user@example.com".to_string()).unwrap(),
+            PasswordHash::from(SecretString::from("hash")),
+        );
+        assert!(user.accept_totp_step(10));
+        assert!(!user.accept_totp_step(10));
+        assert!(!user.accept_totp_step(9));
+        assert!(user.accept_totp_step(11));
+
+        user.enroll_totp(TotpSecret::from(SecretString::from("secret")));
+        assert!(user.accept_totp_step(1));
+    }
 }
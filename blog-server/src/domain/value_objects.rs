@@ -1,17 +1,27 @@
+mod api_token_name;
 mod content;
 mod date_time;
 mod email;
+mod external_identity;
+mod field_violation;
 mod identification;
 mod password;
 mod password_hash;
+mod scope;
 mod title;
+mod totp_secret;
 mod user_name;
 
+pub(crate) use api_token_name::*;
 pub(crate) use content::*;
 pub(crate) use date_time::*;
 pub(crate) use email::*;
+pub(crate) use external_identity::*;
+pub(crate) use field_violation::*;
 pub(crate) use identification::*;
 pub(crate) use password::*;
 pub(crate) use password_hash::*;
+pub(crate) use scope::*;
 pub(crate) use title::*;
+pub(crate) use totp_secret::*;
 pub(crate) use user_name::*;
@@ -0,0 +1,75 @@
+use crate::domain::value_objects::FieldViolation;
+const API_TOKEN_NAME_MAX_LENGTH: usize = 100;
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct ApiTokenName(String);
+
+impl AsRef<str> for ApiTokenName {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl TryFrom<String> for ApiTokenName {
+    type Error = ApiTokenNameError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let value = value.trim().to_string();
+        if value.is_empty() {
+            return Err(ApiTokenNameError::Empty);
+        }
+        if value.len() > API_TOKEN_NAME_MAX_LENGTH {
+            return Err(ApiTokenNameError::TooLong);
+        }
+        Ok(ApiTokenName(value))
+    }
+}
+
+#[derive(Debug, PartialEq, thiserror::Error)]
+pub(crate) enum ApiTokenNameError {
+    #[error("token name is empty")]
+    Empty,
+    #[error("token name is too long")]
+    TooLong,
+}
+
+impl From<&ApiTokenNameError> for FieldViolation {
+    fn from(err: &ApiTokenNameError) -> Self {
+        let code = match err {
+            ApiTokenNameError::Empty => "NAME_EMPTY",
+            ApiTokenNameError::TooLong => "NAME_TOO_LONG",
+        };
+        FieldViolation::new("name", code, err.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn create_valid_name() {
+        let name = ApiTokenName::try_from(" ci ".to_string()).unwrap();
+        assert_eq!(name.as_ref(), "ci");
+    }
+
+    #[test]
+    fn create_invalid_name() {
+        assert_eq!(
+            ApiTokenName::try_from("  ".to_string()),
+            Err(ApiTokenNameError::Empty)
+        );
+        assert_eq!(
+            ApiTokenName::try_from("a".repeat(API_TOKEN_NAME_MAX_LENGTH + 1)),
+            Err(ApiTokenNameError::TooLong)
+        );
+    }
+
+    #[test]
+    fn invalid_name_is_a_name_violation() {
+        let violation = FieldViolation::from(&ApiTokenNameError::TooLong);
+
+        assert_eq!(violation.field(), "name");
+        assert_eq!(violation.code(), "NAME_TOO_LONG");
+    }
+}
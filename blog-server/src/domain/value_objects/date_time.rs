@@ -1,4 +1,4 @@
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub(crate) struct DateTime(chrono::DateTime<chrono::Utc>);
 
 impl DateTime {
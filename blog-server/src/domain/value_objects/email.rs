@@ -1,3 +1,4 @@
+use crate::domain::value_objects::FieldViolation;
 use regex::Regex;
 use std::sync::OnceLock;
 
@@ -43,6 +44,17 @@ pub(crate) enum EmailError {
     Invalid,
 }
 
+impl From<&EmailError> for FieldViolation {
+    fn from(err: &EmailError) -> Self {
+        let code = match err {
+            EmailError::Empty => "EMAIL_EMPTY",
+            EmailError::Regex(_) => "EMAIL_INVALID",
+            EmailError::Invalid => "EMAIL_INVALID",
+        };
+        FieldViolation::new("email", code, err.to_string())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,20 @@
+/// Identity of a user at an external OpenID Connect provider.
+#[derive(Debug, PartialEq)]
+pub(crate) struct ExternalIdentity {
+    issuer: String,
+    subject: String,
+}
+
+impl ExternalIdentity {
+    pub(crate) fn new(issuer: String, subject: String) -> Self {
+        Self { issuer, subject }
+    }
+
+    pub(crate) fn issuer(&self) -> &str {
+        &self.issuer
+    }
+
+    pub(crate) fn subject(&self) -> &str {
+        &self.subject
+    }
+}
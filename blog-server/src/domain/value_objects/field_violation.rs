@@ -0,0 +1,47 @@
+use std::fmt::{Display, Formatter};
+
+/// Machine-readable reason why a single field was rejected by validation.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct FieldViolation {
+    field: &'static str,
+    code: &'static str,
+    description: String,
+}
+
+impl FieldViolation {
+    pub(crate) fn new(field: &'static str, code: &'static str, description: String) -> Self {
+        Self {
+            field,
+            code,
+            description,
+        }
+    }
+
+    pub(crate) fn field(&self) -> &'static str {
+        self.field
+    }
+
+    /// SCREAMING_SNAKE_CASE identifier of the violated rule, e.g. `TITLE_TOO_LONG`.
+    pub(crate) fn code(&self) -> &'static str {
+        self.code
+    }
+
+    pub(crate) fn description(&self) -> &str {
+        &self.description
+    }
+}
+
+impl Display for FieldViolation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.description)
+    }
+}
+
+/// Joins the descriptions of several violations into one message, e.g. for a status line.
+pub(crate) fn describe_violations(violations: &[FieldViolation]) -> String {
+    violations
+        .iter()
+        .map(FieldViolation::description)
+        .collect::<Vec<_>>()
+        .join("; ")
+}
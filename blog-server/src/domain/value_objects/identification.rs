@@ -1,6 +1,6 @@
 use uuid::Uuid;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub(crate) struct Identification(Uuid);
 
 impl Identification {
@@ -1,3 +1,4 @@
+use crate::domain::value_objects::FieldViolation;
 use secrecy::{ExposeSecret, SecretString};
 
 const MIN_PASSWORD_LENGTH: usize = 8;
@@ -63,6 +64,17 @@ pub(crate) enum PasswordError {
     Invalid,
 }
 
+impl From<&PasswordError> for FieldViolation {
+    fn from(err: &PasswordError) -> Self {
+        let code = match err {
+            PasswordError::Empty => "PASSWORD_EMPTY",
+            PasswordError::TooShort => "PASSWORD_TOO_SHORT",
+            PasswordError::Invalid => "PASSWORD_TOO_WEAK",
+        };
+        FieldViolation::new("password", code, err.to_string())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,127 @@
+use crate::domain::value_objects::FieldViolation;
+
+const POSTS_READ: &str = "posts:read";
+const POSTS_WRITE: &str = "posts:write";
+const POSTS_DELETE: &str = "posts:delete";
+const ACCOUNT_MANAGE: &str = "account:manage";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum Scope {
+    PostsRead,
+    PostsWrite,
+    PostsDelete,
+    AccountManage,
+}
+
+impl Scope {
+    pub(crate) fn all() -> Vec<Scope> {
+        vec![
+            Scope::PostsRead,
+            Scope::PostsWrite,
+            Scope::PostsDelete,
+            Scope::AccountManage,
+        ]
+    }
+
+    /// Parses requested scopes, granting every scope when none are requested.
+    pub(crate) fn parse_requested(scopes: Option<&[String]>) -> Result<Vec<Scope>, ScopeError> {
+        match scopes {
+            None | Some([]) => Ok(Scope::all()),
+            Some(scopes) => {
+                let mut parsed = Vec::with_capacity(scopes.len());
+                for scope in scopes {
+                    let scope = Scope::try_from(scope.as_str())?;
+                    if !parsed.contains(&scope) {
+                        parsed.push(scope);
+                    }
+                }
+                Ok(parsed)
+            }
+        }
+    }
+}
+
+impl AsRef<str> for Scope {
+    fn as_ref(&self) -> &str {
+        match self {
+            Scope::PostsRead => POSTS_READ,
+            Scope::PostsWrite => POSTS_WRITE,
+            Scope::PostsDelete => POSTS_DELETE,
+            Scope::AccountManage => ACCOUNT_MANAGE,
+        }
+    }
+}
+
+impl TryFrom<&str> for Scope {
+    type Error = ScopeError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            POSTS_READ => Ok(Scope::PostsRead),
+            POSTS_WRITE => Ok(Scope::PostsWrite),
+            POSTS_DELETE => Ok(Scope::PostsDelete),
+            ACCOUNT_MANAGE => Ok(Scope::AccountManage),
+            _ => Err(ScopeError::Unknown(value.to_string())),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, thiserror::Error)]
+pub(crate) enum ScopeError {
+    #[error("unknown scope: {0}")]
+    Unknown(String),
+    #[error("at least one scope is required")]
+    Empty,
+}
+
+impl From<&ScopeError> for FieldViolation {
+    fn from(err: &ScopeError) -> Self {
+        let code = match err {
+            ScopeError::Unknown(_) => "SCOPE_UNKNOWN",
+            ScopeError::Empty => "SCOPES_EMPTY",
+        };
+        FieldViolation::new("scopes", code, err.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_known_scopes() {
+        for scope in Scope::all() {
+            assert_eq!(Scope::try_from(scope.as_ref()), Ok(scope));
+        }
+    }
+
+    #[test]
+    fn parse_requested_scopes() {
+        assert_eq!(Scope::parse_requested(None), Ok(Scope::all()));
+        assert_eq!(Scope::parse_requested(Some(&[])), Ok(Scope::all()));
+        assert_eq!(
+            Scope::parse_requested(Some(&[
+                "posts:write".to_string(),
+                "posts:write".to_string()
+            ])),
+            Ok(vec![Scope::PostsWrite])
+        );
+        assert!(Scope::parse_requested(Some(&["unknown".to_string()])).is_err());
+    }
+
+    #[test]
+    fn parse_unknown_scope() {
+        assert_eq!(
+            Scope::try_from("posts:admin"),
+            Err(ScopeError::Unknown("posts:admin".to_string()))
+        );
+    }
+
+    #[test]
+    fn unknown_scope_is_a_scopes_violation() {
+        let violation = FieldViolation::from(&ScopeError::Unknown("posts:admin".to_string()));
+
+        assert_eq!(violation.field(), "scopes");
+        assert_eq!(violation.code(), "SCOPE_UNKNOWN");
+    }
+}
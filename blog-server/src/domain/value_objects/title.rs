@@ -1,6 +1,7 @@
+use crate::domain::value_objects::FieldViolation;
 const TITLE_MAX_LENGTH: usize = 100;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub(crate) struct Title(String);
 
 impl AsRef<str> for Title {
@@ -31,6 +32,16 @@ pub(crate) enum TitleError {
     TooLong,
 }
 
+impl From<&TitleError> for FieldViolation {
+    fn from(err: &TitleError) -> Self {
+        let code = match err {
+            TitleError::Empty => "TITLE_EMPTY",
+            TitleError::TooLong => "TITLE_TOO_LONG",
+        };
+        FieldViolation::new("title", code, err.to_string())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -48,4 +59,12 @@ mod tests {
         let long_title = "a".repeat(TITLE_MAX_LENGTH + 1);
         assert_eq!(Title::try_from(long_title), Err(TitleError::TooLong));
     }
+
+    #[test]
+    fn title_error_describes_violated_field() {
+        let violation = FieldViolation::from(&TitleError::TooLong);
+        assert_eq!(violation.field(), "title");
+        assert_eq!(violation.code(), "TITLE_TOO_LONG");
+        assert_eq!(violation.description(), "title is too long");
+    }
 }
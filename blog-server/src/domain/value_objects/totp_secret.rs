@@ -0,0 +1,22 @@
+use secrecy::{ExposeSecret, SecretString};
+
+#[derive(Debug)]
+pub(crate) struct TotpSecret(SecretString);
+
+impl AsRef<SecretString> for TotpSecret {
+    fn as_ref(&self) -> &SecretString {
+        &self.0
+    }
+}
+
+impl From<SecretString> for TotpSecret {
+    fn from(value: SecretString) -> Self {
+        Self(value)
+    }
+}
+
+impl PartialEq for TotpSecret {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.expose_secret() == other.0.expose_secret()
+    }
+}
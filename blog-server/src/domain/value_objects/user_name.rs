@@ -1,5 +1,6 @@
-const USERNAME_MIN_LENGTH: usize = 5;
-const USERNAME_MAX_LENGTH: usize = 20;
+use crate::domain::value_objects::FieldViolation;
+pub(crate) const USERNAME_MIN_LENGTH: usize = 5;
+pub(crate) const USERNAME_MAX_LENGTH: usize = 20;
 
 #[derive(Debug, PartialEq)]
 pub(crate) struct UserName(String);
@@ -37,6 +38,17 @@ pub(crate) enum UserNameError {
     TooLong,
 }
 
+impl From<&UserNameError> for FieldViolation {
+    fn from(err: &UserNameError) -> Self {
+        let code = match err {
+            UserNameError::Empty => "USERNAME_EMPTY",
+            UserNameError::TooShort => "USERNAME_TOO_SHORT",
+            UserNameError::TooLong => "USERNAME_TOO_LONG",
+        };
+        FieldViolation::new("username", code, err.to_string())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
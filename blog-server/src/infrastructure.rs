@@ -1,7 +1,31 @@
+mod allowed_origins;
+mod challenge_attempts;
+mod config_reload;
 mod dal;
 mod jwt;
+#[cfg(unix)]
+mod listeners;
+mod metrics;
+mod oidc;
+mod post_cache;
+mod post_events;
+mod rate_limiter;
+mod tls;
+mod totp_secret_cipher;
 mod tracing;
 
+pub(crate) use allowed_origins::*;
+pub(crate) use challenge_attempts::*;
+pub(crate) use config_reload::*;
 pub(crate) use dal::*;
 pub(crate) use jwt::*;
+#[cfg(unix)]
+pub(crate) use listeners::*;
+pub(crate) use metrics::*;
+pub(crate) use oidc::*;
+pub(crate) use post_cache::*;
+pub(crate) use post_events::*;
+pub(crate) use rate_limiter::*;
+pub(crate) use tls::*;
+pub(crate) use totp_secret_cipher::*;
 pub(crate) use tracing::*;
@@ -0,0 +1,26 @@
+use std::sync::RwLock;
+
+/// Origins of the web clients allowed by CORS; replaced when the configuration is reloaded.
+pub(crate) struct AllowedOrigins {
+    origins: RwLock<Vec<String>>,
+}
+
+impl AllowedOrigins {
+    pub(crate) fn new(origins: &[String]) -> Self {
+        Self {
+            origins: RwLock::new(origins.to_vec()),
+        }
+    }
+
+    pub(crate) fn is_allowed(&self, origin: &[u8]) -> bool {
+        self.origins
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .any(|allowed| allowed.as_bytes() == origin)
+    }
+
+    pub(crate) fn replace(&self, origins: &[String]) {
+        *self.origins.write().unwrap_or_else(|e| e.into_inner()) = origins.to_vec();
+    }
+}
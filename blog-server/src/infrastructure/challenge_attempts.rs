@@ -0,0 +1,56 @@
+use hashlink::LruCache;
+use std::sync::Mutex;
+use uuid::Uuid;
+
+/// Codes that may be tried per two-factor challenge before the user has to log in again.
+pub(crate) const MAX_CHALLENGE_ATTEMPTS: u32 = 5;
+/// Challenges expire after five minutes, so this covers a login rate far above the limits.
+const MAX_TRACKED_CHALLENGES: usize = 100_000;
+
+/// Counts the verification attempts per challenge token on this server, so that a challenge
+/// cannot be used to guess codes. A challenge that is evicted starts over, which still takes a
+/// fresh password login per handful of guesses.
+pub(crate) struct ChallengeAttempts {
+    attempts: Mutex<LruCache<Uuid, u32>>,
+}
+
+impl ChallengeAttempts {
+    pub(crate) fn new() -> Self {
+        Self::with_capacity(MAX_TRACKED_CHALLENGES)
+    }
+
+    fn with_capacity(capacity: usize) -> Self {
+        Self {
+            attempts: Mutex::new(LruCache::new(capacity)),
+        }
+    }
+
+    /// Records an attempt and tells whether the challenge still allows it.
+    pub(crate) fn try_attempt(&self, challenge_id: Uuid) -> bool {
+        let mut attempts = self.attempts.lock().unwrap_or_else(|e| e.into_inner());
+        let count = attempts.get(&challenge_id).copied().unwrap_or(0);
+        if count >= MAX_CHALLENGE_ATTEMPTS {
+            return false;
+        }
+        attempts.insert(challenge_id, count + 1);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn try_attempt_caps_attempts_per_challenge() {
+        let attempts = ChallengeAttempts::with_capacity(10);
+        let challenge_id = Uuid::new_v4();
+
+        for _ in 0..MAX_CHALLENGE_ATTEMPTS {
+            assert!(attempts.try_attempt(challenge_id));
+        }
+
+        assert!(!attempts.try_attempt(challenge_id));
+        assert!(attempts.try_attempt(Uuid::new_v4()));
+    }
+}
@@ -0,0 +1,169 @@
+use crate::configuration::Configuration;
+use crate::infrastructure::{AllowedOrigins, LogFilter, RateLimiter};
+use notify::event::ModifyKind;
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+/// Editors save a file in several steps; changes within this window trigger a single reload.
+const RELOAD_DEBOUNCE: Duration = Duration::from_millis(500);
+const CONFIGURATION_EXTENSION: &str = "yaml";
+
+/// Applies the settings that can change while the server runs: the log level, the CORS white
+/// list and the rate limits. Changes to any other setting are logged and ignored.
+pub(crate) struct ConfigurationReloader {
+    active: Configuration,
+    log_filter: LogFilter,
+    allowed_origins: Arc<AllowedOrigins>,
+    rate_limiter: Arc<RateLimiter>,
+}
+
+impl ConfigurationReloader {
+    pub(crate) fn new(
+        active: Configuration,
+        log_filter: LogFilter,
+        allowed_origins: Arc<AllowedOrigins>,
+        rate_limiter: Arc<RateLimiter>,
+    ) -> Self {
+        Self {
+            active,
+            log_filter,
+            allowed_origins,
+            rate_limiter,
+        }
+    }
+
+    fn reload(&mut self) {
+        match Configuration::read_configuration() {
+            Ok(config) => self.apply(config),
+            Err(err) => tracing::error!(
+                "Failed to reload the configuration, keeping the current one: {:#}",
+                err
+            ),
+        }
+    }
+
+    fn apply(&mut self, config: Configuration) {
+        let restart_required = self.active.restart_required_changes(&config);
+        if !restart_required.is_empty() {
+            tracing::warn!(
+                "Changes to {} only take effect after a restart",
+                restart_required.join(", ")
+            );
+        }
+
+        let mut applied = Vec::new();
+        let active_server = self.active.get_server_configuration();
+        let server = config.get_server_configuration();
+        if active_server.get_log_level() != server.get_log_level() {
+            match self.log_filter.set(server.get_log_level()) {
+                Ok(true) => applied.push("server.log_level"),
+                Ok(false) => tracing::warn!("Ignoring server.log_level because RUST_LOG is set"),
+                Err(err) => tracing::error!("Failed to apply server.log_level: {}", err),
+            }
+        }
+        if active_server.get_white_list() != server.get_white_list() {
+            self.allowed_origins.replace(server.get_white_list());
+            applied.push("server.white_list");
+        }
+        if self.active.get_rate_limit_configuration() != config.get_rate_limit_configuration() {
+            self.rate_limiter
+                .reconfigure(config.get_rate_limit_configuration());
+            applied.push("rate_limit");
+        }
+        self.active.take_reloadable(&config);
+
+        if !applied.is_empty() {
+            tracing::info!("Applied changes to {}", applied.join(", "));
+        }
+    }
+}
+
+/// Reloads the configuration on SIGHUP and whenever a configuration file changes.
+pub(crate) fn watch_configuration(
+    mut reloader: ConfigurationReloader,
+) -> anyhow::Result<JoinHandle<()>> {
+    let (sender, mut receiver) = mpsc::channel(1);
+    let file_sender = sender.clone();
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<Event>| {
+        match event {
+            Ok(event) if is_configuration_change(&event) => {
+                // A reload is already pending when the channel is full.
+                let _ = file_sender.try_send(());
+            }
+            Ok(_) => {}
+            Err(err) => tracing::warn!("Failed to watch the configuration: {}", err),
+        }
+    })?;
+    watcher.watch(&Configuration::config_dir()?, RecursiveMode::NonRecursive)?;
+
+    #[cfg(unix)]
+    {
+        let mut hangup = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup())?;
+        tokio::spawn(async move {
+            while hangup.recv().await.is_some() {
+                tracing::info!("Received SIGHUP, reloading the configuration");
+                let _ = sender.try_send(());
+            }
+        });
+    }
+    #[cfg(not(unix))]
+    drop(sender);
+
+    Ok(tokio::spawn(async move {
+        // Watching stops when the watcher is dropped.
+        let _watcher = watcher;
+        while receiver.recv().await.is_some() {
+            tokio::time::sleep(RELOAD_DEBOUNCE).await;
+            while receiver.try_recv().is_ok() {}
+            reloader.reload();
+        }
+    }))
+}
+
+/// Only writes to configuration files count; reading them for a reload generates access events
+/// that must not trigger another one.
+fn is_configuration_change(event: &Event) -> bool {
+    matches!(
+        event.kind,
+        EventKind::Create(_)
+            | EventKind::Remove(_)
+            | EventKind::Modify(ModifyKind::Data(_) | ModifyKind::Name(_) | ModifyKind::Any)
+    ) && event.paths.iter().any(|path| {
+        path.extension()
+            .is_some_and(|extension| extension == CONFIGURATION_EXTENSION)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::event::{AccessKind, DataChange, MetadataKind};
+    use std::path::PathBuf;
+
+    fn event(kind: EventKind, path: &str) -> Event {
+        Event::new(kind).add_path(PathBuf::from(path))
+    }
+
+    #[test]
+    fn is_configuration_change_ignores_reads_and_other_files() {
+        assert!(is_configuration_change(&event(
+            EventKind::Modify(ModifyKind::Data(DataChange::Content)),
+            "config/base.yaml"
+        )));
+        assert!(!is_configuration_change(&event(
+            EventKind::Access(AccessKind::Read),
+            "config/base.yaml"
+        )));
+        assert!(!is_configuration_change(&event(
+            EventKind::Modify(ModifyKind::Metadata(MetadataKind::AccessTime)),
+            "config/base.yaml"
+        )));
+        assert!(!is_configuration_change(&event(
+            EventKind::Modify(ModifyKind::Data(DataChange::Content)),
+            "config/.base.yaml.swp"
+        )));
+    }
+}
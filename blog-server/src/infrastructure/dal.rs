@@ -1,5 +1,19 @@
+mod cached_post_repository;
+mod cached_unit_of_work;
+mod database_error;
+mod database_pools;
+mod postgres_api_token_repository;
+mod postgres_health_check;
 mod postgres_post_repository;
+mod postgres_unit_of_work;
 mod postgres_user_repository;
 
+pub(crate) use cached_post_repository::*;
+pub(crate) use cached_unit_of_work::*;
+pub(crate) use database_error::*;
+pub(crate) use database_pools::*;
+pub(crate) use postgres_api_token_repository::*;
+pub(crate) use postgres_health_check::*;
 pub(crate) use postgres_post_repository::*;
+pub(crate) use postgres_unit_of_work::*;
 pub(crate) use postgres_user_repository::*;
@@ -0,0 +1,244 @@
+use crate::application::contracts::PostRepository;
+use crate::domain::entities::Post;
+use crate::domain::value_objects::Identification;
+use crate::infrastructure::PostCacheStore;
+use async_trait::async_trait;
+use std::sync::Arc;
+
+/// Caches the results of [`PostRepository::get`] and [`PostRepository::list`] of another
+/// repository and invalidates them on every write through this repository. Updates and deletes
+/// go through a [`CachedUnitOfWork`](crate::infrastructure::CachedUnitOfWork) sharing the cache.
+///
+/// Cache misses are read from `inner`, so they may be served by a read replica. A read that
+/// overlaps an invalidation is not cached; a replica that lags behind may still fill an entry
+/// with a post as old as its lag, which expires like any other entry. Reads that must see the
+/// latest writes go through the unit of work, which uses the primary.
+///
+/// Writes made by other servers are only seen once their entries expire, unless the store is
+/// shared.
+pub(crate) struct CachedPostRepository {
+    inner: Arc<dyn PostRepository>,
+    cache: Arc<dyn PostCacheStore>,
+}
+
+impl CachedPostRepository {
+    pub(crate) fn new(inner: Arc<dyn PostRepository>, cache: Arc<dyn PostCacheStore>) -> Self {
+        Self { inner, cache }
+    }
+}
+
+#[async_trait]
+impl PostRepository for CachedPostRepository {
+    async fn create(&self, post: &Post) -> Result<(), anyhow::Error> {
+        let result = self.inner.create(post).await;
+        // A failed write may still have been committed, so the cache is invalidated either way.
+        self.cache.clear_pages().await;
+        result
+    }
+
+    async fn create_many(&self, posts: &[Post]) -> Result<(), anyhow::Error> {
+        let result = self.inner.create_many(posts).await;
+        self.cache.clear_pages().await;
+        result
+    }
+
+    #[tracing::instrument(name = "Get post from the cache", skip(self))]
+    async fn get(&self, id: &Identification) -> Result<Option<Post>, anyhow::Error> {
+        if let Some(post) = self.cache.get_post(id).await {
+            return Ok(Some(post));
+        }
+        let generation = self.cache.generation().await;
+        let post = self.inner.get(id).await?;
+        if let Some(post) = &post {
+            self.cache.insert_post(post.clone(), generation).await;
+        }
+        Ok(post)
+    }
+
+    async fn get_many(&self, ids: &[Identification]) -> Result<Vec<Post>, anyhow::Error> {
+        self.inner.get_many(ids).await
+    }
+
+    #[tracing::instrument(name = "Get posts list from the cache", skip(self))]
+    async fn list(&self, limit: usize, offset: usize) -> Result<(Vec<Post>, usize), anyhow::Error> {
+        if let Some(page) = self.cache.get_page(limit, offset).await {
+            return Ok(page);
+        }
+        let generation = self.cache.generation().await;
+        let page = self.inner.list(limit, offset).await?;
+        self.cache
+            .insert_page(limit, offset, page.clone(), generation)
+            .await;
+        Ok(page)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::value_objects::{Content, Title};
+    use crate::infrastructure::{DatabasePools, InMemoryPostCacheStore, PostgresPostRepository};
+    use sqlx::PgPool;
+    use sqlx::postgres::PgPoolOptions;
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    /// Serves one post. With `concurrent_update`, every read also replaces the post and
+    /// invalidates it in the cache, like a write committed while the read is in flight.
+    struct StubPostRepository {
+        post: Mutex<Post>,
+        concurrent_update: Option<(Post, Arc<dyn PostCacheStore>)>,
+    }
+
+    impl StubPostRepository {
+        fn new(post: Post) -> Self {
+            Self {
+                post: Mutex::new(post),
+                concurrent_update: None,
+            }
+        }
+
+        async fn read(&self) -> Post {
+            let post = self.post.lock().unwrap().clone();
+            if let Some((updated, cache)) = &self.concurrent_update {
+                *self.post.lock().unwrap() = updated.clone();
+                cache.remove_post(updated.id()).await;
+                cache.clear_pages().await;
+            }
+            post
+        }
+    }
+
+    #[async_trait]
+    impl PostRepository for StubPostRepository {
+        async fn create(&self, _: &Post) -> Result<(), anyhow::Error> {
+            unimplemented!()
+        }
+
+        async fn create_many(&self, _: &[Post]) -> Result<(), anyhow::Error> {
+            unimplemented!()
+        }
+
+        async fn get(&self, _: &Identification) -> Result<Option<Post>, anyhow::Error> {
+            Ok(Some(self.read().await))
+        }
+
+        async fn get_many(&self, _: &[Identification]) -> Result<Vec<Post>, anyhow::Error> {
+            unimplemented!()
+        }
+
+        async fn list(&self, _: usize, _: usize) -> Result<(Vec<Post>, usize), anyhow::Error> {
+            Ok((vec![self.read().await], 1))
+        }
+    }
+
+    fn post(title: &str) -> Post {
+        Post::new(
+            Title::try_from(title.to_string()).unwrap(),
+            Content::from("content".to_string()),
+            Identification::new(),
+        )
+    }
+
+    fn updated(post: &Post, title: &str) -> Post {
+        Post::restore(
+            post.id().clone(),
+            Title::try_from(title.to_string()).unwrap(),
+            post.content().clone(),
+            post.author_id().clone(),
+            post.created_at().clone(),
+            post.updated_at().clone(),
+        )
+    }
+
+    fn cache() -> Arc<dyn PostCacheStore> {
+        Arc::new(InMemoryPostCacheStore::new(10, Duration::from_secs(60)))
+    }
+
+    #[tokio::test]
+    async fn remove_post_makes_get_read_the_update() {
+        let current = post("current");
+        let inner = Arc::new(StubPostRepository::new(current.clone()));
+        let cache = cache();
+        let repository = CachedPostRepository::new(inner.clone(), cache.clone());
+        repository.get(current.id()).await.unwrap();
+        repository.list(10, 0).await.unwrap();
+
+        let update = updated(&current, "update");
+        *inner.post.lock().unwrap() = update.clone();
+        cache.remove_post(current.id()).await;
+        cache.clear_pages().await;
+
+        assert_eq!(
+            repository.get(current.id()).await.unwrap(),
+            Some(update.clone())
+        );
+        assert_eq!(repository.list(10, 0).await.unwrap(), (vec![update], 1));
+    }
+
+    #[tokio::test]
+    async fn reads_that_overlap_an_invalidation_are_not_cached() {
+        let current = post("current");
+        let update = updated(&current, "update");
+        let cache = cache();
+        let inner = Arc::new(StubPostRepository {
+            post: Mutex::new(current.clone()),
+            concurrent_update: Some((update.clone(), cache.clone())),
+        });
+        let repository = CachedPostRepository::new(inner, cache.clone());
+
+        assert_eq!(
+            repository.get(current.id()).await.unwrap(),
+            Some(current.clone())
+        );
+        repository.list(10, 0).await.unwrap();
+
+        assert!(cache.get_post(current.id()).await.is_none());
+        assert!(cache.get_page(10, 0).await.is_none());
+        assert_eq!(repository.get(current.id()).await.unwrap(), Some(update));
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    #[ignore = "needs a Postgres server in DATABASE_URL"]
+    async fn cache_misses_are_read_from_the_replica(pool: PgPool) {
+        let author_id = Identification::new();
+        sqlx::query(
+            "INSERT INTO users (id, username, email, password_hash, created_at) \
+             VALUES ($1, 'author', 'author@example.com', '', now())",
+        )
+        .bind(author_id.as_ref())
+        .execute(&pool)
+        .await
+        .unwrap();
+        let post = Post::new(
+            Title::try_from("replicated".to_string()).unwrap(),
+            Content::from("content".to_string()),
+            author_id,
+        );
+        PostgresPostRepository::new(Arc::new(DatabasePools::new(
+            Arc::new(pool.clone()),
+            Vec::new(),
+        )))
+        .create(&post)
+        .await
+        .unwrap();
+        // Every query on the primary fails, so the reads can only succeed on the replica.
+        let unreachable_primary = PgPoolOptions::new()
+            .acquire_timeout(Duration::from_millis(100))
+            .connect_lazy("postgres://postgres@127.0.0.1:1/primary")
+            .unwrap();
+        let repository = CachedPostRepository::new(
+            Arc::new(PostgresPostRepository::new(Arc::new(DatabasePools::new(
+                Arc::new(unreachable_primary),
+                vec![pool],
+            )))),
+            cache(),
+        );
+
+        let found = repository.get(post.id()).await.unwrap().unwrap();
+        assert_eq!(found.id(), post.id());
+        let (posts, total) = repository.list(10, 0).await.unwrap();
+        assert_eq!(total, 1);
+        assert_eq!(posts[0].id(), post.id());
+    }
+}
@@ -0,0 +1,96 @@
+use crate::application::contracts::{
+    ApiTokenTransactionRepository, ExternalIdentityTransactionRepository,
+    PostTransactionRepository, Transaction, TwoFactorTransactionRepository, UnitOfWork,
+    UserTransactionRepository,
+};
+use crate::domain::entities::Post;
+use crate::domain::value_objects::Identification;
+use crate::infrastructure::PostCacheStore;
+use async_trait::async_trait;
+use std::sync::Arc;
+
+/// Invalidates the posts written by the transactions of another [`UnitOfWork`] in the cache
+/// used by [`CachedPostRepository`](crate::infrastructure::CachedPostRepository).
+pub(crate) struct CachedUnitOfWork {
+    inner: Arc<dyn UnitOfWork>,
+    cache: Arc<dyn PostCacheStore>,
+}
+
+impl CachedUnitOfWork {
+    pub(crate) fn new(inner: Arc<dyn UnitOfWork>, cache: Arc<dyn PostCacheStore>) -> Self {
+        Self { inner, cache }
+    }
+}
+
+#[async_trait]
+impl UnitOfWork for CachedUnitOfWork {
+    async fn begin(&self) -> Result<Box<dyn Transaction>, anyhow::Error> {
+        Ok(Box::new(CachedTransaction {
+            inner: self.inner.begin().await?,
+            cache: Arc::clone(&self.cache),
+            written_posts: Vec::new(),
+        }))
+    }
+}
+
+struct CachedTransaction {
+    inner: Box<dyn Transaction>,
+    cache: Arc<dyn PostCacheStore>,
+    written_posts: Vec<Identification>,
+}
+
+#[async_trait]
+impl Transaction for CachedTransaction {
+    fn users(&mut self) -> &mut dyn UserTransactionRepository {
+        self.inner.users()
+    }
+
+    fn two_factor(&mut self) -> &mut dyn TwoFactorTransactionRepository {
+        self.inner.two_factor()
+    }
+
+    fn external_identities(&mut self) -> &mut dyn ExternalIdentityTransactionRepository {
+        self.inner.external_identities()
+    }
+
+    /// Goes through this transaction, which records the written posts.
+    fn posts(&mut self) -> &mut dyn PostTransactionRepository {
+        self
+    }
+
+    fn api_tokens(&mut self) -> &mut dyn ApiTokenTransactionRepository {
+        self.inner.api_tokens()
+    }
+
+    async fn commit(self: Box<Self>) -> Result<(), anyhow::Error> {
+        let result = self.inner.commit().await;
+        // A failed commit may still have been applied, so the cache is invalidated either way.
+        if !self.written_posts.is_empty() {
+            for id in &self.written_posts {
+                self.cache.remove_post(id).await;
+            }
+            self.cache.clear_pages().await;
+        }
+        result
+    }
+}
+
+#[async_trait]
+impl PostTransactionRepository for CachedTransaction {
+    async fn get_post_for_update(
+        &mut self,
+        id: &Identification,
+    ) -> Result<Option<Post>, anyhow::Error> {
+        self.inner.posts().get_post_for_update(id).await
+    }
+
+    async fn update_post(&mut self, post: &Post) -> Result<(), anyhow::Error> {
+        self.written_posts.push(post.id().clone());
+        self.inner.posts().update_post(post).await
+    }
+
+    async fn delete_post(&mut self, id: &Identification) -> Result<(), anyhow::Error> {
+        self.written_posts.push(id.clone());
+        self.inner.posts().delete_post(id).await
+    }
+}
@@ -0,0 +1,24 @@
+use crate::application::contracts::{StorageUnavailable, UniqueViolation};
+
+/// Converts a database error, reporting a timed out pool acquisition as [`StorageUnavailable`]
+/// and a duplicate key as [`UniqueViolation`].
+pub(crate) fn database_error(err: sqlx::Error) -> anyhow::Error {
+    match err {
+        sqlx::Error::PoolTimedOut => anyhow::Error::new(StorageUnavailable),
+        sqlx::Error::Database(err) if err.is_unique_violation() => {
+            anyhow::Error::new(UniqueViolation(err.message().to_string()))
+        }
+        err => err.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn database_error_reports_pool_timeout_as_unavailable() {
+        assert!(database_error(sqlx::Error::PoolTimedOut).is::<StorageUnavailable>());
+        assert!(!database_error(sqlx::Error::RowNotFound).is::<StorageUnavailable>());
+    }
+}
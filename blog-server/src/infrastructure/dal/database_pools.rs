@@ -0,0 +1,126 @@
+use sqlx::PgPool;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// How long a replica that failed to answer is skipped before it is tried again.
+const REPLICA_RETRY_INTERVAL: Duration = Duration::from_secs(30);
+
+/// A read-only query run on whichever pool [`DatabasePools::read`] picks.
+pub(crate) type ReadQuery<'q, T> =
+    Pin<Box<dyn Future<Output = Result<T, sqlx::Error>> + Send + 'q>>;
+
+/// The primary database and its read replicas.
+///
+/// Writes and reads that must see the latest writes go to the primary. Other reads are spread
+/// over the replicas and fall back to the primary while no replica is reachable.
+pub(crate) struct DatabasePools {
+    primary: Arc<PgPool>,
+    replicas: Vec<Replica>,
+    next_replica: AtomicUsize,
+}
+
+struct Replica {
+    pool: PgPool,
+    unavailable_until: Mutex<Option<Instant>>,
+}
+
+impl DatabasePools {
+    pub(crate) fn new(primary: Arc<PgPool>, replicas: Vec<PgPool>) -> Self {
+        Self {
+            primary,
+            replicas: replicas
+                .into_iter()
+                .map(|pool| Replica {
+                    pool,
+                    unavailable_until: Mutex::new(None),
+                })
+                .collect(),
+            next_replica: AtomicUsize::new(0),
+        }
+    }
+
+    /// Unit of work transactions, user lookups and api token lookups use this pool, so the
+    /// reads that precede an update or decide authentication always see the latest writes.
+    pub(crate) fn primary(&self) -> &PgPool {
+        &self.primary
+    }
+
+    /// Runs a read-only query on a replica, trying the next replica and finally the primary
+    /// when a replica cannot be reached. Query errors are returned as they are.
+    pub(crate) async fn read<'q, T, F>(&self, query: F) -> Result<T, sqlx::Error>
+    where
+        F: Fn(PgPool) -> ReadQuery<'q, T>,
+    {
+        let start = self.next_replica.fetch_add(1, Ordering::Relaxed);
+        for offset in 0..self.replicas.len() {
+            let replica = &self.replicas[(start + offset) % self.replicas.len()];
+            if !replica.is_available() {
+                continue;
+            }
+            match query(replica.pool.clone()).await {
+                Err(err) if is_unavailable(&err) => {
+                    tracing::warn!("Read replica is unavailable, skipping it: {}", err);
+                    replica.mark_unavailable();
+                }
+                result => return result,
+            }
+        }
+        query(self.primary.as_ref().clone()).await
+    }
+
+    pub(crate) async fn close(&self) {
+        self.primary.close().await;
+        for replica in &self.replicas {
+            replica.pool.close().await;
+        }
+    }
+}
+
+impl Replica {
+    fn is_available(&self) -> bool {
+        self.unavailable_until
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .is_none_or(|until| Instant::now() >= until)
+    }
+
+    fn mark_unavailable(&self) {
+        *self
+            .unavailable_until
+            .lock()
+            .unwrap_or_else(|e| e.into_inner()) = Some(Instant::now() + REPLICA_RETRY_INTERVAL);
+    }
+}
+
+/// Errors that mean the database could not be reached or refuses connections, as opposed to a
+/// failing query. Covers the SQLSTATE classes 08 (connection exception) and 57P (shutdown or
+/// still starting up).
+fn is_unavailable(err: &sqlx::Error) -> bool {
+    match err {
+        sqlx::Error::PoolTimedOut
+        | sqlx::Error::PoolClosed
+        | sqlx::Error::Io(_)
+        | sqlx::Error::Tls(_) => true,
+        sqlx::Error::Database(err) => err
+            .code()
+            .is_some_and(|code| code.starts_with("08") || code.starts_with("57P")),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_unavailable_only_matches_connection_failures() {
+        assert!(is_unavailable(&sqlx::Error::PoolTimedOut));
+        assert!(is_unavailable(&sqlx::Error::Io(std::io::Error::from(
+            std::io::ErrorKind::ConnectionRefused
+        ))));
+        assert!(!is_unavailable(&sqlx::Error::RowNotFound));
+    }
+}
@@ -0,0 +1,151 @@
+use crate::application::contracts::ApiTokenRepository;
+use crate::domain::entities::ApiToken;
+use crate::domain::value_objects::{ApiTokenName, DateTime, Identification, Scope};
+use crate::infrastructure::database_error;
+use async_trait::async_trait;
+use sqlx::PgPool;
+use std::sync::Arc;
+
+pub(crate) struct PostgresApiTokenRepository {
+    pool: Arc<PgPool>,
+}
+
+impl PostgresApiTokenRepository {
+    pub(crate) fn new(pool: Arc<PgPool>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl ApiTokenRepository for PostgresApiTokenRepository {
+    #[tracing::instrument(name = "Create api token in the DB", skip(self))]
+    async fn create(&self, token: &ApiToken) -> Result<(), anyhow::Error> {
+        let scopes = scopes_to_strings(token.scopes());
+        sqlx::query!(
+            r#"
+                INSERT INTO api_tokens
+                (id, user_id, name, token_hash, scopes, created_at, expires_at, last_used_at, revoked_at)
+                VALUES
+                ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            "#,
+            token.id().as_ref(),
+            token.user_id().as_ref(),
+            token.name().as_ref(),
+            token.token_hash(),
+            &scopes,
+            token.created_at().as_ref(),
+            token.expires_at().map(|x| *x.as_ref()),
+            token.last_used_at().map(|x| *x.as_ref()),
+            token.revoked_at().map(|x| *x.as_ref()),
+        )
+        .execute(self.pool.as_ref())
+        .await
+        .map_err(database_error)?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "Get api token by hash from the DB", skip_all)]
+    async fn get_by_hash(&self, token_hash: &str) -> Result<Option<ApiToken>, anyhow::Error> {
+        let record = sqlx::query_as!(
+            ApiTokenRecord,
+            "SELECT * FROM api_tokens WHERE token_hash = $1",
+            token_hash
+        )
+        .fetch_optional(self.pool.as_ref())
+        .await
+        .map_err(database_error)?;
+
+        record.map(ApiToken::try_from).transpose()
+    }
+
+    #[tracing::instrument(name = "List api tokens from the DB", skip(self))]
+    async fn list(&self, user_id: &Identification) -> Result<Vec<ApiToken>, anyhow::Error> {
+        let records = sqlx::query_as!(
+            ApiTokenRecord,
+            "SELECT * FROM api_tokens WHERE user_id = $1 ORDER BY created_at DESC",
+            user_id.as_ref()
+        )
+        .fetch_all(self.pool.as_ref())
+        .await
+        .map_err(database_error)?;
+
+        records.into_iter().map(ApiToken::try_from).collect()
+    }
+}
+
+/// Locks the token's row until the surrounding transaction ends.
+pub(super) async fn select_api_token_for_update(
+    connection: &mut sqlx::PgConnection,
+    id: &Identification,
+) -> Result<Option<ApiToken>, anyhow::Error> {
+    let record = sqlx::query_as!(
+        ApiTokenRecord,
+        "SELECT * FROM api_tokens WHERE id = $1 FOR UPDATE",
+        id.as_ref()
+    )
+    .fetch_optional(connection)
+    .await
+    .map_err(database_error)?;
+
+    record.map(ApiToken::try_from).transpose()
+}
+
+pub(super) async fn update_api_token(
+    connection: &mut sqlx::PgConnection,
+    token: &ApiToken,
+) -> Result<(), anyhow::Error> {
+    sqlx::query!(
+        "UPDATE api_tokens SET last_used_at = $1, revoked_at = $2 WHERE id = $3",
+        token.last_used_at().map(|x| *x.as_ref()),
+        token.revoked_at().map(|x| *x.as_ref()),
+        token.id().as_ref(),
+    )
+    .execute(connection)
+    .await
+    .map_err(database_error)?;
+
+    Ok(())
+}
+
+struct ApiTokenRecord {
+    id: uuid::Uuid,
+    user_id: uuid::Uuid,
+    name: String,
+    token_hash: String,
+    scopes: Vec<String>,
+    created_at: chrono::DateTime<chrono::Utc>,
+    expires_at: Option<chrono::DateTime<chrono::Utc>>,
+    last_used_at: Option<chrono::DateTime<chrono::Utc>>,
+    revoked_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl TryFrom<ApiTokenRecord> for ApiToken {
+    type Error = anyhow::Error;
+
+    fn try_from(record: ApiTokenRecord) -> Result<Self, Self::Error> {
+        let scopes = record
+            .scopes
+            .iter()
+            .map(|scope| Scope::try_from(scope.as_str()))
+            .collect::<Result<Vec<Scope>, _>>()?;
+        Ok(ApiToken::restore(
+            Identification::from(record.id),
+            Identification::from(record.user_id),
+            ApiTokenName::try_from(record.name)?,
+            record.token_hash,
+            scopes,
+            DateTime::from(record.created_at),
+            record.expires_at.map(DateTime::from),
+            record.last_used_at.map(DateTime::from),
+            record.revoked_at.map(DateTime::from),
+        ))
+    }
+}
+
+fn scopes_to_strings(scopes: &[Scope]) -> Vec<String> {
+    scopes
+        .iter()
+        .map(|scope| scope.as_ref().to_string())
+        .collect()
+}
@@ -0,0 +1,33 @@
+use crate::application::contracts::HealthCheck;
+use async_trait::async_trait;
+use sqlx::{Connection, PgPool};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Upper bound for a single probe, well below the pool's acquire timeout so that probes
+/// answer quickly while the database is unreachable.
+const PING_TIMEOUT: Duration = Duration::from_secs(2);
+
+pub(crate) struct PostgresHealthCheck {
+    pool: Arc<PgPool>,
+}
+
+impl PostgresHealthCheck {
+    pub(crate) fn new(pool: Arc<PgPool>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl HealthCheck for PostgresHealthCheck {
+    #[tracing::instrument(name = "Ping the DB", skip(self))]
+    async fn check(&self) -> Result<(), anyhow::Error> {
+        tokio::time::timeout(PING_TIMEOUT, async {
+            let mut connection = self.pool.acquire().await?;
+            connection.ping().await
+        })
+        .await
+        .map_err(|_| anyhow::anyhow!("database ping timed out"))??;
+        Ok(())
+    }
+}
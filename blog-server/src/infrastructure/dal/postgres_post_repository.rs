@@ -1,17 +1,17 @@
 use crate::application::contracts::PostRepository;
 use crate::domain::entities::Post;
 use crate::domain::value_objects::{Content, DateTime, Identification, Title};
+use crate::infrastructure::{DatabasePools, database_error};
 use async_trait::async_trait;
-use sqlx::PgPool;
 use std::sync::Arc;
 
 pub(crate) struct PostgresPostRepository {
-    pool: Arc<PgPool>,
+    pools: Arc<DatabasePools>,
 }
 
 impl PostgresPostRepository {
-    pub(crate) fn new(pool: Arc<PgPool>) -> Self {
-        Self { pool }
+    pub(crate) fn new(pools: Arc<DatabasePools>) -> Self {
+        Self { pools }
     }
 }
 
@@ -19,102 +19,272 @@ impl PostgresPostRepository {
 impl PostRepository for PostgresPostRepository {
     #[tracing::instrument(name = "Create post in the DB", skip(self))]
     async fn create(&self, post: &Post) -> Result<(), anyhow::Error> {
-        sqlx::query!(
-            "INSERT INTO posts VALUES ($1, $2, $3, $4, $5, $6)",
-            post.id().as_ref(),
-            post.title().as_ref(),
-            post.content().as_ref(),
-            post.author_id().as_ref(),
-            post.created_at().as_ref(),
-            post.updated_at().as_ref()
-        )
-        .execute(self.pool.as_ref())
-        .await?;
-        Ok(())
+        let mut connection = self
+            .pools
+            .primary()
+            .acquire()
+            .await
+            .map_err(database_error)?;
+        insert_post(&mut connection, post).await
     }
 
-    #[tracing::instrument(name = "Update post from the DB", skip(self))]
-    async fn update(&self, post: &Post) -> Result<(), anyhow::Error> {
-        sqlx::query!(
-            "UPDATE posts SET title = $1, content = $2, updated_at = $3 WHERE id = $4",
-            post.title().as_ref(),
-            post.content().as_ref(),
-            post.updated_at().as_ref(),
-            post.id().as_ref()
-        )
-        .execute(self.pool.as_ref())
-        .await?;
+    #[tracing::instrument(name = "Create posts in the DB", skip_all, fields(count = posts.len()))]
+    async fn create_many(&self, posts: &[Post]) -> Result<(), anyhow::Error> {
+        let mut transaction = self.pools.primary().begin().await.map_err(database_error)?;
+        for post in posts {
+            insert_post(&mut transaction, post).await?;
+        }
+        transaction.commit().await.map_err(database_error)?;
         Ok(())
     }
 
     #[tracing::instrument(name = "Get post from the DB", skip(self))]
     async fn get(&self, id: &Identification) -> Result<Option<Post>, anyhow::Error> {
-        let record = sqlx::query!("SELECT * FROM posts WHERE id = $1", id.as_ref())
-            .fetch_optional(self.pool.as_ref())
-            .await?;
-
-        match record {
-            None => Ok(None),
-            Some(record) => {
-                let id = Identification::from(record.id);
-                let title = Title::try_from(record.title)?;
-                let content = Content::from(record.content);
-                let author_id = Identification::from(record.author_id);
-                let created_at = DateTime::from(record.created_at);
-                let updated_at = DateTime::from(record.updated_at);
-                let post = Post::restore(id, title, content, author_id, created_at, updated_at);
-                Ok(Some(post))
-            }
-        }
+        let record = self
+            .pools
+            .read(|pool| {
+                Box::pin(async move {
+                    sqlx::query_as!(PostRecord, "SELECT * FROM posts WHERE id = $1", id.as_ref())
+                        .fetch_optional(&pool)
+                        .await
+                })
+            })
+            .await
+            .map_err(database_error)?;
+
+        record.map(Post::try_from).transpose()
     }
 
-    #[tracing::instrument(name = "Delete post from the DB", skip(self))]
-    async fn delete(&self, id: &Identification) -> Result<(), anyhow::Error> {
-        sqlx::query!("DELETE FROM posts WHERE id = $1", id.as_ref())
-            .execute(self.pool.as_ref())
-            .await?;
-        Ok(())
+    #[tracing::instrument(name = "Get posts by ids from the DB", skip_all, fields(count = ids.len()))]
+    async fn get_many(&self, ids: &[Identification]) -> Result<Vec<Post>, anyhow::Error> {
+        let ids = ids
+            .iter()
+            .map(|id| *id.as_ref())
+            .collect::<Vec<uuid::Uuid>>();
+        let ids = &ids;
+        let records = self
+            .pools
+            .read(|pool| {
+                Box::pin(async move {
+                    sqlx::query_as!(PostRecord, "SELECT * FROM posts WHERE id = ANY($1)", ids)
+                        .fetch_all(&pool)
+                        .await
+                })
+            })
+            .await
+            .map_err(database_error)?;
+
+        records.into_iter().map(Post::try_from).collect()
     }
 
     #[tracing::instrument(name = "Get posts list from the DB", skip(self))]
     async fn list(&self, limit: usize, offset: usize) -> Result<(Vec<Post>, usize), anyhow::Error> {
-        let mut tx = self.pool.begin().await?;
-
-        sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ")
-            .execute(&mut *tx)
-            .await?;
-
-        let count = sqlx::query!("SELECT count(*) as count FROM posts")
-            .fetch_one(self.pool.as_ref())
-            .await?
-            .count
-            .unwrap_or(0);
-
-        let records = sqlx::query!(
-            "SELECT * FROM posts ORDER BY created_at LIMIT $1 OFFSET $2",
-            limit as i64,
-            offset as i64
+        let (records, count) = self
+            .pools
+            .read(|pool| {
+                Box::pin(async move {
+                    // The count and the page are read from one snapshot, so posts created or
+                    // deleted in between cannot make them disagree.
+                    let mut tx = pool.begin().await?;
+
+                    sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ, READ ONLY")
+                        .execute(&mut *tx)
+                        .await?;
+
+                    let count = sqlx::query!("SELECT count(*) as count FROM posts")
+                        .fetch_one(&mut *tx)
+                        .await?
+                        .count
+                        .unwrap_or(0);
+
+                    let records = sqlx::query_as!(
+                        PostRecord,
+                        "SELECT * FROM posts ORDER BY created_at LIMIT $1 OFFSET $2",
+                        limit as i64,
+                        offset as i64
+                    )
+                    .fetch_all(&mut *tx)
+                    .await?;
+
+                    tx.commit().await?;
+                    Ok((records, count))
+                })
+            })
+            .await
+            .map_err(database_error)?;
+
+        let posts = records
+            .into_iter()
+            .map(Post::try_from)
+            .collect::<Result<Vec<Post>, _>>()?;
+
+        Ok((posts, count as usize))
+    }
+}
+
+async fn insert_post(
+    connection: &mut sqlx::PgConnection,
+    post: &Post,
+) -> Result<(), anyhow::Error> {
+    sqlx::query!(
+        "INSERT INTO posts VALUES ($1, $2, $3, $4, $5, $6)",
+        post.id().as_ref(),
+        post.title().as_ref(),
+        post.content().as_ref(),
+        post.author_id().as_ref(),
+        post.created_at().as_ref(),
+        post.updated_at().as_ref()
+    )
+    .execute(connection)
+    .await
+    .map_err(database_error)?;
+    Ok(())
+}
+
+/// Locks the post's row until the surrounding transaction ends.
+pub(super) async fn select_post_for_update(
+    connection: &mut sqlx::PgConnection,
+    id: &Identification,
+) -> Result<Option<Post>, anyhow::Error> {
+    let record = sqlx::query_as!(
+        PostRecord,
+        "SELECT * FROM posts WHERE id = $1 FOR UPDATE",
+        id.as_ref()
+    )
+    .fetch_optional(connection)
+    .await
+    .map_err(database_error)?;
+
+    record.map(Post::try_from).transpose()
+}
+
+pub(super) async fn update_post(
+    connection: &mut sqlx::PgConnection,
+    post: &Post,
+) -> Result<(), anyhow::Error> {
+    sqlx::query!(
+        "UPDATE posts SET title = $1, content = $2, updated_at = $3 WHERE id = $4",
+        post.title().as_ref(),
+        post.content().as_ref(),
+        post.updated_at().as_ref(),
+        post.id().as_ref()
+    )
+    .execute(connection)
+    .await
+    .map_err(database_error)?;
+    Ok(())
+}
+
+pub(super) async fn delete_post(
+    connection: &mut sqlx::PgConnection,
+    id: &Identification,
+) -> Result<(), anyhow::Error> {
+    sqlx::query!("DELETE FROM posts WHERE id = $1", id.as_ref())
+        .execute(connection)
+        .await
+        .map_err(database_error)?;
+    Ok(())
+}
+
+struct PostRecord {
+    id: uuid::Uuid,
+    title: String,
+    content: String,
+    author_id: uuid::Uuid,
+    created_at: chrono::DateTime<chrono::Utc>,
+    updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl TryFrom<PostRecord> for Post {
+    type Error = anyhow::Error;
+
+    fn try_from(record: PostRecord) -> Result<Self, Self::Error> {
+        Ok(Post::restore(
+            Identification::from(record.id),
+            Title::try_from(record.title)?,
+            Content::from(record.content),
+            Identification::from(record.author_id),
+            DateTime::from(record.created_at),
+            DateTime::from(record.updated_at),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::PgPool;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    const CONCURRENT_POSTS: usize = 200;
+
+    async fn create_author(pool: &PgPool) -> Identification {
+        let id = Identification::new();
+        sqlx::query(
+            "INSERT INTO users (id, username, email, password_hash, created_at) \
+             VALUES ($1, 'author', 'author@example.com', '', now())",
         )
-        .fetch_all(self.pool.as_ref())
-        .await?;
+        .bind(id.as_ref())
+        .execute(pool)
+        .await
+        .unwrap();
+        id
+    }
 
-        tx.commit().await?;
+    fn new_post(author_id: &Identification, number: usize) -> Post {
+        Post::new(
+            Title::try_from(format!("Post {number}")).unwrap(),
+            Content::from("content".to_string()),
+            author_id.clone(),
+        )
+    }
 
-        let mut posts = Vec::with_capacity(records.len());
+    /// Pages through the posts while another task keeps creating them, checking that every
+    /// page holds exactly as many posts as its total promises.
+    async fn assert_pages_match_totals_during_inserts(pool: PgPool, limit: usize, offset: usize) {
+        let author_id = create_author(&pool).await;
+        let repository = Arc::new(PostgresPostRepository::new(Arc::new(DatabasePools::new(
+            Arc::new(pool),
+            Vec::new(),
+        ))));
+        let done = Arc::new(AtomicBool::new(false));
 
-        for record in records {
-            let id = Identification::from(record.id);
-            let title = Title::try_from(record.title)?;
-            let content = Content::from(record.content);
-            let author_id = Identification::from(record.author_id);
-            let created_at = DateTime::from(record.created_at);
-            let updated_at = DateTime::from(record.updated_at);
+        let writer = tokio::spawn({
+            let repository = repository.clone();
+            let done = done.clone();
+            async move {
+                for number in 0..CONCURRENT_POSTS {
+                    repository
+                        .create(&new_post(&author_id, number))
+                        .await
+                        .unwrap();
+                }
+                done.store(true, Ordering::SeqCst);
+            }
+        });
 
-            posts.push(Post::restore(
-                id, title, content, author_id, created_at, updated_at,
-            ));
+        let mut pages = 0;
+        while !done.load(Ordering::SeqCst) {
+            let (posts, total) = repository.list(limit, offset).await.unwrap();
+            assert_eq!(posts.len(), total.saturating_sub(offset).min(limit));
+            pages += 1;
         }
+        writer.await.unwrap();
 
-        Ok((posts, count as usize))
+        assert!(pages > 0);
+        let (_, total) = repository.list(limit, offset).await.unwrap();
+        assert_eq!(total, CONCURRENT_POSTS);
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    #[ignore = "needs a Postgres server in DATABASE_URL"]
+    async fn list_returns_every_post_with_its_total_during_inserts(pool: PgPool) {
+        assert_pages_match_totals_during_inserts(pool, CONCURRENT_POSTS, 0).await;
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    #[ignore = "needs a Postgres server in DATABASE_URL"]
+    async fn list_returns_a_partial_last_page_during_inserts(pool: PgPool) {
+        assert_pages_match_totals_during_inserts(pool, 10, CONCURRENT_POSTS / 2).await;
     }
 }
@@ -0,0 +1,351 @@
+use crate::application::contracts::{
+    ApiTokenTransactionRepository, ExternalIdentityTransactionRepository,
+    PostTransactionRepository, Transaction, TwoFactorTransactionRepository, UnitOfWork,
+    UserTransactionRepository,
+};
+use crate::domain::entities::{ApiToken, Post, User};
+use crate::domain::value_objects::{Email, ExternalIdentity, Identification, UserName};
+use crate::infrastructure::dal::postgres_api_token_repository::{
+    select_api_token_for_update, update_api_token,
+};
+use crate::infrastructure::dal::postgres_post_repository::{
+    delete_post, select_post_for_update, update_post,
+};
+use crate::infrastructure::dal::postgres_user_repository::{
+    email_exists, insert_external_identity, insert_user, lock_external_identity,
+    replace_recovery_codes, select_unused_recovery_codes, select_user_by_external_identity,
+    select_user_for_update, update_two_factor, use_recovery_code, user_exists,
+};
+use crate::infrastructure::{DatabasePools, TotpSecretCipher, database_error};
+use async_trait::async_trait;
+use sqlx::Postgres;
+use std::sync::Arc;
+
+pub(crate) struct PostgresUnitOfWork {
+    pools: Arc<DatabasePools>,
+    cipher: Arc<TotpSecretCipher>,
+}
+
+impl PostgresUnitOfWork {
+    pub(crate) fn new(pools: Arc<DatabasePools>, cipher: Arc<TotpSecretCipher>) -> Self {
+        Self { pools, cipher }
+    }
+}
+
+#[async_trait]
+impl UnitOfWork for PostgresUnitOfWork {
+    async fn begin(&self) -> Result<Box<dyn Transaction>, anyhow::Error> {
+        let transaction = self.pools.primary().begin().await.map_err(database_error)?;
+        Ok(Box::new(PostgresTransaction {
+            transaction,
+            cipher: Arc::clone(&self.cipher),
+        }))
+    }
+}
+
+struct PostgresTransaction {
+    transaction: sqlx::Transaction<'static, Postgres>,
+    cipher: Arc<TotpSecretCipher>,
+}
+
+#[async_trait]
+impl Transaction for PostgresTransaction {
+    fn users(&mut self) -> &mut dyn UserTransactionRepository {
+        self
+    }
+
+    fn two_factor(&mut self) -> &mut dyn TwoFactorTransactionRepository {
+        self
+    }
+
+    fn external_identities(&mut self) -> &mut dyn ExternalIdentityTransactionRepository {
+        self
+    }
+
+    fn posts(&mut self) -> &mut dyn PostTransactionRepository {
+        self
+    }
+
+    fn api_tokens(&mut self) -> &mut dyn ApiTokenTransactionRepository {
+        self
+    }
+
+    async fn commit(self: Box<Self>) -> Result<(), anyhow::Error> {
+        self.transaction.commit().await.map_err(database_error)
+    }
+}
+
+#[async_trait]
+impl UserTransactionRepository for PostgresTransaction {
+    #[tracing::instrument(
+        name = "Check if username or email exists in the transaction",
+        skip(self)
+    )]
+    async fn user_exists(
+        &mut self,
+        username: &UserName,
+        email: &Email,
+    ) -> Result<bool, anyhow::Error> {
+        user_exists(&mut self.transaction, username, email).await
+    }
+
+    #[tracing::instrument(name = "Create user in the transaction", skip(self))]
+    async fn create_user(&mut self, user: &User) -> Result<(), anyhow::Error> {
+        insert_user(&mut self.transaction, user).await
+    }
+
+    #[tracing::instrument(name = "Check if email exists in the transaction", skip(self))]
+    async fn email_exists(&mut self, email: &Email) -> Result<bool, anyhow::Error> {
+        email_exists(&mut self.transaction, email).await
+    }
+
+    #[tracing::instrument(name = "Lock user in the transaction", skip(self))]
+    async fn get_user_for_update(
+        &mut self,
+        id: &Identification,
+    ) -> Result<Option<User>, anyhow::Error> {
+        select_user_for_update(&mut self.transaction, id, &self.cipher).await
+    }
+}
+
+#[async_trait]
+impl TwoFactorTransactionRepository for PostgresTransaction {
+    #[tracing::instrument(
+        name = "Update user two-factor settings in the transaction",
+        skip(self)
+    )]
+    async fn update_two_factor(&mut self, user: &User) -> Result<(), anyhow::Error> {
+        update_two_factor(&mut self.transaction, user, &self.cipher).await
+    }
+
+    #[tracing::instrument(
+        name = "Replace recovery codes in the transaction",
+        skip(self, code_hashes)
+    )]
+    async fn replace_recovery_codes(
+        &mut self,
+        user_id: &Identification,
+        code_hashes: &[String],
+    ) -> Result<(), anyhow::Error> {
+        replace_recovery_codes(&mut self.transaction, user_id, code_hashes).await
+    }
+
+    #[tracing::instrument(name = "Get unused recovery codes in the transaction", skip(self))]
+    async fn get_unused_recovery_codes(
+        &mut self,
+        user_id: &Identification,
+    ) -> Result<Vec<(Identification, String)>, anyhow::Error> {
+        select_unused_recovery_codes(&mut self.transaction, user_id).await
+    }
+
+    #[tracing::instrument(name = "Use recovery code in the transaction", skip(self))]
+    async fn use_recovery_code(&mut self, id: &Identification) -> Result<(), anyhow::Error> {
+        use_recovery_code(&mut self.transaction, id).await
+    }
+}
+
+#[async_trait]
+impl ExternalIdentityTransactionRepository for PostgresTransaction {
+    #[tracing::instrument(name = "Lock external identity in the transaction", skip(self))]
+    async fn lock_external_identity(
+        &mut self,
+        identity: &ExternalIdentity,
+    ) -> Result<(), anyhow::Error> {
+        lock_external_identity(&mut self.transaction, identity).await
+    }
+
+    #[tracing::instrument(name = "Get user by external identity in the transaction", skip(self))]
+    async fn get_user_by_external_identity(
+        &mut self,
+        identity: &ExternalIdentity,
+    ) -> Result<Option<User>, anyhow::Error> {
+        select_user_by_external_identity(&mut self.transaction, identity, &self.cipher).await
+    }
+
+    #[tracing::instrument(name = "Link external identity in the transaction", skip(self))]
+    async fn link_external_identity(
+        &mut self,
+        user_id: &Identification,
+        identity: &ExternalIdentity,
+    ) -> Result<(), anyhow::Error> {
+        insert_external_identity(&mut self.transaction, user_id, identity).await
+    }
+}
+
+#[async_trait]
+impl PostTransactionRepository for PostgresTransaction {
+    #[tracing::instrument(name = "Lock post in the transaction", skip(self))]
+    async fn get_post_for_update(
+        &mut self,
+        id: &Identification,
+    ) -> Result<Option<Post>, anyhow::Error> {
+        select_post_for_update(&mut self.transaction, id).await
+    }
+
+    #[tracing::instrument(name = "Update post in the transaction", skip(self))]
+    async fn update_post(&mut self, post: &Post) -> Result<(), anyhow::Error> {
+        update_post(&mut self.transaction, post).await
+    }
+
+    #[tracing::instrument(name = "Delete post in the transaction", skip(self))]
+    async fn delete_post(&mut self, id: &Identification) -> Result<(), anyhow::Error> {
+        delete_post(&mut self.transaction, id).await
+    }
+}
+
+#[async_trait]
+impl ApiTokenTransactionRepository for PostgresTransaction {
+    #[tracing::instrument(name = "Lock api token in the transaction", skip(self))]
+    async fn get_api_token_for_update(
+        &mut self,
+        id: &Identification,
+    ) -> Result<Option<ApiToken>, anyhow::Error> {
+        select_api_token_for_update(&mut self.transaction, id).await
+    }
+
+    #[tracing::instrument(name = "Update api token in the transaction", skip(self))]
+    async fn update_api_token(&mut self, token: &ApiToken) -> Result<(), anyhow::Error> {
+        update_api_token(&mut self.transaction, token).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::contracts::{ApiTokenRepository, UniqueViolation};
+    use crate::domain::value_objects::{ApiTokenName, PasswordHash, Scope};
+    use crate::infrastructure::PostgresApiTokenRepository;
+    use secrecy::SecretString;
+    use sqlx::PgPool;
+
+    fn unit_of_work(pool: PgPool) -> PostgresUnitOfWork {
+        PostgresUnitOfWork::new(
+            Arc::new(DatabasePools::new(Arc::new(pool), Vec::new())),
+            Arc::new(
+                TotpSecretCipher::new(&SecretString::from("0123456789abcdef0123456789abcdef"))
+                    .unwrap(),
+            ),
+        )
+    }
+
+    fn new_user(username: &str, email: &str) -> User {
+        User::new(
+            UserName::try_from(username.to_string()).unwrap(),
+            Email::try_from(email.to_string()).unwrap(),
+            PasswordHash::from(SecretString::from("hash")),
+        )
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    #[ignore = "needs a Postgres server in DATABASE_URL"]
+    async fn concurrent_registration_fails_with_unique_violation(pool: PgPool) {
+        let unit_of_work = unit_of_work(pool);
+        let first = new_user("first", "same@example.com");
+        let second = new_user("second", "same@example.com");
+
+        let mut late = unit_of_work.begin().await.unwrap();
+        assert!(
+            !late
+                .users()
+                .user_exists(second.username(), second.email())
+                .await
+                .unwrap()
+        );
+
+        let mut early = unit_of_work.begin().await.unwrap();
+        early.users().create_user(&first).await.unwrap();
+        early.commit().await.unwrap();
+
+        let err = late.users().create_user(&second).await.unwrap_err();
+        assert!(err.is::<UniqueViolation>());
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    #[ignore = "needs a Postgres server in DATABASE_URL"]
+    async fn locked_api_token_sees_concurrent_revocation(pool: PgPool) {
+        let unit_of_work = unit_of_work(pool.clone());
+        let user = new_user("owner", "owner@example.com");
+        let token = ApiToken::new(
+            user.id().clone(),
+            ApiTokenName::try_from("ci".to_string()).unwrap(),
+            "hash".to_string(),
+            vec![Scope::PostsWrite],
+            None,
+        );
+        let mut setup = unit_of_work.begin().await.unwrap();
+        setup.users().create_user(&user).await.unwrap();
+        setup.commit().await.unwrap();
+        PostgresApiTokenRepository::new(Arc::new(pool))
+            .create(&token)
+            .await
+            .unwrap();
+
+        let mut revoke = unit_of_work.begin().await.unwrap();
+        let mut revoked = revoke
+            .api_tokens()
+            .get_api_token_for_update(token.id())
+            .await
+            .unwrap()
+            .unwrap();
+        let mut touch = unit_of_work.begin().await.unwrap();
+        let id = token.id().clone();
+        let locked = tokio::spawn(async move {
+            let token = touch
+                .api_tokens()
+                .get_api_token_for_update(&id)
+                .await
+                .unwrap()
+                .unwrap();
+            (touch, token)
+        });
+        revoked.revoke();
+        revoke
+            .api_tokens()
+            .update_api_token(&revoked)
+            .await
+            .unwrap();
+        revoke.commit().await.unwrap();
+
+        let (_touch, token) = locked.await.unwrap();
+        assert!(!token.is_active());
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    #[ignore = "needs a Postgres server in DATABASE_URL"]
+    async fn external_identity_lock_serializes_sign_ins(pool: PgPool) {
+        let unit_of_work = unit_of_work(pool);
+        let identity = ExternalIdentity::new("https://idp".to_string(), "42".to_string());
+        let user = new_user("first", "first@example.com");
+
+        let mut first = unit_of_work.begin().await.unwrap();
+        first
+            .external_identities()
+            .lock_external_identity(&identity)
+            .await
+            .unwrap();
+        let mut second = unit_of_work.begin().await.unwrap();
+        let second_identity = ExternalIdentity::new("https://idp".to_string(), "42".to_string());
+        let found = tokio::spawn(async move {
+            second
+                .external_identities()
+                .lock_external_identity(&second_identity)
+                .await
+                .unwrap();
+            second
+                .external_identities()
+                .get_user_by_external_identity(&second_identity)
+                .await
+                .unwrap()
+        });
+        first.users().create_user(&user).await.unwrap();
+        first
+            .external_identities()
+            .link_external_identity(user.id(), &identity)
+            .await
+            .unwrap();
+        first.commit().await.unwrap();
+
+        let found = found.await.unwrap().unwrap();
+        assert_eq!(found.id(), user.id());
+    }
+}
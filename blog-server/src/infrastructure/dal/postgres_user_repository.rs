@@ -1,73 +1,300 @@
 use crate::application::contracts::UserRepository;
 use crate::domain::entities::User;
-use crate::domain::value_objects::{DateTime, Email, Identification, PasswordHash, UserName};
+use crate::domain::value_objects::{
+    DateTime, Email, ExternalIdentity, Identification, PasswordHash, UserName,
+};
+use crate::infrastructure::{DatabasePools, TotpSecretCipher, database_error};
 use async_trait::async_trait;
 use secrecy::{ExposeSecret, SecretString};
-use sqlx::PgPool;
 use std::sync::Arc;
 
 pub(crate) struct PostgresUserRepository {
-    pool: Arc<PgPool>,
+    pools: Arc<DatabasePools>,
+    cipher: Arc<TotpSecretCipher>,
 }
 
 impl PostgresUserRepository {
-    pub(crate) fn new(pool: Arc<PgPool>) -> Self {
-        Self { pool }
+    pub(crate) fn new(pools: Arc<DatabasePools>, cipher: Arc<TotpSecretCipher>) -> Self {
+        Self { pools, cipher }
     }
 }
 
 #[async_trait]
 impl UserRepository for PostgresUserRepository {
-    #[tracing::instrument(name = "Check if username or email exists in the DB", skip(self))]
-    async fn exist(&self, username: &UserName, email: &Email) -> Result<bool, anyhow::Error> {
-        let is_exists = sqlx::query_scalar!(
-            "SELECT EXISTS(SELECT 1 FROM users WHERE username = $1 OR email = $2)",
-            username.as_ref(),
-            email.as_ref(),
+    #[tracing::instrument(name = "Get user from the DB", skip(self))]
+    async fn get(&self, username: &UserName) -> Result<Option<User>, anyhow::Error> {
+        let record = sqlx::query_as!(
+            UserRecord,
+            "SELECT * FROM users WHERE username = $1",
+            username.as_ref()
         )
-        .fetch_one(self.pool.as_ref())
-        .await?;
+        .fetch_optional(self.pools.primary())
+        .await
+        .map_err(database_error)?;
 
-        Ok(is_exists.is_some_and(|x| x))
+        record
+            .map(|record| record.into_user(&self.cipher))
+            .transpose()
     }
+}
+
+pub(super) async fn user_exists(
+    connection: &mut sqlx::PgConnection,
+    username: &UserName,
+    email: &Email,
+) -> Result<bool, anyhow::Error> {
+    let is_exists = sqlx::query_scalar!(
+        "SELECT EXISTS(SELECT 1 FROM users WHERE username = $1 OR email = $2)",
+        username.as_ref(),
+        email.as_ref(),
+    )
+    .fetch_one(connection)
+    .await
+    .map_err(database_error)?;
+
+    Ok(is_exists.is_some_and(|x| x))
+}
+
+/// Locks the user's row until the surrounding transaction ends.
+pub(super) async fn select_user_for_update(
+    connection: &mut sqlx::PgConnection,
+    id: &Identification,
+    cipher: &TotpSecretCipher,
+) -> Result<Option<User>, anyhow::Error> {
+    let record = sqlx::query_as!(
+        UserRecord,
+        "SELECT * FROM users WHERE id = $1 FOR UPDATE",
+        id.as_ref()
+    )
+    .fetch_optional(connection)
+    .await
+    .map_err(database_error)?;
+
+    record.map(|record| record.into_user(cipher)).transpose()
+}
+
+pub(super) async fn insert_user(
+    connection: &mut sqlx::PgConnection,
+    user: &User,
+) -> Result<(), anyhow::Error> {
+    sqlx::query!(
+        r#"
+            INSERT INTO users (id, username, email, password_hash, created_at)
+            VALUES
+            ($1, $2, $3, $4, $5)
+        "#,
+        user.id().as_ref(),
+        user.username().as_ref(),
+        user.email().as_ref(),
+        user.password_hash().as_ref().expose_secret(),
+        user.created_at().as_ref(),
+    )
+    .execute(connection)
+    .await
+    .map_err(database_error)?;
 
-    #[tracing::instrument(name = "Create user in the DB", skip(self))]
-    async fn create(&self, user: &User) -> Result<(), anyhow::Error> {
+    Ok(())
+}
+
+pub(super) async fn update_two_factor(
+    connection: &mut sqlx::PgConnection,
+    user: &User,
+    cipher: &TotpSecretCipher,
+) -> Result<(), anyhow::Error> {
+    let totp_secret = user
+        .totp_secret()
+        .map(|secret| cipher.encrypt(user.id(), secret))
+        .transpose()?;
+    let totp_last_step = user.totp_last_step().map(i64::try_from).transpose()?;
+    sqlx::query!(
+        r#"
+            UPDATE users
+            SET totp_secret = $1, totp_enabled = $2, totp_last_step = $3
+            WHERE id = $4
+        "#,
+        totp_secret,
+        user.is_totp_enabled(),
+        totp_last_step,
+        user.id().as_ref(),
+    )
+    .execute(connection)
+    .await
+    .map_err(database_error)?;
+
+    Ok(())
+}
+
+/// Deletes the user's recovery codes and stores the new ones; run it in a transaction so that
+/// the user never ends up without codes.
+pub(super) async fn replace_recovery_codes(
+    connection: &mut sqlx::PgConnection,
+    user_id: &Identification,
+    code_hashes: &[String],
+) -> Result<(), anyhow::Error> {
+    sqlx::query!(
+        "DELETE FROM recovery_codes WHERE user_id = $1",
+        user_id.as_ref()
+    )
+    .execute(&mut *connection)
+    .await
+    .map_err(database_error)?;
+    for code_hash in code_hashes {
+        let id = Identification::new();
         sqlx::query!(
-            r#"
-                INSERT INTO users
-                VALUES
-                ($1, $2, $3, $4, $5)
-            "#,
-            user.id().as_ref(),
-            user.username().as_ref(),
-            user.email().as_ref(),
-            user.password_hash().as_ref().expose_secret(),
-            user.created_at().as_ref(),
+            "INSERT INTO recovery_codes (id, user_id, code_hash) VALUES ($1, $2, $3)",
+            id.as_ref(),
+            user_id.as_ref(),
+            code_hash,
         )
-        .execute(self.pool.as_ref())
-        .await?;
-
-        Ok(())
+        .execute(&mut *connection)
+        .await
+        .map_err(database_error)?;
     }
 
-    #[tracing::instrument(name = "Get user from the DB", skip(self))]
-    async fn get(&self, username: &UserName) -> Result<Option<User>, anyhow::Error> {
-        let record = sqlx::query!("SELECT * FROM users WHERE username = $1", username.as_ref())
-            .fetch_optional(self.pool.as_ref())
-            .await?;
-
-        match record {
-            None => Ok(None),
-            Some(record) => {
-                let id = Identification::from(record.id);
-                let user_name = UserName::try_from(record.username)?;
-                let email = Email::try_from(record.email)?;
-                let password_hash = PasswordHash::from(SecretString::from(record.password_hash));
-                let created_at = DateTime::from(record.created_at);
-                let user = User::restore(id, user_name, email, password_hash, created_at);
-                Ok(Some(user))
-            }
-        }
+    Ok(())
+}
+
+pub(super) async fn select_unused_recovery_codes(
+    connection: &mut sqlx::PgConnection,
+    user_id: &Identification,
+) -> Result<Vec<(Identification, String)>, anyhow::Error> {
+    let records = sqlx::query!(
+        "SELECT id, code_hash FROM recovery_codes WHERE user_id = $1 AND used_at IS NULL",
+        user_id.as_ref()
+    )
+    .fetch_all(connection)
+    .await
+    .map_err(database_error)?;
+
+    Ok(records
+        .into_iter()
+        .map(|record| (Identification::from(record.id), record.code_hash))
+        .collect())
+}
+
+pub(super) async fn use_recovery_code(
+    connection: &mut sqlx::PgConnection,
+    id: &Identification,
+) -> Result<(), anyhow::Error> {
+    sqlx::query!(
+        "UPDATE recovery_codes SET used_at = now() WHERE id = $1",
+        id.as_ref()
+    )
+    .execute(connection)
+    .await
+    .map_err(database_error)?;
+
+    Ok(())
+}
+
+/// Makes transactions that sign in with the same external identity run one after another, even
+/// before the identity is stored.
+pub(super) async fn lock_external_identity(
+    connection: &mut sqlx::PgConnection,
+    identity: &ExternalIdentity,
+) -> Result<(), anyhow::Error> {
+    sqlx::query!(
+        "SELECT pg_advisory_xact_lock(hashtextextended($1, 0))",
+        format!("{}\n{}", identity.issuer(), identity.subject()),
+    )
+    .execute(connection)
+    .await
+    .map_err(database_error)?;
+
+    Ok(())
+}
+
+pub(super) async fn select_user_by_external_identity(
+    connection: &mut sqlx::PgConnection,
+    identity: &ExternalIdentity,
+    cipher: &TotpSecretCipher,
+) -> Result<Option<User>, anyhow::Error> {
+    let record = sqlx::query_as!(
+        UserRecord,
+        r#"
+            SELECT users.*
+            FROM users
+            JOIN user_identities ON user_identities.user_id = users.id
+            WHERE user_identities.issuer = $1 AND user_identities.subject = $2
+        "#,
+        identity.issuer(),
+        identity.subject(),
+    )
+    .fetch_optional(connection)
+    .await
+    .map_err(database_error)?;
+
+    record.map(|record| record.into_user(cipher)).transpose()
+}
+
+pub(super) async fn email_exists(
+    connection: &mut sqlx::PgConnection,
+    email: &Email,
+) -> Result<bool, anyhow::Error> {
+    let is_exists = sqlx::query_scalar!(
+        "SELECT EXISTS(SELECT 1 FROM users WHERE email = $1)",
+        email.as_ref(),
+    )
+    .fetch_one(connection)
+    .await
+    .map_err(database_error)?;
+
+    Ok(is_exists.is_some_and(|x| x))
+}
+
+pub(super) async fn insert_external_identity(
+    connection: &mut sqlx::PgConnection,
+    user_id: &Identification,
+    identity: &ExternalIdentity,
+) -> Result<(), anyhow::Error> {
+    let id = Identification::new();
+    let created_at = DateTime::now();
+    sqlx::query!(
+        r#"
+            INSERT INTO user_identities (id, user_id, issuer, subject, created_at)
+            VALUES
+            ($1, $2, $3, $4, $5)
+        "#,
+        id.as_ref(),
+        user_id.as_ref(),
+        identity.issuer(),
+        identity.subject(),
+        created_at.as_ref(),
+    )
+    .execute(connection)
+    .await
+    .map_err(database_error)?;
+
+    Ok(())
+}
+
+struct UserRecord {
+    id: uuid::Uuid,
+    username: String,
+    email: String,
+    password_hash: String,
+    created_at: chrono::DateTime<chrono::Utc>,
+    totp_secret: Option<String>,
+    totp_enabled: bool,
+    totp_last_step: Option<i64>,
+}
+
+impl UserRecord {
+    fn into_user(self, cipher: &TotpSecretCipher) -> Result<User, anyhow::Error> {
+        let id = Identification::from(self.id);
+        let totp_secret = self
+            .totp_secret
+            .map(|secret| cipher.decrypt(&id, secret))
+            .transpose()?;
+        Ok(User::restore(
+            id,
+            UserName::try_from(self.username)?,
+            Email::try_from(self.email)?,
+            PasswordHash::from(SecretString::from(self.password_hash)),
+            DateTime::from(self.created_at),
+            totp_secret,
+            self.totp_enabled,
+            self.totp_last_step.map(u64::try_from).transpose()?,
+        ))
     }
 }
@@ -1,4 +1,5 @@
 use crate::domain::entities::User;
+use crate::domain::value_objects::Scope;
 use jsonwebtoken::{DecodingKey, EncodingKey, Header, TokenData, Validation, decode, encode};
 use secrecy::{ExposeSecret, SecretString};
 use serde::{Deserialize, Serialize};
@@ -6,6 +7,8 @@ use std::time::SystemTime;
 use uuid::Uuid;
 
 const TOKEN_EXPIRATION_TIME: usize = 60 * 60;
+const CHALLENGE_TOKEN_EXPIRATION_TIME: usize = 5 * 60;
+const CHALLENGE_TOKEN_AUDIENCE: &str = "two-factor";
 
 pub(crate) struct JwtService {
     secret: SecretString,
@@ -17,10 +20,11 @@ impl JwtService {
     }
 
     #[tracing::instrument(name = "Generate JWT", skip(self))]
-    pub(crate) fn generate_jwt(&self, user: &User) -> anyhow::Result<String> {
+    pub(crate) fn generate_jwt(&self, user: &User, scopes: &[Scope]) -> anyhow::Result<String> {
         let claims = Claims {
             sub: *user.id().as_ref(),
             username: user.username().as_ref().to_string(),
+            scopes: scopes_to_strings(scopes),
             exp: SystemTime::now()
                 .duration_since(std::time::UNIX_EPOCH)?
                 .as_secs() as usize
@@ -43,12 +47,55 @@ impl JwtService {
         )?;
         Ok(token_data.claims)
     }
+
+    /// Issues a short-lived token proving that the password step of a two-factor login passed.
+    #[tracing::instrument(name = "Generate challenge token", skip(self))]
+    pub(crate) fn generate_challenge_token(
+        &self,
+        user: &User,
+        scopes: &[Scope],
+    ) -> anyhow::Result<String> {
+        let claims = ChallengeClaims {
+            jti: Uuid::new_v4(),
+            sub: *user.id().as_ref(),
+            scopes: scopes_to_strings(scopes),
+            aud: CHALLENGE_TOKEN_AUDIENCE.to_string(),
+            exp: SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)?
+                .as_secs() as usize
+                + CHALLENGE_TOKEN_EXPIRATION_TIME,
+        };
+        let token = encode(
+            &Header::default(),
+            &claims,
+            &EncodingKey::from_secret(self.secret.expose_secret().as_bytes()),
+        )?;
+        Ok(token)
+    }
+
+    #[tracing::instrument(name = "Decode challenge token", skip(self, token))]
+    pub(crate) fn decode_challenge_token(
+        &self,
+        token: &str,
+    ) -> Result<ChallengeClaims, JwtDecodeError> {
+        let mut validation = Validation::default();
+        validation.set_audience(&[CHALLENGE_TOKEN_AUDIENCE]);
+        let token_data: TokenData<ChallengeClaims> = decode(
+            token,
+            &DecodingKey::from_secret(self.secret.expose_secret().as_bytes()),
+            &validation,
+        )?;
+        Ok(token_data.claims)
+    }
 }
 
 #[derive(Serialize, Deserialize)]
 pub(crate) struct Claims {
     sub: Uuid,
     username: String,
+    // Tokens issued before scopes were introduced carry full access.
+    #[serde(default = "all_scopes")]
+    scopes: Vec<String>,
     exp: usize,
 }
 
@@ -56,6 +103,34 @@ impl Claims {
     pub(crate) fn sub(&self) -> Uuid {
         self.sub
     }
+
+    pub(crate) fn scopes(&self) -> Vec<Scope> {
+        strings_to_scopes(&self.scopes)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub(crate) struct ChallengeClaims {
+    jti: Uuid,
+    sub: Uuid,
+    aud: String,
+    scopes: Vec<String>,
+    exp: usize,
+}
+
+impl ChallengeClaims {
+    /// Identifies the challenge, to count the codes tried against it.
+    pub(crate) fn id(&self) -> Uuid {
+        self.jti
+    }
+
+    pub(crate) fn sub(&self) -> Uuid {
+        self.sub
+    }
+
+    pub(crate) fn scopes(&self) -> Vec<Scope> {
+        strings_to_scopes(&self.scopes)
+    }
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -63,3 +138,21 @@ pub(crate) enum JwtDecodeError {
     #[error("unauthorized")]
     Unauthorized(#[from] jsonwebtoken::errors::Error),
 }
+
+fn all_scopes() -> Vec<String> {
+    scopes_to_strings(&Scope::all())
+}
+
+fn scopes_to_strings(scopes: &[Scope]) -> Vec<String> {
+    scopes
+        .iter()
+        .map(|scope| scope.as_ref().to_string())
+        .collect()
+}
+
+fn strings_to_scopes(scopes: &[String]) -> Vec<Scope> {
+    scopes
+        .iter()
+        .filter_map(|scope| Scope::try_from(scope.as_str()).ok())
+        .collect()
+}
@@ -0,0 +1,24 @@
+use anyhow::Context;
+use std::os::unix::fs::FileTypeExt;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::Path;
+
+/// Binds a non-blocking Unix domain socket listener.
+///
+/// A socket file left behind by a previous run is replaced, but binding fails while another
+/// process still accepts connections on it.
+pub(crate) fn bind_unix_socket(path: &Path) -> anyhow::Result<UnixListener> {
+    if let Ok(metadata) = std::fs::symlink_metadata(path)
+        && metadata.file_type().is_socket()
+    {
+        if UnixStream::connect(path).is_ok() {
+            anyhow::bail!("{} is in use by another process", path.display());
+        }
+        std::fs::remove_file(path)
+            .with_context(|| format!("failed to remove stale socket {}", path.display()))?;
+    }
+    let listener = UnixListener::bind(path)
+        .with_context(|| format!("failed to bind Unix socket {}", path.display()))?;
+    listener.set_nonblocking(true)?;
+    Ok(listener)
+}
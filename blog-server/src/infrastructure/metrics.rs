@@ -0,0 +1,169 @@
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGauge, IntGaugeVec, Opts,
+    Registry, TextEncoder,
+};
+use sqlx::PgPool;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Route label of HTTP requests that matched no registered route.
+pub(crate) const UNMATCHED_ROUTE: &str = "unmatched";
+/// Method label of gRPC calls to methods the server does not serve.
+pub(crate) const UNKNOWN_GRPC_METHOD: &str = "unknown";
+
+/// Prometheus metrics of the server, rendered in the text exposition format.
+pub(crate) struct Metrics {
+    registry: Registry,
+    pg_pool: Arc<PgPool>,
+    http_requests: IntCounterVec,
+    http_request_duration: HistogramVec,
+    grpc_requests: IntCounterVec,
+    grpc_request_duration: HistogramVec,
+    db_pool_connections: IntGaugeVec,
+    db_pool_max_connections: IntGauge,
+    posts_created: IntCounter,
+    logins_failed: IntCounter,
+}
+
+impl Metrics {
+    pub(crate) fn new(pg_pool: Arc<PgPool>) -> Result<Self, prometheus::Error> {
+        let registry = Registry::new_custom(Some("blog".to_string()), None)?;
+        let http_requests = IntCounterVec::new(
+            Opts::new("http_requests_total", "HTTP requests handled"),
+            &["method", "route", "status"],
+        )?;
+        let http_request_duration = HistogramVec::new(
+            HistogramOpts::new(
+                "http_request_duration_seconds",
+                "HTTP request latency in seconds",
+            ),
+            &["method", "route"],
+        )?;
+        let grpc_requests = IntCounterVec::new(
+            Opts::new("grpc_requests_total", "gRPC calls handled"),
+            &["method", "code"],
+        )?;
+        let grpc_request_duration = HistogramVec::new(
+            HistogramOpts::new(
+                "grpc_request_duration_seconds",
+                "gRPC call latency until the response headers, in seconds",
+            ),
+            &["method"],
+        )?;
+        let db_pool_connections = IntGaugeVec::new(
+            Opts::new("db_pool_connections", "Open database connections"),
+            &["state"],
+        )?;
+        let db_pool_max_connections = IntGauge::new(
+            "db_pool_max_connections",
+            "Maximum number of database connections",
+        )?;
+        let posts_created = IntCounter::new("posts_created_total", "Posts created")?;
+        let logins_failed = IntCounter::new(
+            "logins_failed_total",
+            "Logins rejected because of wrong credentials",
+        )?;
+
+        registry.register(Box::new(http_requests.clone()))?;
+        registry.register(Box::new(http_request_duration.clone()))?;
+        registry.register(Box::new(grpc_requests.clone()))?;
+        registry.register(Box::new(grpc_request_duration.clone()))?;
+        registry.register(Box::new(db_pool_connections.clone()))?;
+        registry.register(Box::new(db_pool_max_connections.clone()))?;
+        registry.register(Box::new(posts_created.clone()))?;
+        registry.register(Box::new(logins_failed.clone()))?;
+
+        Ok(Self {
+            registry,
+            pg_pool,
+            http_requests,
+            http_request_duration,
+            grpc_requests,
+            grpc_request_duration,
+            db_pool_connections,
+            db_pool_max_connections,
+            posts_created,
+            logins_failed,
+        })
+    }
+
+    /// Records an HTTP request. `route` must be the matched route pattern, not the raw path.
+    pub(crate) fn observe_http_request(
+        &self,
+        method: &str,
+        route: &str,
+        status: u16,
+        elapsed: Duration,
+    ) {
+        self.http_requests
+            .with_label_values(&[method, route, &status.to_string()])
+            .inc();
+        self.http_request_duration
+            .with_label_values(&[method, route])
+            .observe(elapsed.as_secs_f64());
+    }
+
+    /// Records a gRPC call. `method` must be a served method or [`UNKNOWN_GRPC_METHOD`].
+    pub(crate) fn observe_grpc_request(&self, method: &str, code: tonic::Code, elapsed: Duration) {
+        self.grpc_requests
+            .with_label_values(&[method, &format!("{:?}", code)])
+            .inc();
+        self.grpc_request_duration
+            .with_label_values(&[method])
+            .observe(elapsed.as_secs_f64());
+    }
+
+    pub(crate) fn posts_created(&self, count: usize) {
+        self.posts_created.inc_by(count as u64);
+    }
+
+    pub(crate) fn login_failed(&self) {
+        self.logins_failed.inc();
+    }
+
+    /// Renders all metrics, sampling the database pool at the time of the scrape.
+    pub(crate) fn render(&self) -> Result<String, prometheus::Error> {
+        let size = i64::from(self.pg_pool.size());
+        let idle = self.pg_pool.num_idle() as i64;
+        self.db_pool_connections
+            .with_label_values(&["idle"])
+            .set(idle);
+        self.db_pool_connections
+            .with_label_values(&["in_use"])
+            .set(size - idle);
+        self.db_pool_max_connections
+            .set(i64::from(self.pg_pool.options().get_max_connections()));
+
+        let mut buffer = Vec::new();
+        TextEncoder::new().encode(&self.registry.gather(), &mut buffer)?;
+        String::from_utf8(buffer).map_err(|e| prometheus::Error::Msg(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::postgres::PgPoolOptions;
+
+    fn metrics() -> Metrics {
+        let pg_pool = PgPoolOptions::new()
+            .connect_lazy("postgres://localhost/blog")
+            .unwrap();
+        Metrics::new(Arc::new(pg_pool)).unwrap()
+    }
+
+    #[tokio::test]
+    async fn render_labels_requests_by_route_pattern() {
+        let metrics = metrics();
+        metrics.observe_http_request("GET", "/api/posts/{id}", 200, Duration::from_millis(5));
+        metrics.posts_created(2);
+
+        let rendered = metrics.render().unwrap();
+
+        assert!(rendered.contains(
+            r#"blog_http_requests_total{method="GET",route="/api/posts/{id}",status="200"} 1"#
+        ));
+        assert!(rendered.contains("blog_posts_created_total 2"));
+        assert!(rendered.contains(r#"blog_db_pool_connections{state="in_use"} 0"#));
+    }
+}
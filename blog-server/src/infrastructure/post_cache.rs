@@ -0,0 +1,204 @@
+use crate::domain::entities::Post;
+use crate::domain::value_objects::Identification;
+use async_trait::async_trait;
+use hashlink::LruCache;
+use std::hash::Hash;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// A page of posts and the total number of posts, as returned by `PostRepository::list`.
+pub(crate) type PostPage = (Vec<Post>, usize);
+
+/// Storage behind [`CachedPostRepository`](crate::infrastructure::CachedPostRepository).
+///
+/// Implementations decide on eviction and expiry; a shared store such as Redis lets several
+/// servers see each other's invalidations.
+///
+/// Every invalidation bumps the store's generation. A value read from the database before an
+/// invalidation may predate the write behind it, so inserts carry the generation taken before
+/// the read and are dropped when an invalidation has happened since.
+#[async_trait]
+pub(crate) trait PostCacheStore: Send + Sync {
+    async fn generation(&self) -> u64;
+    async fn get_post(&self, id: &Identification) -> Option<Post>;
+    async fn insert_post(&self, post: Post, generation: u64);
+    async fn remove_post(&self, id: &Identification);
+    async fn get_page(&self, limit: usize, offset: usize) -> Option<PostPage>;
+    async fn insert_page(&self, limit: usize, offset: usize, page: PostPage, generation: u64);
+    /// Removes every cached page, since any write can shift posts between pages.
+    async fn clear_pages(&self);
+}
+
+/// In-process [`PostCacheStore`] that keeps the most recently used entries for a fixed time.
+pub(crate) struct InMemoryPostCacheStore {
+    posts: ExpiringLru<Uuid, Post>,
+    pages: ExpiringLru<(usize, usize), PostPage>,
+    /// Held while an entry is inserted or invalidated, so that both happen in one order.
+    generation: Mutex<u64>,
+}
+
+impl InMemoryPostCacheStore {
+    /// Holds up to `capacity` posts and, separately, up to `capacity` pages.
+    pub(crate) fn new(capacity: usize, ttl: Duration) -> Self {
+        Self {
+            posts: ExpiringLru::new(capacity, ttl),
+            pages: ExpiringLru::new(capacity, ttl),
+            generation: Mutex::new(0),
+        }
+    }
+
+    fn lock_generation(&self) -> MutexGuard<'_, u64> {
+        self.generation.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[async_trait]
+impl PostCacheStore for InMemoryPostCacheStore {
+    async fn generation(&self) -> u64 {
+        *self.lock_generation()
+    }
+
+    async fn get_post(&self, id: &Identification) -> Option<Post> {
+        self.posts.get(id.as_ref())
+    }
+
+    async fn insert_post(&self, post: Post, generation: u64) {
+        let current = self.lock_generation();
+        if *current == generation {
+            self.posts.insert(*post.id().as_ref(), post);
+        }
+    }
+
+    async fn remove_post(&self, id: &Identification) {
+        let mut current = self.lock_generation();
+        *current += 1;
+        self.posts.remove(id.as_ref());
+    }
+
+    async fn get_page(&self, limit: usize, offset: usize) -> Option<PostPage> {
+        self.pages.get(&(limit, offset))
+    }
+
+    async fn insert_page(&self, limit: usize, offset: usize, page: PostPage, generation: u64) {
+        let current = self.lock_generation();
+        if *current == generation {
+            self.pages.insert((limit, offset), page);
+        }
+    }
+
+    async fn clear_pages(&self) {
+        let mut current = self.lock_generation();
+        *current += 1;
+        self.pages.clear();
+    }
+}
+
+struct ExpiringLru<K, V> {
+    entries: Mutex<LruCache<K, (V, Instant)>>,
+    ttl: Duration,
+}
+
+impl<K: Eq + Hash, V: Clone> ExpiringLru<K, V> {
+    fn new(capacity: usize, ttl: Duration) -> Self {
+        Self {
+            entries: Mutex::new(LruCache::new(capacity)),
+            ttl,
+        }
+    }
+
+    fn get(&self, key: &K) -> Option<V> {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        match entries.get(key) {
+            Some((value, expires_at)) if Instant::now() < *expires_at => Some(value.clone()),
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    fn insert(&self, key: K, value: V) {
+        let expires_at = Instant::now() + self.ttl;
+        self.entries
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(key, (value, expires_at));
+    }
+
+    fn remove(&self, key: &K) {
+        self.entries
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(key);
+    }
+
+    fn clear(&self) {
+        self.entries
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::value_objects::{Content, Title};
+
+    #[test]
+    fn expiring_lru_evicts_least_recently_used() {
+        let cache = ExpiringLru::new(2, Duration::from_secs(60));
+        cache.insert(1, "one");
+        cache.insert(2, "two");
+        assert_eq!(cache.get(&1), Some("one"));
+
+        cache.insert(3, "three");
+
+        assert_eq!(cache.get(&1), Some("one"));
+        assert_eq!(cache.get(&2), None);
+        assert_eq!(cache.get(&3), Some("three"));
+    }
+
+    fn post() -> Post {
+        Post::new(
+            Title::try_from("title".to_string()).unwrap(),
+            Content::from("content".to_string()),
+            Identification::new(),
+        )
+    }
+
+    #[tokio::test]
+    async fn insert_is_dropped_after_invalidation() {
+        let cache = InMemoryPostCacheStore::new(10, Duration::from_secs(60));
+        let post = post();
+
+        let generation = cache.generation().await;
+        cache.remove_post(&Identification::new()).await;
+        cache.insert_post(post.clone(), generation).await;
+        cache
+            .insert_page(10, 0, (vec![post.clone()], 1), generation)
+            .await;
+
+        assert!(cache.get_post(post.id()).await.is_none());
+        assert!(cache.get_page(10, 0).await.is_none());
+
+        let generation = cache.generation().await;
+        cache.insert_post(post.clone(), generation).await;
+        cache
+            .insert_page(10, 0, (vec![post.clone()], 1), generation)
+            .await;
+
+        assert_eq!(cache.get_post(post.id()).await, Some(post));
+        assert!(cache.get_page(10, 0).await.is_some());
+    }
+
+    #[test]
+    fn expiring_lru_drops_expired_entries() {
+        let cache = ExpiringLru::new(2, Duration::ZERO);
+        cache.insert(1, "one");
+
+        assert_eq!(cache.get(&1), None);
+    }
+}
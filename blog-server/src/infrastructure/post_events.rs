@@ -0,0 +1,102 @@
+use crate::application::contracts::{PostEvent, PostEventBus};
+use std::sync::RwLock;
+use tokio::sync::broadcast;
+
+/// In-process [`PostEventBus`] backed by a broadcast channel.
+///
+/// Subscribers that fall more than `capacity` events behind miss the oldest ones and
+/// observe a lag error on their next receive.
+pub(crate) struct BroadcastPostEventBus {
+    sender: RwLock<Option<broadcast::Sender<PostEvent>>>,
+}
+
+impl BroadcastPostEventBus {
+    pub(crate) fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity);
+        Self {
+            sender: RwLock::new(Some(sender)),
+        }
+    }
+}
+
+impl PostEventBus for BroadcastPostEventBus {
+    fn publish(&self, event: PostEvent) {
+        let sender = self.sender.read().unwrap_or_else(|e| e.into_inner());
+        if let Some(sender) = sender.as_ref() {
+            // Sending only fails when there are no subscribers, which is not an error.
+            let _ = sender.send(event);
+        }
+    }
+
+    fn subscribe(&self) -> broadcast::Receiver<PostEvent> {
+        let sender = self.sender.read().unwrap_or_else(|e| e.into_inner());
+        match sender.as_ref() {
+            Some(sender) => sender.subscribe(),
+            // The sender is dropped right away, so the receiver reports the bus as closed.
+            None => broadcast::channel(1).1,
+        }
+    }
+
+    fn close(&self) {
+        self.sender
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .take();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::entities::Post;
+    use crate::domain::value_objects::{Content, Identification, Title};
+
+    fn post() -> Post {
+        Post::new(
+            Title::try_from("Hello".to_string()).unwrap(),
+            Content::from("world".to_string()),
+            Identification::new(),
+        )
+    }
+
+    #[tokio::test]
+    async fn subscribers_receive_published_events() {
+        let bus = BroadcastPostEventBus::new(8);
+        let mut first = bus.subscribe();
+        let mut second = bus.subscribe();
+        let post = post();
+
+        bus.publish(PostEvent::Created(post.clone()));
+
+        for receiver in [&mut first, &mut second] {
+            match receiver.recv().await.unwrap() {
+                PostEvent::Created(received) => assert_eq!(received.id(), post.id()),
+                event => panic!("unexpected event: {:?}", event),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn close_ends_current_and_later_subscriptions() {
+        let bus = BroadcastPostEventBus::new(8);
+        let mut current = bus.subscribe();
+
+        bus.close();
+        bus.publish(PostEvent::Deleted(post()));
+
+        assert!(matches!(
+            current.recv().await,
+            Err(broadcast::error::RecvError::Closed)
+        ));
+        assert!(matches!(
+            bus.subscribe().recv().await,
+            Err(broadcast::error::RecvError::Closed)
+        ));
+    }
+
+    #[test]
+    fn publish_without_subscribers_is_ignored() {
+        let bus = BroadcastPostEventBus::new(8);
+        bus.publish(PostEvent::Deleted(post()));
+    }
+}
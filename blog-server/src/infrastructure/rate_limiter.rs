@@ -0,0 +1,531 @@
+use crate::configuration::{
+    RateLimitBudgetConfiguration, RateLimitConfiguration, TokenBucketConfiguration,
+};
+use hashlink::LruCache;
+use std::net::{IpAddr, Ipv6Addr};
+use std::sync::{Mutex, RwLock};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// At most this many buckets are tracked. The bucket of the least recently seen client is
+/// only dropped once it has refilled, so that forgetting it does not reset its limit; until
+/// then new clients share the overflow buckets.
+const MAX_TRACKED_BUCKETS: usize = 10_000;
+
+pub(crate) struct RateLimiter {
+    limits: RwLock<Limits>,
+    buckets: Mutex<Buckets>,
+}
+
+struct Buckets {
+    tracked: LruCache<(RateLimitSubject, RateLimitOperation), TokenBucket>,
+    overflow_read: Option<TokenBucket>,
+    overflow_write: Option<TokenBucket>,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(config: &RateLimitConfiguration) -> Self {
+        Self::with_limits(Limits::from(config), MAX_TRACKED_BUCKETS)
+    }
+
+    fn with_limits(limits: Limits, max_tracked_buckets: usize) -> Self {
+        Self {
+            limits: RwLock::new(limits),
+            buckets: Mutex::new(Buckets {
+                tracked: LruCache::new(max_tracked_buckets),
+                overflow_read: None,
+                overflow_write: None,
+            }),
+        }
+    }
+
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.limits
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .enabled
+    }
+
+    /// Applies new limits, including to the buckets of clients that are already tracked.
+    pub(crate) fn reconfigure(&self, config: &RateLimitConfiguration) {
+        self.set_limits(Limits::from(config));
+    }
+
+    fn set_limits(&self, limits: Limits) {
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        for ((subject, operation), bucket) in buckets.tracked.iter_mut() {
+            bucket.set_limit(limits.limit(*subject, *operation));
+        }
+        for operation in [RateLimitOperation::Read, RateLimitOperation::Write] {
+            if let Some(bucket) = buckets.overflow(operation).as_mut() {
+                bucket.set_limit(limits.overflow_limit(operation));
+            }
+        }
+        *self.limits.write().unwrap_or_else(|e| e.into_inner()) = limits;
+    }
+
+    pub(crate) fn check(
+        &self,
+        subject: RateLimitSubject,
+        operation: RateLimitOperation,
+    ) -> RateLimitDecision {
+        self.check_at(subject, operation, Instant::now())
+    }
+
+    fn check_at(
+        &self,
+        subject: RateLimitSubject,
+        operation: RateLimitOperation,
+        now: Instant,
+    ) -> RateLimitDecision {
+        let subject = subject.bucket_subject();
+        let (limit, overflow_limit) = {
+            let limits = self.limits.read().unwrap_or_else(|e| e.into_inner());
+            (
+                limits.limit(subject, operation),
+                limits.overflow_limit(operation),
+            )
+        };
+
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        let key = (subject, operation);
+        if let Some(bucket) = buckets.tracked.get_mut(&key) {
+            return bucket.try_acquire(now);
+        }
+        if buckets.tracked.len() >= buckets.tracked.capacity() {
+            let is_idle = buckets
+                .tracked
+                .iter()
+                .next()
+                .is_some_and(|(_, bucket)| bucket.is_full_at(now));
+            if !is_idle {
+                return buckets
+                    .overflow(operation)
+                    .get_or_insert_with(|| TokenBucket::new(overflow_limit, now))
+                    .try_acquire(now);
+            }
+            buckets.tracked.remove_lru();
+        }
+        let mut bucket = TokenBucket::new(limit, now);
+        let decision = bucket.try_acquire(now);
+        buckets.tracked.insert(key, bucket);
+        decision
+    }
+}
+
+impl Buckets {
+    fn overflow(&mut self, operation: RateLimitOperation) -> &mut Option<TokenBucket> {
+        match operation {
+            RateLimitOperation::Read => &mut self.overflow_read,
+            RateLimitOperation::Write => &mut self.overflow_write,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum RateLimitSubject {
+    Anonymous(IpAddr),
+    User(Uuid),
+}
+
+impl RateLimitSubject {
+    /// Clients get a whole IPv6 /64, so they share one bucket per /64 rather than one per
+    /// address.
+    fn bucket_subject(self) -> Self {
+        match self {
+            RateLimitSubject::Anonymous(ip) => match ip.to_canonical() {
+                IpAddr::V6(ip) => RateLimitSubject::Anonymous(IpAddr::V6(Ipv6Addr::from_bits(
+                    ip.to_bits() & !u128::from(u64::MAX),
+                ))),
+                ip => RateLimitSubject::Anonymous(ip),
+            },
+            subject => subject,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum RateLimitOperation {
+    Read,
+    Write,
+}
+
+#[derive(Debug)]
+pub(crate) struct RateLimitDecision {
+    allowed: bool,
+    limit: u32,
+    remaining: u32,
+    reset_after: Duration,
+}
+
+impl RateLimitDecision {
+    pub(crate) fn is_allowed(&self) -> bool {
+        self.allowed
+    }
+
+    pub(crate) fn limit(&self) -> u32 {
+        self.limit
+    }
+
+    pub(crate) fn remaining(&self) -> u32 {
+        self.remaining
+    }
+
+    /// Seconds until the bucket is completely refilled, rounded up.
+    pub(crate) fn reset_after_secs(&self) -> u64 {
+        self.reset_after.as_secs_f64().ceil() as u64
+    }
+}
+
+#[derive(Clone, Copy)]
+struct BucketLimit {
+    capacity: u32,
+    refill_per_second: f64,
+}
+
+impl From<&TokenBucketConfiguration> for BucketLimit {
+    fn from(value: &TokenBucketConfiguration) -> Self {
+        Self {
+            capacity: value.get_capacity(),
+            refill_per_second: f64::from(value.get_refill_per_minute()) / 60.0,
+        }
+    }
+}
+
+struct Limits {
+    enabled: bool,
+    anonymous: Budget,
+    authenticated: Budget,
+}
+
+impl Limits {
+    fn limit(&self, subject: RateLimitSubject, operation: RateLimitOperation) -> BucketLimit {
+        let budget = match subject {
+            RateLimitSubject::Anonymous(_) => &self.anonymous,
+            RateLimitSubject::User(_) => &self.authenticated,
+        };
+        match operation {
+            RateLimitOperation::Read => budget.read,
+            RateLimitOperation::Write => budget.write,
+        }
+    }
+
+    /// The overflow buckets are shared by clients nobody vouches for, so they get the
+    /// anonymous limits.
+    fn overflow_limit(&self, operation: RateLimitOperation) -> BucketLimit {
+        match operation {
+            RateLimitOperation::Read => self.anonymous.read,
+            RateLimitOperation::Write => self.anonymous.write,
+        }
+    }
+}
+
+impl From<&RateLimitConfiguration> for Limits {
+    fn from(value: &RateLimitConfiguration) -> Self {
+        Self {
+            enabled: value.is_enabled(),
+            anonymous: Budget::from(value.get_anonymous()),
+            authenticated: Budget::from(value.get_authenticated()),
+        }
+    }
+}
+
+struct Budget {
+    read: BucketLimit,
+    write: BucketLimit,
+}
+
+impl From<&RateLimitBudgetConfiguration> for Budget {
+    fn from(value: &RateLimitBudgetConfiguration) -> Self {
+        Self {
+            read: BucketLimit::from(value.get_read()),
+            write: BucketLimit::from(value.get_write()),
+        }
+    }
+}
+
+struct TokenBucket {
+    limit: BucketLimit,
+    tokens: f64,
+    updated_at: Instant,
+}
+
+impl TokenBucket {
+    fn new(limit: BucketLimit, now: Instant) -> Self {
+        Self {
+            limit,
+            tokens: f64::from(limit.capacity),
+            updated_at: now,
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.limit.refill_per_second)
+            .min(f64::from(self.limit.capacity));
+        self.updated_at = now;
+    }
+
+    fn is_full_at(&self, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.updated_at).as_secs_f64();
+        self.tokens + elapsed * self.limit.refill_per_second >= f64::from(self.limit.capacity)
+    }
+
+    /// Keeps the tokens left, but no more than the new capacity.
+    fn set_limit(&mut self, limit: BucketLimit) {
+        self.tokens = self.tokens.min(f64::from(limit.capacity));
+        self.limit = limit;
+    }
+
+    fn try_acquire(&mut self, now: Instant) -> RateLimitDecision {
+        self.refill(now);
+        let allowed = self.tokens >= 1.0;
+        if allowed {
+            self.tokens -= 1.0;
+        }
+
+        let missing = f64::from(self.limit.capacity) - self.tokens;
+        let reset_after = if self.limit.refill_per_second > 0.0 {
+            Duration::from_secs_f64(missing / self.limit.refill_per_second)
+        } else {
+            Duration::MAX
+        };
+
+        RateLimitDecision {
+            allowed,
+            limit: self.limit.capacity,
+            remaining: self.tokens.floor() as u32,
+            reset_after,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv4Addr;
+
+    fn create_limits(anonymous_read_capacity: u32) -> Limits {
+        Limits {
+            enabled: true,
+            anonymous: Budget {
+                read: BucketLimit {
+                    capacity: anonymous_read_capacity,
+                    refill_per_second: 1.0,
+                },
+                write: BucketLimit {
+                    capacity: 1,
+                    refill_per_second: 0.5,
+                },
+            },
+            authenticated: Budget {
+                read: BucketLimit {
+                    capacity: 5,
+                    refill_per_second: 1.0,
+                },
+                write: BucketLimit {
+                    capacity: 3,
+                    refill_per_second: 1.0,
+                },
+            },
+        }
+    }
+
+    fn create_limiter() -> RateLimiter {
+        RateLimiter::with_limits(create_limits(2), MAX_TRACKED_BUCKETS)
+    }
+
+    #[test]
+    fn rejects_when_bucket_is_exhausted() {
+        let limiter = create_limiter();
+        let subject = RateLimitSubject::Anonymous(IpAddr::V4(Ipv4Addr::LOCALHOST));
+        let now = Instant::now();
+
+        let first = limiter.check_at(subject, RateLimitOperation::Read, now);
+        assert!(first.is_allowed());
+        assert_eq!(first.limit(), 2);
+        assert_eq!(first.remaining(), 1);
+
+        assert!(
+            limiter
+                .check_at(subject, RateLimitOperation::Read, now)
+                .is_allowed()
+        );
+        let rejected = limiter.check_at(subject, RateLimitOperation::Read, now);
+        assert!(!rejected.is_allowed());
+        assert_eq!(rejected.remaining(), 0);
+        assert_eq!(rejected.reset_after_secs(), 2);
+    }
+
+    #[test]
+    fn refills_over_time() {
+        let limiter = create_limiter();
+        let subject = RateLimitSubject::Anonymous(IpAddr::V4(Ipv4Addr::LOCALHOST));
+        let now = Instant::now();
+
+        assert!(
+            limiter
+                .check_at(subject, RateLimitOperation::Write, now)
+                .is_allowed()
+        );
+        assert!(
+            !limiter
+                .check_at(subject, RateLimitOperation::Write, now)
+                .is_allowed()
+        );
+        assert!(
+            limiter
+                .check_at(
+                    subject,
+                    RateLimitOperation::Write,
+                    now + Duration::from_secs(2)
+                )
+                .is_allowed()
+        );
+    }
+
+    #[test]
+    fn separates_subjects_and_operations() {
+        let limiter = create_limiter();
+        let anonymous = RateLimitSubject::Anonymous(IpAddr::V4(Ipv4Addr::LOCALHOST));
+        let user = RateLimitSubject::User(Uuid::new_v4());
+        let now = Instant::now();
+
+        assert!(
+            limiter
+                .check_at(anonymous, RateLimitOperation::Write, now)
+                .is_allowed()
+        );
+        assert!(
+            !limiter
+                .check_at(anonymous, RateLimitOperation::Write, now)
+                .is_allowed()
+        );
+        assert!(
+            limiter
+                .check_at(anonymous, RateLimitOperation::Read, now)
+                .is_allowed()
+        );
+
+        let decision = limiter.check_at(user, RateLimitOperation::Write, now);
+        assert!(decision.is_allowed());
+        assert_eq!(decision.limit(), 3);
+    }
+
+    #[test]
+    fn set_limits_caps_tracked_buckets() {
+        let limiter = RateLimiter::with_limits(create_limits(3), MAX_TRACKED_BUCKETS);
+        let subject = RateLimitSubject::Anonymous(IpAddr::V4(Ipv4Addr::LOCALHOST));
+        let now = Instant::now();
+        assert!(
+            limiter
+                .check_at(subject, RateLimitOperation::Read, now)
+                .is_allowed()
+        );
+
+        limiter.set_limits(create_limits(1));
+
+        let decision = limiter.check_at(subject, RateLimitOperation::Read, now);
+        assert!(decision.is_allowed());
+        assert_eq!(decision.limit(), 1);
+        assert!(
+            !limiter
+                .check_at(subject, RateLimitOperation::Read, now)
+                .is_allowed()
+        );
+    }
+
+    #[test]
+    fn evicts_least_recently_seen_buckets_once_refilled() {
+        let limiter = RateLimiter::with_limits(create_limits(1), 2);
+        let first = RateLimitSubject::Anonymous(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)));
+        let second = RateLimitSubject::Anonymous(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2)));
+        let third = RateLimitSubject::Anonymous(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 3)));
+        let now = Instant::now();
+
+        for subject in [first, second, first] {
+            limiter.check_at(subject, RateLimitOperation::Read, now);
+        }
+        limiter.check_at(
+            third,
+            RateLimitOperation::Read,
+            now + Duration::from_secs(1),
+        );
+
+        let buckets = limiter.buckets.lock().unwrap();
+        assert_eq!(buckets.tracked.len(), 2);
+        assert!(
+            buckets
+                .tracked
+                .contains_key(&(first, RateLimitOperation::Read))
+        );
+        assert!(
+            !buckets
+                .tracked
+                .contains_key(&(second, RateLimitOperation::Read))
+        );
+    }
+
+    #[test]
+    fn new_clients_share_the_overflow_bucket_while_tracked_buckets_are_active() {
+        let limiter = RateLimiter::with_limits(create_limits(1), 1);
+        let active = RateLimitSubject::Anonymous(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)));
+        let now = Instant::now();
+        assert!(
+            limiter
+                .check_at(active, RateLimitOperation::Read, now)
+                .is_allowed()
+        );
+
+        let newcomers = (2..5).map(|host| {
+            let subject = RateLimitSubject::Anonymous(IpAddr::V4(Ipv4Addr::new(10, 0, 0, host)));
+            limiter
+                .check_at(subject, RateLimitOperation::Read, now)
+                .is_allowed()
+        });
+        assert_eq!(newcomers.collect::<Vec<_>>(), [true, false, false]);
+
+        assert!(
+            !limiter
+                .check_at(active, RateLimitOperation::Read, now)
+                .is_allowed()
+        );
+    }
+
+    #[test]
+    fn ipv6_clients_share_a_bucket_per_64_prefix() {
+        let limiter = create_limiter();
+        let address = |ip: &str| RateLimitSubject::Anonymous(ip.parse().unwrap());
+        let now = Instant::now();
+
+        assert!(
+            limiter
+                .check_at(address("2001:db8::1"), RateLimitOperation::Write, now)
+                .is_allowed()
+        );
+        assert!(
+            !limiter
+                .check_at(
+                    address("2001:db8::ffff:1:2:3"),
+                    RateLimitOperation::Write,
+                    now
+                )
+                .is_allowed()
+        );
+        assert!(
+            limiter
+                .check_at(address("2001:db8:0:1::1"), RateLimitOperation::Write, now)
+                .is_allowed()
+        );
+        assert!(
+            limiter
+                .check_at(address("::ffff:10.0.0.1"), RateLimitOperation::Write, now)
+                .is_allowed()
+        );
+        assert!(
+            !limiter
+                .check_at(address("10.0.0.1"), RateLimitOperation::Write, now)
+                .is_allowed()
+        );
+    }
+}
@@ -0,0 +1,112 @@
+use crate::configuration::TlsConfiguration;
+use anyhow::Context;
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+use tokio_rustls::TlsAcceptor;
+use tokio_rustls::server::TlsStream;
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::transport::{Certificate, Identity, ServerTlsConfig};
+
+/// Handshakes that take longer than this are dropped.
+const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+/// Number of established connections waiting to be picked up by the server.
+const TLS_ACCEPT_BACKLOG: usize = 128;
+
+/// Selects the process-wide rustls crypto provider.
+///
+/// Dependencies enable both the `ring` and the `aws-lc-rs` backends, so rustls cannot pick one
+/// on its own and panics when a TLS configuration is built without an explicit provider.
+pub(crate) fn install_crypto_provider() {
+    // Fails only when a provider is already installed, which is just as good.
+    let _ = rustls::crypto::ring::default_provider().install_default();
+}
+
+/// rustls configuration of the HTTP listener.
+pub(crate) fn http_tls_config(config: &TlsConfiguration) -> anyhow::Result<rustls::ServerConfig> {
+    let certificates = CertificateDer::pem_file_iter(config.get_certificate_path())
+        .and_then(|certificates| certificates.collect::<Result<Vec<_>, _>>())
+        .with_context(|| {
+            format!(
+                "failed to read certificate {}",
+                config.get_certificate_path()
+            )
+        })?;
+    let private_key =
+        PrivateKeyDer::from_pem_file(config.get_private_key_path()).with_context(|| {
+            format!(
+                "failed to read private key {}",
+                config.get_private_key_path()
+            )
+        })?;
+    Ok(rustls::ServerConfig::builder()
+        .with_no_client_auth()
+        .with_single_cert(certificates, private_key)?)
+}
+
+/// rustls configuration of the single-port listener, which negotiates HTTP/2 for gRPC and either
+/// HTTP version for the HTTP API.
+pub(crate) fn single_port_tls_config(
+    config: &TlsConfiguration,
+) -> anyhow::Result<rustls::ServerConfig> {
+    let mut tls_config = http_tls_config(config)?;
+    tls_config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+    Ok(tls_config)
+}
+
+/// Accepts connections on `listener` and performs the TLS handshakes concurrently, yielding the
+/// established streams. Stops accepting once the returned stream is dropped.
+pub(crate) fn tls_incoming(
+    listener: TcpListener,
+    tls_config: rustls::ServerConfig,
+) -> ReceiverStream<std::io::Result<TlsStream<TcpStream>>> {
+    let acceptor = TlsAcceptor::from(Arc::new(tls_config));
+    let (sender, receiver) = mpsc::channel(TLS_ACCEPT_BACKLOG);
+    tokio::spawn(async move {
+        loop {
+            let stream = tokio::select! {
+                accepted = listener.accept() => match accepted {
+                    Ok((stream, _)) => stream,
+                    Err(err) => {
+                        tracing::warn!("Failed to accept connection: {}", err);
+                        continue;
+                    }
+                },
+                _ = sender.closed() => return,
+            };
+            let _ = stream.set_nodelay(true);
+            let acceptor = acceptor.clone();
+            let sender = sender.clone();
+            tokio::spawn(async move {
+                match tokio::time::timeout(TLS_HANDSHAKE_TIMEOUT, acceptor.accept(stream)).await {
+                    Ok(Ok(stream)) => {
+                        let _ = sender.send(Ok(stream)).await;
+                    }
+                    Ok(Err(err)) => tracing::debug!("TLS handshake failed: {}", err),
+                    Err(_) => tracing::debug!("TLS handshake timed out"),
+                }
+            });
+        }
+    });
+    ReceiverStream::new(receiver)
+}
+
+/// TLS configuration of the gRPC listener; clients must present a certificate signed by the
+/// client CA when one is configured.
+pub(crate) fn grpc_tls_config(config: &TlsConfiguration) -> anyhow::Result<ServerTlsConfig> {
+    let certificate = read_pem(config.get_certificate_path())?;
+    let private_key = read_pem(config.get_private_key_path())?;
+    let mut tls_config =
+        ServerTlsConfig::new().identity(Identity::from_pem(certificate, private_key));
+    if let Some(client_ca_path) = config.get_client_ca_path() {
+        tls_config = tls_config.client_ca_root(Certificate::from_pem(read_pem(client_ca_path)?));
+    }
+    Ok(tls_config)
+}
+
+fn read_pem(path: &str) -> anyhow::Result<Vec<u8>> {
+    std::fs::read(path).with_context(|| format!("failed to read {}", path))
+}
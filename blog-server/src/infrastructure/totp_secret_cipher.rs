@@ -0,0 +1,133 @@
+use crate::domain::value_objects::{Identification, TotpSecret};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use ring::aead::{AES_256_GCM, Aad, LessSafeKey, NONCE_LEN, Nonce, UnboundKey};
+use ring::rand::{SecureRandom, SystemRandom};
+use secrecy::{ExposeSecret, SecretString};
+use sha2::{Digest, Sha256};
+
+/// Prefix of stored secrets encrypted by [`TotpSecretCipher`].
+const ENCRYPTED_PREFIX: &str = "enc:v1:";
+
+/// Encrypts TOTP secrets with AES-256-GCM before they are written to the database.
+///
+/// The ciphertext is bound to the user's id, so a secret copied to another row fails to
+/// decrypt. Changing the key makes every stored secret unreadable.
+pub(crate) struct TotpSecretCipher {
+    key: LessSafeKey,
+    random: SystemRandom,
+}
+
+impl TotpSecretCipher {
+    pub(crate) fn new(key: &SecretString) -> anyhow::Result<Self> {
+        let key = Sha256::digest(key.expose_secret().as_bytes());
+        let key = UnboundKey::new(&AES_256_GCM, &key)
+            .map_err(|_| anyhow::anyhow!("failed to create totp secret key"))?;
+        Ok(Self {
+            key: LessSafeKey::new(key),
+            random: SystemRandom::new(),
+        })
+    }
+
+    pub(crate) fn encrypt(
+        &self,
+        user_id: &Identification,
+        secret: &TotpSecret,
+    ) -> anyhow::Result<String> {
+        let mut nonce = [0u8; NONCE_LEN];
+        self.random
+            .fill(&mut nonce)
+            .map_err(|_| anyhow::anyhow!("failed to generate nonce"))?;
+        let mut in_out = secret.as_ref().expose_secret().as_bytes().to_vec();
+        self.key
+            .seal_in_place_append_tag(
+                Nonce::assume_unique_for_key(nonce),
+                Aad::from(user_id.as_ref().as_bytes()),
+                &mut in_out,
+            )
+            .map_err(|_| anyhow::anyhow!("failed to encrypt totp secret"))?;
+
+        let mut encrypted = nonce.to_vec();
+        encrypted.extend_from_slice(&in_out);
+        Ok(format!(
+            "{}{}",
+            ENCRYPTED_PREFIX,
+            STANDARD.encode(encrypted)
+        ))
+    }
+
+    pub(crate) fn decrypt(
+        &self,
+        user_id: &Identification,
+        stored: String,
+    ) -> anyhow::Result<TotpSecret> {
+        let encrypted = stored
+            .strip_prefix(ENCRYPTED_PREFIX)
+            .ok_or_else(|| anyhow::anyhow!("totp secret is not encrypted"))?;
+        let mut encrypted = STANDARD.decode(encrypted)?;
+        if encrypted.len() < NONCE_LEN {
+            return Err(anyhow::anyhow!("encrypted totp secret is too short"));
+        }
+        let mut in_out = encrypted.split_off(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(&encrypted)
+            .map_err(|_| anyhow::anyhow!("invalid totp secret nonce"))?;
+        let secret = self
+            .key
+            .open_in_place(nonce, Aad::from(user_id.as_ref().as_bytes()), &mut in_out)
+            .map_err(|_| anyhow::anyhow!("failed to decrypt totp secret"))?;
+        Ok(TotpSecret::from(SecretString::from(String::from_utf8(
+            secret.to_vec(),
+        )?)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cipher(key: &str) -> TotpSecretCipher {
+        TotpSecretCipher::new(&SecretString::from(key)).unwrap()
+    }
+
+    fn secret() -> TotpSecret {
+        TotpSecret::from(SecretString::from("JBSWY3DPEHPK3PXP"))
+    }
+
+    #[test]
+    fn decrypt_restores_encrypted_secret() {
+        let cipher = cipher("0123456789abcdef0123456789abcdef");
+        let user_id = Identification::new();
+
+        let encrypted = cipher.encrypt(&user_id, &secret()).unwrap();
+
+        assert!(encrypted.starts_with(ENCRYPTED_PREFIX));
+        assert!(!encrypted.contains("JBSWY3DPEHPK3PXP"));
+        assert_eq!(cipher.decrypt(&user_id, encrypted).unwrap(), secret());
+    }
+
+    #[test]
+    fn decrypt_rejects_other_user_or_key() {
+        let cipher_a = cipher("0123456789abcdef0123456789abcdef");
+        let cipher_b = cipher("fedcba9876543210fedcba9876543210");
+        let user_id = Identification::new();
+        let encrypted = cipher_a.encrypt(&user_id, &secret()).unwrap();
+
+        assert!(
+            cipher_a
+                .decrypt(&Identification::new(), encrypted.clone())
+                .is_err()
+        );
+        assert!(cipher_b.decrypt(&user_id, encrypted).is_err());
+    }
+
+    #[test]
+    fn decrypt_rejects_unencrypted_secrets() {
+        let cipher = cipher("0123456789abcdef0123456789abcdef");
+
+        assert!(
+            cipher
+                .decrypt(&Identification::new(), "JBSWY3DPEHPK3PXP".to_string())
+                .is_err()
+        );
+    }
+}
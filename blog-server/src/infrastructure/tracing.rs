@@ -1,11 +1,58 @@
+use crate::configuration::TelemetryConfiguration;
+use opentelemetry::global;
+use opentelemetry::trace::TracerProvider;
+use opentelemetry_otlp::{SpanExporter, WithExportConfig};
+use opentelemetry_sdk::Resource;
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use opentelemetry_sdk::trace::SdkTracerProvider;
 use tracing::subscriber::set_global_default;
 use tracing_log::LogTracer;
 use tracing_subscriber::layer::SubscriberExt;
-use tracing_subscriber::{EnvFilter, Registry, fmt};
+use tracing_subscriber::{EnvFilter, Registry, fmt, reload};
 
-pub(crate) fn initialize_tracing_subscribe(env_filter: &str) -> anyhow::Result<()> {
-    let env_filter =
-        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(env_filter));
+/// Flushes the spans still buffered for export when dropped.
+pub(crate) struct TracingGuard {
+    tracer_provider: Option<SdkTracerProvider>,
+}
+
+impl Drop for TracingGuard {
+    fn drop(&mut self) {
+        if let Some(tracer_provider) = self.tracer_provider.take()
+            && let Err(err) = tracer_provider.shutdown()
+        {
+            eprintln!("Failed to flush spans: {}", err);
+        }
+    }
+}
+
+/// Replaces the log filter of the running subscriber.
+#[derive(Clone)]
+pub(crate) struct LogFilter {
+    handle: reload::Handle<EnvFilter, Registry>,
+    from_env: bool,
+}
+
+impl LogFilter {
+    /// Applies new filter directives. Returns `false` without changing anything when the filter
+    /// is set by the `RUST_LOG` environment variable, which takes precedence.
+    pub(crate) fn set(&self, directives: &str) -> anyhow::Result<bool> {
+        if self.from_env {
+            return Ok(false);
+        }
+        self.handle.reload(EnvFilter::try_new(directives)?)?;
+        Ok(true)
+    }
+}
+
+pub(crate) fn initialize_tracing_subscribe(
+    env_filter: &str,
+    telemetry: &TelemetryConfiguration,
+) -> anyhow::Result<(TracingGuard, LogFilter)> {
+    let (env_filter, from_env) = match EnvFilter::try_from_default_env() {
+        Ok(env_filter) => (env_filter, true),
+        Err(_) => (EnvFilter::new(env_filter), false),
+    };
+    let (env_filter, handle) = reload::Layer::new(env_filter);
     let json_layer = fmt::layer()
         .json()
         .with_timer(fmt::time::ChronoUtc::new("%Y-%m-%dT%H:%M:%S%.3fZ".into()))
@@ -13,8 +60,38 @@ pub(crate) fn initialize_tracing_subscribe(env_filter: &str) -> anyhow::Result<(
         .with_target(true)
         .with_current_span(true)
         .flatten_event(true);
-    let subscriber = Registry::default().with(env_filter).with(json_layer);
+    let tracer_provider = if telemetry.is_enabled() {
+        Some(create_tracer_provider(telemetry)?)
+    } else {
+        None
+    };
+    let otel_layer = tracer_provider.as_ref().map(|tracer_provider| {
+        tracing_opentelemetry::layer().with_tracer(tracer_provider.tracer("blog-server"))
+    });
+    let subscriber = Registry::default()
+        .with(env_filter)
+        .with(json_layer)
+        .with(otel_layer);
+    global::set_text_map_propagator(TraceContextPropagator::new());
     LogTracer::init()?;
     set_global_default(subscriber)?;
-    Ok(())
+    Ok((
+        TracingGuard { tracer_provider },
+        LogFilter { handle, from_env },
+    ))
+}
+
+fn create_tracer_provider(telemetry: &TelemetryConfiguration) -> anyhow::Result<SdkTracerProvider> {
+    let exporter = SpanExporter::builder()
+        .with_tonic()
+        .with_endpoint(telemetry.get_otlp_endpoint())
+        .build()?;
+    Ok(SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(
+            Resource::builder()
+                .with_service_name(telemetry.get_service_name().to_string())
+                .build(),
+        )
+        .build())
 }
@@ -1,8 +1,10 @@
 use crate::infrastructure::initialize_tracing_subscribe;
 use crate::server::Server;
+use clap::Parser;
 
 mod api;
 mod application;
+mod args;
 mod configuration;
 mod domain;
 mod infrastructure;
@@ -10,9 +12,17 @@ mod server;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
+    let args = args::Args::parse();
     let config = configuration::Configuration::read_configuration()?;
-    initialize_tracing_subscribe(config.get_server_configuration().get_log_level())?;
-    let server = Server::start(config).await?;
+    if args.check_config {
+        println!("configuration is valid");
+        return Ok(());
+    }
+    let (_tracing_guard, log_filter) = initialize_tracing_subscribe(
+        config.get_server_configuration().get_log_level(),
+        config.get_telemetry_configuration(),
+    )?;
+    let server = Server::start(config, log_filter).await?;
     server.run_until_shutdown().await?;
     Ok(())
 }
@@ -1,144 +1,593 @@
+use crate::api::ForwardSecret;
 use crate::api::grpc::blog::blog_service_server::BlogServiceServer;
 use crate::api::grpc::grpc_handlers::blog_service::GrpcBlogService;
+use crate::api::grpc::grpc_handlers::health_service::health_service;
+use crate::api::grpc::middleware::auth::AuthLayer;
+use crate::api::grpc::middleware::cors::cors_layer;
+use crate::api::grpc::middleware::http_forward::HttpForwardLayer;
+use crate::api::grpc::middleware::metrics::MetricsLayer;
+use crate::api::grpc::middleware::rate_limit::RateLimitLayer;
+use crate::api::grpc::middleware::trace_context::grpc_request_span;
 use crate::api::grpc::proto;
-use crate::api::http::http_handlers::{auth, posts};
+use crate::api::http::http_handlers::{auth, events, health, metrics, posts, tokens};
 use crate::api::http::middleware;
-use crate::application::contracts::{PostRepository, UserRepository};
+use crate::api::http::middleware::rate_limit::ForwardedConnection;
+use crate::application::contracts::{
+    ApiTokenRepository, HealthCheck, IdentityProvider, PostEventBus, PostRepository, UnitOfWork,
+    UserRepository,
+};
 use crate::configuration::Configuration;
-use crate::infrastructure::{JwtService, PostgresPostRepository, PostgresUserRepository};
+#[cfg(unix)]
+use crate::infrastructure::bind_unix_socket;
+use crate::infrastructure::{
+    AllowedOrigins, BroadcastPostEventBus, CachedPostRepository, CachedUnitOfWork,
+    ChallengeAttempts, ConfigurationReloader, DatabasePools, InMemoryPostCacheStore, JwtService,
+    LogFilter, Metrics, OidcClient, PostCacheStore, PostgresApiTokenRepository,
+    PostgresHealthCheck, PostgresPostRepository, PostgresUnitOfWork, PostgresUserRepository,
+    RateLimiter, TotpSecretCipher, grpc_tls_config, http_tls_config, install_crypto_provider,
+    single_port_tls_config, tls_incoming, watch_configuration,
+};
 use actix_cors::Cors;
+use actix_web::dev::ServerHandle;
 use actix_web::middleware::from_fn;
 use actix_web::{App, HttpServer, web};
-use sqlx::postgres::PgPoolOptions;
+use anyhow::Context;
+use std::net::{Ipv4Addr, SocketAddr, TcpListener};
 use std::sync::Arc;
-use tokio::task::JoinHandle;
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::task::{JoinError, JoinHandle, JoinSet};
+use tonic::transport::server::TcpIncoming;
+use tonic_web::GrpcWebLayer;
 use tower_http::trace::TraceLayer;
 use tracing_actix_web::TracingLogger;
 
+/// Number of post events buffered for slow watchers before they start lagging.
+const POST_EVENTS_CAPACITY: usize = 1024;
+/// Request body limit for post writes, large enough for a full bulk import.
+const POSTS_PAYLOAD_LIMIT: usize = 16 * 1024 * 1024;
+
+/// Shared services handed to both the HTTP and the gRPC server.
+struct Dependencies {
+    user_repository: Arc<dyn UserRepository>,
+    post_repository: Arc<dyn PostRepository>,
+    api_token_repository: Arc<dyn ApiTokenRepository>,
+    unit_of_work: Arc<dyn UnitOfWork>,
+    post_events: Arc<dyn PostEventBus>,
+    jwt_service: Arc<JwtService>,
+    challenge_attempts: Arc<ChallengeAttempts>,
+    rate_limiter: Arc<RateLimiter>,
+    allowed_origins: Arc<AllowedOrigins>,
+    identity_provider: Option<Arc<dyn IdentityProvider>>,
+    health_check: Arc<dyn HealthCheck>,
+    metrics: Arc<Metrics>,
+}
+
 pub(crate) struct Server {
     http_server: JoinHandle<std::io::Result<()>>,
-    grpc_server: JoinHandle<Result<(), tonic::transport::Error>>,
+    http_handle: ServerHandle,
+    grpc_server: JoinHandle<anyhow::Result<()>>,
+    grpc_shutdown: watch::Sender<()>,
+    config_watcher: JoinHandle<()>,
+    database_pools: Arc<DatabasePools>,
+    post_events: Arc<dyn PostEventBus>,
+    shutdown_timeout: Duration,
 }
 
 impl Server {
-    pub(crate) async fn start(config: Configuration) -> anyhow::Result<Self> {
+    pub(crate) async fn start(
+        config: Configuration,
+        log_filter: LogFilter,
+    ) -> anyhow::Result<Self> {
+        install_crypto_provider();
+        let database_config = config.get_database_configuration();
         let pg_pool = Arc::new(
-            PgPoolOptions::new()
-                .connect_lazy_with(config.get_database_configuration().get_connection_options()),
+            database_config
+                .get_pool_options()
+                .connect_lazy_with(database_config.get_connection_options()),
         );
-        let user_repository: Arc<dyn UserRepository> =
-            Arc::new(PostgresUserRepository::new(Arc::clone(&pg_pool)));
-        let post_repository: Arc<dyn PostRepository> =
-            Arc::new(PostgresPostRepository::new(Arc::clone(&pg_pool)));
-        let jwt_service = Arc::new(JwtService::new(
-            config.get_jwt_configuration().get_secret().clone(),
+        let database_pools = Arc::new(DatabasePools::new(
+            Arc::clone(&pg_pool),
+            database_config
+                .get_read_replica_connection_options()
+                .into_iter()
+                .map(|options| {
+                    database_config
+                        .get_pool_options()
+                        .connect_lazy_with(options)
+                })
+                .collect(),
         ));
+        let oidc_config = config.get_oidc_configuration();
+        let totp_secret_cipher = Arc::new(TotpSecretCipher::new(
+            config.get_two_factor_configuration().get_encryption_key(),
+        )?);
+        let (post_repository, unit_of_work) = post_storage(
+            &config,
+            Arc::clone(&database_pools),
+            Arc::clone(&totp_secret_cipher),
+        );
+        let dependencies = Dependencies {
+            user_repository: Arc::new(PostgresUserRepository::new(
+                Arc::clone(&database_pools),
+                totp_secret_cipher,
+            )),
+            post_repository,
+            api_token_repository: Arc::new(PostgresApiTokenRepository::new(Arc::clone(&pg_pool))),
+            unit_of_work,
+            post_events: Arc::new(BroadcastPostEventBus::new(POST_EVENTS_CAPACITY)),
+            jwt_service: Arc::new(JwtService::new(
+                config.get_jwt_configuration().get_secret().clone(),
+            )),
+            challenge_attempts: Arc::new(ChallengeAttempts::new()),
+            rate_limiter: Arc::new(RateLimiter::new(config.get_rate_limit_configuration())),
+            allowed_origins: Arc::new(AllowedOrigins::new(
+                config.get_server_configuration().get_white_list(),
+            )),
+            identity_provider: if oidc_config.is_enabled() {
+                Some(Arc::new(OidcClient::new(oidc_config)?))
+            } else {
+                None
+            },
+            health_check: Arc::new(PostgresHealthCheck::new(Arc::clone(&pg_pool))),
+            metrics: Arc::new(Metrics::new(Arc::clone(&pg_pool))?),
+        };
+
+        // In single-port mode the HTTP server only accepts requests forwarded by the gRPC server.
+        let forward_listener = if config.get_server_configuration().is_single_port() {
+            Some(TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?)
+        } else {
+            None
+        };
+        let forward_address = forward_listener
+            .as_ref()
+            .map(TcpListener::local_addr)
+            .transpose()?;
 
-        let grpc_server =
-            run_grpc_server(&config, &user_repository, &post_repository, &jwt_service)?;
-        let http_server =
-            run_http_server(&config, &user_repository, &post_repository, &jwt_service)?;
+        let forward_secret = ForwardSecret::generate();
+
+        let (grpc_shutdown, grpc_shutdown_signal) = watch::channel(());
+        let grpc_server = run_grpc_server(
+            &config,
+            &dependencies,
+            grpc_shutdown_signal,
+            forward_address,
+            forward_secret.clone(),
+        )?;
+        let (http_server, http_handle) =
+            run_http_server(&config, &dependencies, forward_listener, forward_secret)?;
+        let config_watcher = watch_configuration(ConfigurationReloader::new(
+            config.clone(),
+            log_filter,
+            Arc::clone(&dependencies.allowed_origins),
+            Arc::clone(&dependencies.rate_limiter),
+        ))?;
         Ok(Self {
             http_server,
+            http_handle,
             grpc_server,
+            grpc_shutdown,
+            config_watcher,
+            database_pools,
+            post_events: dependencies.post_events,
+            shutdown_timeout: config.get_server_configuration().get_shutdown_timeout(),
         })
     }
 
+    /// Runs until SIGINT or SIGTERM is received or one of the servers stops, then shuts both
+    /// down gracefully: they stop accepting connections, watch streams are ended and in-flight
+    /// requests get `shutdown_timeout` to finish before they are cancelled.
+    ///
+    /// Fails when a server stopped on its own or requests were still running at the timeout.
     pub(crate) async fn run_until_shutdown(self) -> anyhow::Result<()> {
+        let Self {
+            mut http_server,
+            http_handle,
+            mut grpc_server,
+            grpc_shutdown,
+            config_watcher,
+            database_pools,
+            post_events,
+            shutdown_timeout,
+        } = self;
+        let mut http_result = None;
+        let mut grpc_result = None;
+
         tokio::select! {
-            o = self.grpc_server => match o {
-                Ok(_) => Ok(()),
-                Err(e) => Err(e.into()),
-            },
-            o = self.http_server => match o {
-                Ok(_) => Ok(()),
-                Err(e) => Err(e.into()),
-            },
+            signal = shutdown_signal() => tracing::info!("Received {}, shutting down", signal),
+            result = &mut http_server => {
+                tracing::error!("HTTP server stopped, shutting down");
+                http_result = Some(server_result("HTTP", result));
+            }
+            result = &mut grpc_server => {
+                tracing::error!("gRPC server stopped, shutting down");
+                grpc_result = Some(server_result("gRPC", result));
+            }
+        }
+        let stopped_early = http_result.is_some() || grpc_result.is_some();
+
+        config_watcher.abort();
+        post_events.close();
+        let http_stopped = http_handle.stop(true);
+        // Fails only when the gRPC server has already stopped.
+        let _ = grpc_shutdown.send(());
+        let http_abort = http_server.abort_handle();
+        let grpc_abort = grpc_server.abort_handle();
+        let drained = tokio::time::timeout(shutdown_timeout, async {
+            http_stopped.await;
+            let http_result = match http_result {
+                Some(result) => result,
+                None => server_result("HTTP", http_server.await),
+            };
+            let grpc_result = match grpc_result {
+                Some(result) => result,
+                None => server_result("gRPC", grpc_server.await),
+            };
+            http_result.and(grpc_result)
+        })
+        .await;
+
+        let result = match drained {
+            Ok(result) => result,
+            Err(_) => {
+                http_abort.abort();
+                grpc_abort.abort();
+                Err(anyhow::anyhow!(
+                    "in-flight requests did not finish within {} seconds",
+                    shutdown_timeout.as_secs()
+                ))
+            }
+        };
+        database_pools.close().await;
+
+        match result {
+            Ok(()) if stopped_early => Err(anyhow::anyhow!("server stopped unexpectedly")),
+            Ok(()) => {
+                tracing::info!("Shutdown complete");
+                Ok(())
+            }
+            Err(err) => Err(err),
+        }
+    }
+}
+
+/// Resolves with the name of the first shutdown signal received.
+async fn shutdown_signal() -> &'static str {
+    let interrupt = async {
+        if let Err(err) = tokio::signal::ctrl_c().await {
+            tracing::error!("Failed to listen for SIGINT: {}", err);
+            std::future::pending::<()>().await;
+        }
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                terminate.recv().await;
+            }
+            Err(err) => {
+                tracing::error!("Failed to listen for SIGTERM: {}", err);
+                std::future::pending::<()>().await;
+            }
         }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = interrupt => "SIGINT",
+        _ = terminate => "SIGTERM",
+    }
+}
+
+fn server_result<E>(name: &str, result: Result<Result<(), E>, JoinError>) -> anyhow::Result<()>
+where
+    E: Into<anyhow::Error>,
+{
+    match result {
+        Ok(Ok(())) => Ok(()),
+        Ok(Err(err)) => Err(err.into().context(format!("{} server failed", name))),
+        Err(err) => Err(anyhow::Error::from(err).context(format!("{} server panicked", name))),
     }
 }
 
+/// The post repository and the unit of work. When caching is enabled they share an in-memory
+/// post cache, which the repository reads through and both invalidate on writes.
+fn post_storage(
+    config: &Configuration,
+    database_pools: Arc<DatabasePools>,
+    totp_secret_cipher: Arc<TotpSecretCipher>,
+) -> (Arc<dyn PostRepository>, Arc<dyn UnitOfWork>) {
+    let repository = Arc::new(PostgresPostRepository::new(Arc::clone(&database_pools)));
+    let unit_of_work = Arc::new(PostgresUnitOfWork::new(
+        Arc::clone(&database_pools),
+        totp_secret_cipher,
+    ));
+    let cache_config = config.get_cache_configuration();
+    if !cache_config.is_enabled() {
+        return (repository, unit_of_work);
+    }
+    let cache: Arc<dyn PostCacheStore> = Arc::new(InMemoryPostCacheStore::new(
+        cache_config.get_capacity(),
+        cache_config.get_ttl(),
+    ));
+    (
+        Arc::new(CachedPostRepository::new(repository, Arc::clone(&cache))),
+        Arc::new(CachedUnitOfWork::new(unit_of_work, cache)),
+    )
+}
+
+/// Serves gRPC on every configured TCP address and on the Unix socket, if any. TLS, when
+/// enabled, is only terminated on the TCP listeners.
+///
+/// With a `forward_address` the server takes over the HTTP listeners and forwards every request
+/// that is not gRPC to the HTTP server at that address.
 fn run_grpc_server(
     config: &Configuration,
-    user_repository: &Arc<dyn UserRepository>,
-    post_repository: &Arc<dyn PostRepository>,
-    jwt_service: &Arc<JwtService>,
-) -> anyhow::Result<JoinHandle<Result<(), tonic::transport::Error>>> {
-    let grpc_address = config.get_server_configuration().get_grpc_address()?;
+    dependencies: &Dependencies,
+    shutdown_signal: watch::Receiver<()>,
+    forward_address: Option<SocketAddr>,
+    forward_secret: ForwardSecret,
+) -> anyhow::Result<JoinHandle<anyhow::Result<()>>> {
+    let server_config = config.get_server_configuration();
+    let (grpc_addresses, grpc_unix_socket) = match forward_address {
+        Some(_) => (
+            server_config.get_http_addresses()?,
+            server_config.get_http_unix_socket(),
+        ),
+        None => (
+            server_config.get_grpc_addresses()?,
+            server_config.get_grpc_unix_socket(),
+        ),
+    };
     let reflection_service = tonic_reflection::server::Builder::configure()
         .register_encoded_file_descriptor_set(proto::FILE_DESCRIPTOR_SET)
+        .register_encoded_file_descriptor_set(tonic_health::pb::FILE_DESCRIPTOR_SET)
         .build_v1()?;
+    let health_service = health_service(Arc::clone(&dependencies.health_check));
 
-    let blog_service = GrpcBlogService::new(
-        Arc::clone(user_repository),
-        Arc::clone(post_repository),
-        Arc::clone(jwt_service),
+    let blog_service = BlogServiceServer::new(GrpcBlogService::new(
+        Arc::clone(&dependencies.user_repository),
+        Arc::clone(&dependencies.post_repository),
+        Arc::clone(&dependencies.api_token_repository),
+        Arc::clone(&dependencies.unit_of_work),
+        Arc::clone(&dependencies.post_events),
+        Arc::clone(&dependencies.jwt_service),
+        Arc::clone(&dependencies.challenge_attempts),
+        Arc::clone(&dependencies.metrics),
+    ));
+    let metrics_layer = MetricsLayer::new(Arc::clone(&dependencies.metrics))?;
+    let rate_limit_layer = RateLimitLayer::new(
+        Arc::clone(&dependencies.rate_limiter),
+        Arc::clone(&dependencies.jwt_service),
+    );
+    let auth_layer = AuthLayer::new(
+        Arc::clone(&dependencies.jwt_service),
+        Arc::clone(&dependencies.api_token_repository),
+        Arc::clone(&dependencies.unit_of_work),
     );
+    let cors_layer = cors_layer(Arc::clone(&dependencies.allowed_origins));
+    let http_forward_layer = HttpForwardLayer::new(forward_address, forward_secret)?;
+    let router = |server: tonic::transport::Server| {
+        server
+            .accept_http1(true)
+            .layer(http_forward_layer.clone())
+            .layer(TraceLayer::new_for_grpc().make_span_with(grpc_request_span))
+            .layer(cors_layer.clone())
+            .layer(GrpcWebLayer::new())
+            .layer(metrics_layer.clone())
+            .layer(rate_limit_layer.clone())
+            .layer(auth_layer.clone())
+            .add_service(blog_service.clone())
+            .add_service(health_service.clone())
+            .add_service(reflection_service.clone())
+    };
+    let shutdown = move || {
+        let mut shutdown_signal = shutdown_signal.clone();
+        async move {
+            let _ = shutdown_signal.changed().await;
+        }
+    };
+
+    let tls = server_config.get_tls_configuration();
+    let mut listeners = JoinSet::new();
+    // tonic only offers HTTP/2 during the TLS handshake, which locks out HTTP/1.1 clients of the
+    // HTTP API, so the single-port listener terminates TLS itself.
+    if tls.is_enabled() && forward_address.is_some() {
+        let tcp_server = tonic::transport::Server::builder();
+        let tls_config = single_port_tls_config(tls)?;
+        for address in grpc_addresses {
+            let listener = std::net::TcpListener::bind(address)
+                .with_context(|| format!("failed to bind gRPC listener to {}", address))?;
+            listener.set_nonblocking(true)?;
+            let incoming = tls_incoming(
+                tokio::net::TcpListener::from_std(listener)?,
+                tls_config.clone(),
+            );
+            listeners.spawn(
+                router(tcp_server.clone()).serve_with_incoming_shutdown(incoming, shutdown()),
+            );
+        }
+    } else {
+        let mut tcp_server = tonic::transport::Server::builder();
+        if tls.is_enabled() {
+            tcp_server = tcp_server.tls_config(grpc_tls_config(tls)?)?;
+        }
+        for address in grpc_addresses {
+            let incoming = TcpIncoming::bind(address)
+                .with_context(|| format!("failed to bind gRPC listener to {}", address))?
+                .with_nodelay(Some(true));
+            listeners.spawn(
+                router(tcp_server.clone()).serve_with_incoming_shutdown(incoming, shutdown()),
+            );
+        }
+    }
+    #[cfg(unix)]
+    if let Some(path) = grpc_unix_socket {
+        let listener = tokio::net::UnixListener::from_std(bind_unix_socket(path)?)?;
+        listeners.spawn(
+            router(tonic::transport::Server::builder()).serve_with_incoming_shutdown(
+                tokio_stream::wrappers::UnixListenerStream::new(listener),
+                shutdown(),
+            ),
+        );
+    }
+    #[cfg(not(unix))]
+    if grpc_unix_socket.is_some() {
+        anyhow::bail!("Unix sockets are not supported on this platform");
+    }
 
+    // Stops at the first failing listener; dropping the set then aborts the others.
     Ok(tokio::spawn(async move {
-        tonic::transport::Server::builder()
-            .layer(TraceLayer::new_for_grpc())
-            .add_service(BlogServiceServer::new(blog_service))
-            .add_service(reflection_service)
-            .serve(grpc_address)
-            .await
+        while let Some(result) = listeners.join_next().await {
+            result??;
+        }
+        Ok(())
     }))
 }
 
+/// Serves the HTTP API on the configured listeners, or only on `forward_listener` in single-port
+/// mode.
 fn run_http_server(
     config: &Configuration,
-    user_repository: &Arc<dyn UserRepository>,
-    post_repository: &Arc<dyn PostRepository>,
-    jwt_service: &Arc<JwtService>,
-) -> anyhow::Result<JoinHandle<std::io::Result<()>>> {
-    let user_repository_data: web::Data<Arc<dyn UserRepository>> =
-        web::Data::new(Arc::clone(user_repository));
-    let post_repository_data: web::Data<Arc<dyn PostRepository>> =
-        web::Data::new(Arc::clone(post_repository));
-    let jwt_service_data = web::Data::new(Arc::clone(jwt_service));
-    let white_list = config
-        .get_server_configuration()
-        .get_white_list()
-        .iter()
-        .map(|origin| origin.to_string())
-        .collect::<Vec<String>>();
+    dependencies: &Dependencies,
+    forward_listener: Option<TcpListener>,
+    forward_secret: ForwardSecret,
+) -> anyhow::Result<(JoinHandle<std::io::Result<()>>, ServerHandle)> {
+    let user_repository_data = web::Data::new(Arc::clone(&dependencies.user_repository));
+    let post_repository_data = web::Data::new(Arc::clone(&dependencies.post_repository));
+    let api_token_repository_data = web::Data::new(Arc::clone(&dependencies.api_token_repository));
+    let unit_of_work_data = web::Data::new(Arc::clone(&dependencies.unit_of_work));
+    let post_events_data = web::Data::new(Arc::clone(&dependencies.post_events));
+    let jwt_service_data = web::Data::new(Arc::clone(&dependencies.jwt_service));
+    let challenge_attempts_data = web::Data::new(Arc::clone(&dependencies.challenge_attempts));
+    let rate_limiter_data = web::Data::new(Arc::clone(&dependencies.rate_limiter));
+    let health_check_data = web::Data::new(Arc::clone(&dependencies.health_check));
+    let metrics_data = web::Data::new(Arc::clone(&dependencies.metrics));
+    let identity_provider_data = dependencies
+        .identity_provider
+        .as_ref()
+        .map(|identity_provider| web::Data::new(Arc::clone(identity_provider)));
+    let oidc_config_data = web::Data::new(config.get_oidc_configuration().clone());
+    let cache_config_data = web::Data::new(config.get_cache_configuration().clone());
+    let allowed_origins = Arc::clone(&dependencies.allowed_origins);
 
     let server = HttpServer::new(move || {
-        let mut cors = Cors::default()
+        let allowed_origins = Arc::clone(&allowed_origins);
+        let cors = Cors::default()
+            .allowed_origin_fn(move |origin, _| allowed_origins.is_allowed(origin.as_bytes()))
             .allowed_methods(vec!["GET", "POST", "PUT", "DELETE", "OPTIONS"])
             .allow_any_header()
             .max_age(3600);
 
-        for origin in &white_list {
-            cors = cors.allowed_origin(origin);
-        }
-
-        App::new()
+        let mut app = App::new()
+            .wrap(from_fn(middleware::rate_limit::rate_limit_middleware))
+            .wrap(from_fn(middleware::metrics::metrics_middleware))
             .wrap(TracingLogger::default())
             .wrap(cors)
+            .service(metrics::export_metrics)
+            .service(
+                web::scope("/health")
+                    .service(health::liveness)
+                    .service(health::readiness),
+            )
             .service(
                 web::scope("/api")
                     .service(
                         web::scope("/auth")
                             .service(auth::register_user)
-                            .service(auth::login),
+                            .service(auth::login)
+                            .service(auth::verify_two_factor)
+                            .service(auth::oidc_login)
+                            .service(auth::oidc_callback)
+                            .service(
+                                web::scope("/oidc")
+                                    .wrap(from_fn(middleware::auth::auth_middleware))
+                                    .service(auth::oidc_link),
+                            )
+                            .service(
+                                web::scope("/2fa")
+                                    .wrap(from_fn(middleware::auth::auth_middleware))
+                                    .service(auth::enroll_two_factor)
+                                    .service(auth::confirm_two_factor),
+                            ),
                     )
+                    .service(events::watch_posts)
+                    .service(posts::batch_get_posts)
                     .service(posts::get_post)
                     .service(posts::get_post_list)
                     .service(
                         web::scope("/posts")
                             .wrap(from_fn(middleware::auth::auth_middleware))
+                            .app_data(web::JsonConfig::default().limit(POSTS_PAYLOAD_LIMIT))
+                            .service(posts::bulk_create_posts)
                             .service(posts::create_post)
                             .service(posts::update_post)
                             .service(posts::delete_post),
+                    )
+                    .service(
+                        web::scope("/tokens")
+                            .wrap(from_fn(middleware::auth::auth_middleware))
+                            .service(tokens::list_tokens)
+                            .service(tokens::create_token)
+                            .service(tokens::revoke_token),
                     ),
             )
             .app_data(user_repository_data.clone())
             .app_data(post_repository_data.clone())
+            .app_data(api_token_repository_data.clone())
+            .app_data(unit_of_work_data.clone())
+            .app_data(post_events_data.clone())
             .app_data(jwt_service_data.clone())
-    })
-    .bind(config.get_server_configuration().get_http_address())?
-    .run();
+            .app_data(challenge_attempts_data.clone())
+            .app_data(rate_limiter_data.clone())
+            .app_data(health_check_data.clone())
+            .app_data(metrics_data.clone())
+            .app_data(cache_config_data.clone());
+        if let Some(identity_provider_data) = &identity_provider_data {
+            app = app
+                .app_data(identity_provider_data.clone())
+                .app_data(oidc_config_data.clone());
+        }
+        app
+    });
+    let server_config = config.get_server_configuration();
+    let server = match forward_listener {
+        Some(listener) => server
+            .on_connect(move |_, extensions| {
+                extensions.insert(ForwardedConnection::new(forward_secret.clone()));
+            })
+            .listen(listener)?,
+        None => {
+            let mut server = server;
+            let http_addresses = server_config.get_http_addresses()?;
+            let http_unix_socket = server_config.get_http_unix_socket();
+            let tls = server_config.get_tls_configuration();
+            let tls_config = tls.is_enabled().then(|| http_tls_config(tls)).transpose()?;
+            for address in http_addresses {
+                server = match &tls_config {
+                    Some(tls_config) => server.bind_rustls_0_23(address, tls_config.clone()),
+                    None => server.bind(address),
+                }
+                .with_context(|| format!("failed to bind HTTP listener to {}", address))?;
+            }
+            // TLS, when enabled, is only terminated on the TCP listeners.
+            #[cfg(unix)]
+            if let Some(path) = http_unix_socket {
+                server = server.listen_uds(bind_unix_socket(path)?)?;
+            }
+            #[cfg(not(unix))]
+            if http_unix_socket.is_some() {
+                anyhow::bail!("Unix sockets are not supported on this platform");
+            }
+            server
+        }
+    };
+    let server = server
+        .disable_signals()
+        .shutdown_timeout(server_config.get_shutdown_timeout().as_secs())
+        .run();
 
-    Ok(tokio::spawn(server))
+    let handle = server.handle();
+    Ok((tokio::spawn(server), handle))
 }
@@ -0,0 +1,13 @@
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Only the `grpc-web` feature uses the generated client, so the default build does not
+    // need `protoc`.
+    if std::env::var_os("CARGO_FEATURE_GRPC_WEB").is_some() {
+        tonic_prost_build::configure()
+            .build_server(false)
+            .build_client(true)
+            .build_transport(false)
+            .compile_protos(&["src/proto/blog.proto"], &["src/proto"])?;
+    }
+    println!("cargo:rerun-if-changed=build.rs");
+    Ok(())
+}
@@ -0,0 +1,46 @@
+//! Post operations used by the components.
+//!
+//! The JSON HTTP API is used by default; the `grpc-web` feature switches to the client
+//! generated from `blog.proto`.
+
+#[cfg(feature = "grpc-web")]
+mod grpc_web;
+#[cfg(not(feature = "grpc-web"))]
+mod http;
+
+#[cfg(feature = "grpc-web")]
+pub use grpc_web::*;
+#[cfg(not(feature = "grpc-web"))]
+pub use http::*;
+
+use std::fmt::{Display, Formatter};
+
+/// One page of the post list.
+pub struct PostsPage {
+    pub posts: Vec<Post>,
+    pub total: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ApiError {
+    /// The request did not reach the server.
+    Request(String),
+    /// The server answered with something that could not be read.
+    InvalidResponse(String),
+    NotFound,
+    /// The server rejected the request with this message.
+    Rejected(String),
+}
+
+impl Display for ApiError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ApiError::Request(message) => write!(f, "Request failed: {}", message),
+            ApiError::InvalidResponse(message) => {
+                write!(f, "Failed to parse response: {}", message)
+            }
+            ApiError::NotFound => write!(f, "Post not found"),
+            ApiError::Rejected(message) => write!(f, "{}", message),
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use crate::api::{ApiError, PostsPage};
+use tonic::metadata::MetadataValue;
+use tonic::{Code, Request, Status};
+use tonic_web_wasm_client::Client;
+
+mod proto {
+    tonic::include_proto!("blog");
+}
+
+pub use proto::Post;
+use proto::blog_service_client::BlogServiceClient;
+
+const GRPC_WEB_URL: &str = "http://localhost:50051";
+
+pub fn format_timestamp(timestamp: &Option<prost_types::Timestamp>) -> String {
+    timestamp
+        .as_ref()
+        .map(ToString::to_string)
+        .unwrap_or_default()
+}
+
+pub async fn list_posts(limit: usize, offset: usize) -> Result<PostsPage, ApiError> {
+    let result = client()
+        .get_post_list(proto::GetPostListCommand {
+            limit: limit as u32,
+            offset: offset as u32,
+        })
+        .await?
+        .into_inner();
+    Ok(PostsPage {
+        posts: result.post,
+        total: result.total as usize,
+    })
+}
+
+pub async fn get_post(id: &str) -> Result<Post, ApiError> {
+    let post = client()
+        .get_post(proto::GetPostCommand { id: id.to_string() })
+        .await?
+        .into_inner();
+    Ok(post)
+}
+
+pub async fn create_post(token: &str, title: &str, content: &str) -> Result<Post, ApiError> {
+    let request = authorized(
+        proto::CreatePostCommand {
+            title: title.to_string(),
+            content: content.to_string(),
+        },
+        token,
+    )?;
+    Ok(client().create_post(request).await?.into_inner())
+}
+
+pub async fn update_post(
+    token: &str,
+    id: &str,
+    title: &str,
+    content: &str,
+) -> Result<Post, ApiError> {
+    let request = authorized(
+        proto::UpdatePostCommand {
+            id: id.to_string(),
+            title: title.to_string(),
+            content: content.to_string(),
+        },
+        token,
+    )?;
+    Ok(client().update_post(request).await?.into_inner())
+}
+
+pub async fn delete_post(token: &str, id: &str) -> Result<(), ApiError> {
+    let request = authorized(proto::DeletePostCommand { id: id.to_string() }, token)?;
+    client().delete_post(request).await?;
+    Ok(())
+}
+
+fn client() -> BlogServiceClient<Client> {
+    BlogServiceClient::new(Client::new(GRPC_WEB_URL.to_string()))
+}
+
+fn authorized<T>(message: T, token: &str) -> Result<Request<T>, ApiError> {
+    let mut request = Request::new(message);
+    let value = MetadataValue::try_from(format!("Bearer {}", token))
+        .map_err(|e| ApiError::Request(e.to_string()))?;
+    request.metadata_mut().insert("authorization", value);
+    Ok(request)
+}
+
+impl From<Status> for ApiError {
+    fn from(status: Status) -> Self {
+        match status.code() {
+            Code::NotFound => ApiError::NotFound,
+            Code::Unavailable | Code::Unknown => ApiError::Request(status.message().to_string()),
+            Code::DataLoss => ApiError::InvalidResponse(status.message().to_string()),
+            _ => ApiError::Rejected(status.message().to_string()),
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use crate::api::{ApiError, PostsPage};
+use gloo_net::http::{Request, Response};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+const API_URL: &str = "http://localhost:3000/api";
+
+#[derive(Clone, PartialEq, Debug, Deserialize)]
+pub struct Post {
+    pub id: String,
+    pub title: String,
+    pub content: String,
+    #[serde(rename = "user_id")]
+    pub author_id: String,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Deserialize)]
+struct PostsResponse {
+    posts: Vec<Post>,
+    total: usize,
+}
+
+#[derive(Serialize)]
+struct PostRequest<'a> {
+    title: &'a str,
+    content: &'a str,
+}
+
+#[derive(Deserialize)]
+struct ErrorResponse {
+    error: String,
+}
+
+pub fn format_timestamp(timestamp: &str) -> String {
+    timestamp.to_string()
+}
+
+pub async fn list_posts(limit: usize, offset: usize) -> Result<PostsPage, ApiError> {
+    let url = format!("{}/posts?limit={}&offset={}", API_URL, limit, offset);
+    let response = read_json::<PostsResponse>(Request::get(&url).send().await?).await?;
+    Ok(PostsPage {
+        posts: response.posts,
+        total: response.total,
+    })
+}
+
+pub async fn get_post(id: &str) -> Result<Post, ApiError> {
+    let url = format!("{}/posts/{}", API_URL, id);
+    read_json(Request::get(&url).send().await?).await
+}
+
+pub async fn create_post(token: &str, title: &str, content: &str) -> Result<Post, ApiError> {
+    let url = format!("{}/posts", API_URL);
+    let response = Request::post(&url)
+        .header("Authorization", &format!("Bearer {}", token))
+        .json(&PostRequest { title, content })?
+        .send()
+        .await?;
+    read_json(response).await
+}
+
+pub async fn update_post(
+    token: &str,
+    id: &str,
+    title: &str,
+    content: &str,
+) -> Result<Post, ApiError> {
+    let url = format!("{}/posts/{}", API_URL, id);
+    let response = Request::put(&url)
+        .header("Authorization", &format!("Bearer {}", token))
+        .json(&PostRequest { title, content })?
+        .send()
+        .await?;
+    read_json(response).await
+}
+
+pub async fn delete_post(token: &str, id: &str) -> Result<(), ApiError> {
+    let url = format!("{}/posts/{}", API_URL, id);
+    let response = Request::delete(&url)
+        .header("Authorization", &format!("Bearer {}", token))
+        .send()
+        .await?;
+    check_status(response).await.map(|_| ())
+}
+
+async fn read_json<T: DeserializeOwned>(response: Response) -> Result<T, ApiError> {
+    check_status(response)
+        .await?
+        .json::<T>()
+        .await
+        .map_err(|e| ApiError::InvalidResponse(e.to_string()))
+}
+
+async fn check_status(response: Response) -> Result<Response, ApiError> {
+    if response.ok() {
+        return Ok(response);
+    }
+    if response.status() == 404 {
+        return Err(ApiError::NotFound);
+    }
+    match response.json::<ErrorResponse>().await {
+        Ok(data) => Err(ApiError::Rejected(data.error)),
+        Err(_) => Err(ApiError::Rejected(format!("status {}", response.status()))),
+    }
+}
+
+impl From<gloo_net::Error> for ApiError {
+    fn from(e: gloo_net::Error) -> Self {
+        ApiError::Request(e.to_string())
+    }
+}
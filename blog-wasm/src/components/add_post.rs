@@ -1,18 +1,10 @@
-use crate::components::error::Error;
+use crate::api::{self, ApiError};
 use crate::route::Route;
 use crate::token_storage::TokenStorage;
-use gloo_net::http::Request;
-use serde::Serialize;
 use web_sys::HtmlInputElement;
 use yew::prelude::*;
 use yew_router::prelude::use_navigator;
 
-#[derive(Serialize)]
-struct CreatePostRequest {
-    title: String,
-    content: String,
-}
-
 #[component(CreatePost)]
 pub fn create_post() -> Html {
     let title = use_state(String::new);
@@ -58,33 +50,15 @@ pub fn create_post() -> Html {
 
             wasm_bindgen_futures::spawn_local(async move {
                 let token = TokenStorage::get_token().unwrap_or_default();
-                let update_data = CreatePostRequest {
-                    title: title_val,
-                    content: content_val,
-                };
-
-                let url = "http://localhost:3000/api/posts";
-                let resp = Request::post(url)
-                    .header("Authorization", &format!("Bearer {}", token))
-                    .json(&update_data)
-                    .unwrap()
-                    .send()
-                    .await;
-
-                match resp {
-                    Ok(r) if r.ok() => {
+                match api::create_post(&token, &title_val, &content_val).await {
+                    Ok(_) => {
                         navigator.push(&Route::Home);
                     }
-                    Ok(r) => match r.json::<Error>().await {
-                        Ok(data) => {
-                            error.set(Some(format!("Create failed: {}", data.message())));
-                        }
-                        Err(_) => {
-                            error.set(Some(format!("Create failed with status: {}", r.status())));
-                        }
-                    },
+                    Err(ApiError::Rejected(message)) => {
+                        error.set(Some(format!("Create failed: {}", message)));
+                    }
                     Err(e) => {
-                        error.set(Some(format!("Request failed: {}", e)));
+                        error.set(Some(e.to_string()));
                     }
                 }
                 saving.set(false);
@@ -1,19 +1,10 @@
-use crate::components::PostDetailInfo;
-use crate::components::error::Error;
+use crate::api::{self, ApiError};
 use crate::route::Route;
 use crate::token_storage::TokenStorage;
-use gloo_net::http::Request;
-use serde::Serialize;
 use web_sys::HtmlInputElement;
 use yew::prelude::*;
 use yew_router::prelude::use_navigator;
 
-#[derive(Serialize)]
-struct UpdatePostRequest {
-    title: String,
-    content: String,
-}
-
 #[derive(Properties, PartialEq)]
 pub struct EditPostProps {
     pub id: String,
@@ -41,23 +32,16 @@ pub fn edit_post(props: &EditPostProps) -> Html {
             let error = error.clone();
             let id = id.clone();
             wasm_bindgen_futures::spawn_local(async move {
-                let url = format!("http://localhost:3000/api/posts/{}", id);
-                let resp = Request::get(&url).send().await;
-                match resp {
-                    Ok(r) if r.status() == 404 => {
+                match api::get_post(&id).await {
+                    Ok(data) => {
+                        title.set(data.title);
+                        content.set(data.content);
+                    }
+                    Err(ApiError::NotFound) => {
                         error.set(Some(format!("Post with id {} not found", id)));
                     }
-                    Ok(r) => match r.json::<PostDetailInfo>().await {
-                        Ok(data) => {
-                            title.set(data.title);
-                            content.set(data.content);
-                        }
-                        Err(e) => {
-                            error.set(Some(format!("Failed to parse response: {}", e)));
-                        }
-                    },
                     Err(e) => {
-                        error.set(Some(format!("Request failed: {}", e)));
+                        error.set(Some(e.to_string()));
                     }
                 }
                 loading.set(false);
@@ -104,33 +88,15 @@ pub fn edit_post(props: &EditPostProps) -> Html {
 
             wasm_bindgen_futures::spawn_local(async move {
                 let token = TokenStorage::get_token().unwrap_or_default();
-                let update_data = UpdatePostRequest {
-                    title: title_val,
-                    content: content_val,
-                };
-
-                let url = format!("http://localhost:3000/api/posts/{}", id);
-                let resp = Request::put(&url)
-                    .header("Authorization", &format!("Bearer {}", token))
-                    .json(&update_data)
-                    .unwrap()
-                    .send()
-                    .await;
-
-                match resp {
-                    Ok(r) if r.ok() => {
+                match api::update_post(&token, &id, &title_val, &content_val).await {
+                    Ok(_) => {
                         navigator.push(&Route::Home);
                     }
-                    Ok(r) => match r.json::<Error>().await {
-                        Ok(data) => {
-                            error.set(Some(format!("Update failed: {}", data.message())));
-                        }
-                        Err(_) => {
-                            error.set(Some(format!("Update failed with status: {}", r.status())));
-                        }
-                    },
+                    Err(ApiError::Rejected(message)) => {
+                        error.set(Some(format!("Update failed: {}", message)));
+                    }
                     Err(e) => {
-                        error.set(Some(format!("Request failed: {}", e)));
+                        error.set(Some(e.to_string()));
                     }
                 }
                 saving.set(false);
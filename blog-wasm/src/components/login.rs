@@ -13,19 +13,58 @@ struct LoginRequest {
     password: String,
 }
 
+#[derive(Serialize)]
+struct VerifyTwoFactorRequest {
+    challenge_token: String,
+    code: String,
+}
+
 #[derive(Deserialize)]
 struct LoginResponse {
-    token: String,
+    token: Option<String>,
+    challenge_token: Option<String>,
 }
 
 #[component(Login)]
 pub fn login() -> Html {
     let username = use_state(String::new);
     let password = use_state(String::new);
+    let code = use_state(String::new);
+    let challenge_token = use_state(|| Option::<String>::None);
     let error = use_state(|| Option::<String>::None);
     let loading = use_state(|| false);
     let navigator = use_navigator().unwrap();
 
+    // Single sign-on redirects back here with the outcome in the URL fragment.
+    {
+        let error = error.clone();
+        let challenge_token = challenge_token.clone();
+        let navigator = navigator.clone();
+        use_effect_with((), move |_| {
+            if let Some(location) = web_sys::window().map(|window| window.location()) {
+                let hash = location.hash().unwrap_or_default();
+                if let Some(token) = hash.strip_prefix("#token=") {
+                    let _ = location.set_hash("");
+                    if let Err(e) = TokenStorage::set_token(token.to_string()) {
+                        error.set(Some(format!("Failed to save token: {}", e)));
+                    } else {
+                        navigator.push(&Route::Home);
+                    }
+                } else if let Some(token) = hash.strip_prefix("#challenge=") {
+                    let _ = location.set_hash("");
+                    challenge_token.set(Some(token.to_string()));
+                } else if hash == "#linked" {
+                    let _ = location.set_hash("");
+                    navigator.push(&Route::Home);
+                } else if let Some(code) = hash.strip_prefix("#error=") {
+                    let _ = location.set_hash("");
+                    error.set(Some(sso_error_message(code)));
+                }
+            }
+            || ()
+        });
+    }
+
     let on_username_input = {
         let username = username.clone();
         Callback::from(move |e: InputEvent| {
@@ -42,9 +81,19 @@ pub fn login() -> Html {
         })
     };
 
+    let on_code_input = {
+        let code = code.clone();
+        Callback::from(move |e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            code.set(input.value());
+        })
+    };
+
     let on_submit = {
         let username = username.clone();
         let password = password.clone();
+        let code = code.clone();
+        let challenge_token = challenge_token.clone();
         let error = error.clone();
         let loading = loading.clone();
         let navigator = navigator.clone();
@@ -53,6 +102,9 @@ pub fn login() -> Html {
             e.prevent_default();
             let username_val = (*username).clone();
             let password_val = (*password).clone();
+            let code_val = (*code).clone();
+            let challenge_token_val = (*challenge_token).clone();
+            let challenge_token = challenge_token.clone();
             let error = error.clone();
             let loading = loading.clone();
             let navigator = navigator.clone();
@@ -61,30 +113,59 @@ pub fn login() -> Html {
             error.set(None);
 
             wasm_bindgen_futures::spawn_local(async move {
-                let login_data = LoginRequest {
-                    username: username_val,
-                    password: password_val,
+                let is_two_factor_step = challenge_token_val.is_some();
+                let resp = match challenge_token_val {
+                    None => {
+                        let login_data = LoginRequest {
+                            username: username_val,
+                            password: password_val,
+                        };
+                        Request::post("http://localhost:3000/api/auth/login")
+                            .json(&login_data)
+                            .unwrap()
+                            .send()
+                            .await
+                    }
+                    Some(challenge_token) => {
+                        let verify_data = VerifyTwoFactorRequest {
+                            challenge_token,
+                            code: code_val,
+                        };
+                        Request::post("http://localhost:3000/api/auth/2fa/verify")
+                            .json(&verify_data)
+                            .unwrap()
+                            .send()
+                            .await
+                    }
                 };
 
-                let resp = Request::post("http://localhost:3000/api/auth/login")
-                    .json(&login_data)
-                    .unwrap()
-                    .send()
-                    .await;
-
                 match resp {
                     Ok(r) if r.ok() => match r.json::<LoginResponse>().await {
-                        Ok(data) => {
-                            if let Err(e) = TokenStorage::set_token(data.token) {
+                        Ok(LoginResponse {
+                            challenge_token: Some(token),
+                            ..
+                        }) => {
+                            challenge_token.set(Some(token));
+                        }
+                        Ok(LoginResponse {
+                            token: Some(token), ..
+                        }) => {
+                            if let Err(e) = TokenStorage::set_token(token) {
                                 error.set(Some(format!("Failed to save token: {}", e)));
                             } else {
                                 navigator.push(&Route::Home);
                             }
                         }
+                        Ok(_) => {
+                            error.set(Some("Failed to parse response: missing token".to_string()));
+                        }
                         Err(e) => {
                             error.set(Some(format!("Failed to parse response: {}", e)));
                         }
                     },
+                    Ok(r) if r.status() == 401 && is_two_factor_step => {
+                        error.set(Some("Invalid or expired two-factor code".to_string()));
+                    }
                     Ok(r) if r.status() == 401 => {
                         error.set(Some("Invalid username or password".to_string()));
                     }
@@ -120,6 +201,21 @@ pub fn login() -> Html {
                                 </div>
                             }
                             <form onsubmit={on_submit}>
+                                if challenge_token.is_some() {
+                                <div class="mb-3">
+                                    <label for="code" class="form-label">{"Two-factor code"}</label>
+                                    <input
+                                        type="text"
+                                        class="form-control"
+                                        id="code"
+                                        autocomplete="one-time-code"
+                                        placeholder="123456 or recovery code"
+                                        value={(*code).clone()}
+                                        oninput={on_code_input}
+                                        required=true
+                                    />
+                                </div>
+                                } else {
                                 <div class="mb-3">
                                     <label for="username" class="form-label">{"Username"}</label>
                                     <input
@@ -142,6 +238,7 @@ pub fn login() -> Html {
                                         required=true
                                     />
                                 </div>
+                                }
                                 <div class="d-grid gap-2">
                                     <button
                                         type="submit"
@@ -151,10 +248,20 @@ pub fn login() -> Html {
                                         if *loading {
                                             <span class="spinner-border spinner-border-sm me-2" role="status" aria-hidden="true"></span>
                                         }
-                                        {"Login"}
+                                        if challenge_token.is_some() { {"Verify"} } else { {"Login"} }
                                     </button>
                                 </div>
                             </form>
+                            if challenge_token.is_none() {
+                                <div class="d-grid gap-2 mt-3">
+                                    <a
+                                        class="btn btn-outline-secondary"
+                                        href="http://localhost:3000/api/auth/oidc/login"
+                                    >
+                                        {"Sign in with SSO"}
+                                    </a>
+                                </div>
+                            }
                         </div>
                         <div class="card-footer text-center">
                             <p class="mb-0">{"Don't have an account? "} <Link<Route> to={Route::Register}>{"Sign Up"}</Link<Route>></p>
@@ -165,3 +272,27 @@ pub fn login() -> Html {
         </div>
     }
 }
+
+fn sso_error_message(code: &str) -> String {
+    match code {
+        "email_required" => {
+            "Single sign-on failed: the identity provider did not share a valid email".to_string()
+        }
+        "email_not_verified" => {
+            "Single sign-on failed: verify your email with the identity provider first".to_string()
+        }
+        "account_exists" => {
+            "An account with this email already exists: log in and link single sign-on from it"
+                .to_string()
+        }
+        "identity_in_use" => {
+            "Single sign-on failed: this identity is linked to another account".to_string()
+        }
+        "conflict" => {
+            "Single sign-on failed: the account changed meanwhile, please try again".to_string()
+        }
+        "invalid_state" => "Single sign-on session expired, please try again".to_string(),
+        "access_denied" => "Single sign-on was cancelled".to_string(),
+        _ => format!("Single sign-on failed: {}", code),
+    }
+}
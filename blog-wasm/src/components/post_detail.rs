@@ -1,18 +1,9 @@
+use crate::api::{self, ApiError, Post};
+use crate::post_events::use_post_events;
 use crate::route::Route;
-use gloo_net::http::Request;
 use yew::prelude::*;
 use yew_router::prelude::Link;
 
-#[derive(Clone, PartialEq, Debug, serde::Deserialize)]
-pub struct PostDetailInfo {
-    pub id: String,
-    pub title: String,
-    pub content: String,
-    pub user_id: String,
-    pub created_at: String,
-    pub updated_at: String,
-}
-
 #[derive(Properties, PartialEq)]
 pub struct PostDetailProps {
     pub id: String,
@@ -20,37 +11,34 @@ pub struct PostDetailProps {
 
 #[component(PostDetail)]
 pub fn post_detail(props: &PostDetailProps) -> Html {
-    let post = use_state(|| Option::<PostDetailInfo>::None);
+    let post = use_state(|| Option::<Post>::None);
     let loading = use_state(|| true);
     let error = use_state(|| Option::<String>::None);
+    // Reload when another user changes this post.
+    let post_event = use_post_events()
+        .filter(|event| event.affects(&props.id))
+        .map(|event| event.sequence);
 
     {
         let post = post.clone();
         let loading = loading.clone();
         let error = error.clone();
         let id = props.id.clone();
-        use_effect_with(id.clone(), move |id| {
+        use_effect_with((id.clone(), post_event), move |(id, _)| {
             let post = post.clone();
             let loading = loading.clone();
             let error = error.clone();
             let id = id.clone();
             wasm_bindgen_futures::spawn_local(async move {
-                let url = format!("http://localhost:3000/api/posts/{}", id);
-                let resp = Request::get(&url).send().await;
-                match resp {
-                    Ok(r) if r.status() == 404 => {
+                match api::get_post(&id).await {
+                    Ok(data) => {
+                        post.set(Some(data));
+                    }
+                    Err(ApiError::NotFound) => {
                         error.set(Some(format!("Post with id {} not found", id)));
                     }
-                    Ok(r) => match r.json::<PostDetailInfo>().await {
-                        Ok(data) => {
-                            post.set(Some(data));
-                        }
-                        Err(e) => {
-                            error.set(Some(format!("Failed to parse response: {}", e)));
-                        }
-                    },
                     Err(e) => {
-                        error.set(Some(format!("Request failed: {}", e)));
+                        error.set(Some(e.to_string()));
                     }
                 }
                 loading.set(false);
@@ -80,9 +68,9 @@ pub fn post_detail(props: &PostDetailProps) -> Html {
                         <p class="card-text" style="white-space: pre-wrap;">{&p.content}</p>
                     </div>
                     <div class="card-footer text-muted">
-                        <div>{format!("Author ID: {}", p.user_id)}</div>
-                        <div>{format!("Created: {}", p.created_at)}</div>
-                        <div>{format!("Updated: {}", p.updated_at)}</div>
+                        <div>{format!("Author ID: {}", p.author_id)}</div>
+                        <div>{format!("Created: {}", api::format_timestamp(&p.created_at))}</div>
+                        <div>{format!("Updated: {}", api::format_timestamp(&p.updated_at))}</div>
                     </div>
                 </div>
             }
@@ -1,25 +1,10 @@
-use crate::components::error::Error;
+use crate::api::{self, ApiError, Post};
+use crate::post_events::use_post_events;
 use crate::route::Route;
 use crate::token_storage::TokenStorage;
-use gloo_net::http::Request;
 use yew::prelude::*;
 use yew_router::prelude::Link;
 
-#[derive(Clone, PartialEq, Debug, serde::Deserialize)]
-pub struct Post {
-    pub id: String,
-    pub title: String,
-    pub content: String,
-}
-
-#[derive(Clone, PartialEq, Debug, serde::Deserialize)]
-struct PostsResponse {
-    posts: Vec<Post>,
-    total: usize,
-    limit: usize,
-    offset: usize,
-}
-
 #[derive(Properties, PartialEq)]
 pub struct PostsListProps {
     #[prop_or_default]
@@ -37,6 +22,7 @@ pub fn posts_list(props: &PostsListProps) -> Html {
     let is_logged_in = use_state(TokenStorage::is_logged_in);
     let refresh_trigger = use_state(|| 0);
     let parent_refresh = props.refresh_version;
+    let post_event = use_post_events();
 
     {
         let posts = posts.clone();
@@ -48,8 +34,14 @@ pub fn posts_list(props: &PostsListProps) -> Html {
         let refresh_trigger = refresh_trigger.clone();
         let is_logged_in = is_logged_in.clone();
         use_effect_with(
-            (*limit, *offset, *refresh_trigger, parent_refresh),
-            move |(limit, offset, _, _)| {
+            (
+                *limit,
+                *offset,
+                *refresh_trigger,
+                parent_refresh,
+                post_event,
+            ),
+            move |(limit, offset, _, _, _)| {
                 let l = *limit;
                 let o = *offset;
                 let posts = posts.clone();
@@ -60,20 +52,13 @@ pub fn posts_list(props: &PostsListProps) -> Html {
                 error.set(None);
                 is_logged_in.set(TokenStorage::is_logged_in());
                 wasm_bindgen_futures::spawn_local(async move {
-                    let url = format!("http://localhost:3000/api/posts?limit={}&offset={}", l, o);
-                    let resp = Request::get(&url).send().await;
-                    match resp {
-                        Ok(r) => match r.json::<PostsResponse>().await {
-                            Ok(data) => {
-                                posts.set(data.posts);
-                                total.set(data.total);
-                            }
-                            Err(e) => {
-                                error.set(Some(format!("Failed to parse response: {}", e)));
-                            }
-                        },
+                    match api::list_posts(l, o).await {
+                        Ok(page) => {
+                            posts.set(page.posts);
+                            total.set(page.total);
+                        }
                         Err(e) => {
-                            error.set(Some(format!("Request failed: {}", e)));
+                            error.set(Some(e.to_string()));
                         }
                     }
                     loading.set(false);
@@ -116,26 +101,15 @@ pub fn posts_list(props: &PostsListProps) -> Html {
 
             wasm_bindgen_futures::spawn_local(async move {
                 loading.set(true);
-                let url = format!("http://localhost:3000/api/posts/{}", id);
-                let resp = Request::delete(&url)
-                    .header("Authorization", &format!("Bearer {}", token))
-                    .send()
-                    .await;
-
-                match resp {
-                    Ok(r) if r.ok() => {
+                match api::delete_post(&token, &id).await {
+                    Ok(()) => {
                         refresh_trigger.set(*refresh_trigger + 1);
                     }
-                    Ok(r) => match r.json::<Error>().await {
-                        Ok(data) => {
-                            error.set(Some(format!("Delete failed: {}", data.message())));
-                        }
-                        Err(_) => {
-                            error.set(Some(format!("Delete failed with status: {}", r.status())));
-                        }
-                    },
+                    Err(ApiError::Rejected(message)) => {
+                        error.set(Some(format!("Delete failed: {}", message)));
+                    }
                     Err(e) => {
-                        error.set(Some(format!("Request failed: {}", e)));
+                        error.set(Some(e.to_string()));
                     }
                 }
                 loading.set(false);
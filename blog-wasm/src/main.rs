@@ -1,4 +1,6 @@
+mod api;
 pub mod components;
+mod post_events;
 mod route;
 mod token_storage;
 
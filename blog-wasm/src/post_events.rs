@@ -0,0 +1,84 @@
+use futures::StreamExt;
+use futures::stream::select_all;
+use gloo_net::eventsource::futures::EventSource;
+use yew::prelude::*;
+
+const EVENTS_URL: &str = "http://localhost:3000/api/events";
+const EVENT_TYPES: [&str; 4] = ["created", "updated", "deleted", "lagged"];
+
+/// A post change pushed by the server.
+#[derive(Clone, PartialEq, Debug)]
+pub struct PostEvent {
+    /// Grows with every received event, so repeated changes of one post still re-run effects.
+    pub sequence: usize,
+    /// The changed post, or `None` when events were missed and everything should be reloaded.
+    pub post_id: Option<String>,
+}
+
+impl PostEvent {
+    pub fn affects(&self, id: &str) -> bool {
+        self.post_id.as_deref().is_none_or(|post_id| post_id == id)
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct EventData {
+    id: String,
+}
+
+/// Subscribes to the server's post events while the component is mounted and returns the latest one.
+#[hook]
+pub fn use_post_events() -> Option<PostEvent> {
+    let latest = use_state(|| Option::<PostEvent>::None);
+
+    {
+        let latest = latest.clone();
+        use_effect_with((), move |_| {
+            let event_source = match connect(latest) {
+                Ok(event_source) => Some(event_source),
+                Err(e) => {
+                    log::error!("Failed to subscribe to post events: {}", e);
+                    None
+                }
+            };
+            // Dropping the event source closes the connection.
+            move || drop(event_source)
+        });
+    }
+
+    (*latest).clone()
+}
+
+fn connect(latest: UseStateHandle<Option<PostEvent>>) -> Result<EventSource, String> {
+    let mut event_source = EventSource::new(EVENTS_URL).map_err(|e| e.to_string())?;
+    let mut subscriptions = Vec::with_capacity(EVENT_TYPES.len());
+    for event_type in EVENT_TYPES {
+        subscriptions.push(
+            event_source
+                .subscribe(event_type)
+                .map_err(|e| e.to_string())?,
+        );
+    }
+
+    wasm_bindgen_futures::spawn_local(async move {
+        let mut events = select_all(subscriptions);
+        let mut sequence = 0;
+        while let Some(event) = events.next().await {
+            match event {
+                Ok((_, message)) => {
+                    sequence += 1;
+                    let post_id = message
+                        .data()
+                        .as_string()
+                        .and_then(|data| serde_json::from_str::<EventData>(&data).ok())
+                        .map(|data| data.id);
+                    latest.set(Some(PostEvent { sequence, post_id }));
+                }
+                // The browser reconnects on its own; events sent meanwhile are lost.
+                Err(e) => log::warn!("Post events connection error: {}", e),
+            }
+        }
+    });
+
+    Ok(event_source)
+}
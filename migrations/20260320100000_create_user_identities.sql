@@ -0,0 +1,11 @@
+CREATE TABLE user_identities(
+    id              uuid PRIMARY KEY,
+    user_id         uuid NOT NULL,
+    issuer          TEXT NOT NULL,
+    subject         TEXT NOT NULL,
+    created_at      TIMESTAMPTZ NOT NULL,
+    UNIQUE (issuer, subject),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE INDEX user_identities_user_id_idx ON user_identities(user_id);